@@ -0,0 +1,480 @@
+## Commands
+
+commands-title = These commands are supported:
+command-help = display this text
+command-start = starts club
+command-event = create new event, optionally in a named track
+command-suggest = make new suggestion
+command-achieve = achieves active event
+command-review = adds a short review to your rating of the last event
+command-pick = picks a subject for active event, /pick under 150 skips longer ones
+command-current = current event info
+command-where = where the event takes place, /where Cafe Central or reply to a location to set it
+command-ics = sends the active event as a calendar file, e.g. /ics books
+command-calendar = shows the link to follow club events in a calendar, /calendar reset makes a new one
+command-progress = shares how far along you are, e.g. /progress 45% or /progress p.120
+command-spoiler = posts a hidden spoiler, e.g. /spoiler 12 text or /spoiler 45% text
+command-question = adds a discussion question, /question anon text hides your name
+command-insights = turns insights on/off for current event
+command-startclub = starts current event, posts the questions and the insights summary link
+command-log = shows last club changes, e.g. /log 20
+command-hosts = manages the host rotation: /hosts add, /hosts remove or /hosts list
+command-swaphost = hands the event to another host, reply to their message with /swaphost
+command-history = shows past events with their ratings, e.g. /history 20
+command-stats = shows best rated picks, the harshest critic and suggesters' averages
+command-settings = club settings, also /settings timezone Europe/Berlin, /settings limit 3 or /settings kind films
+
+## Replies
+
+start-done = You're all set up! Now you can create event for your club
+start-already = You're already started a club
+event-usage =
+    Please write a date in format -
+    /event 2023.07.16 15:00
+    or add a track name -
+    /event books 2023.07.16 15:00
+    and a place or a call link after the date, if you like -
+    /event 2023.07.16 15:00 Cafe Central, Herrengasse 14
+event-noun = { $track ->
+    [main] club event
+   *[other] { $track } event
+}
+event-created = New { $noun } created on { $date }
+event-created-host =
+    { $created }
+    Host: { $host }
+event-created-location =
+    { $created }
+    Place: { $location }
+location-none = No place is set for the { $noun } on { $date } yet, add one with /where Cafe Central or reply /where to a shared location
+location-current = The { $noun } on { $date } takes place at { $location }
+location-set = The { $noun } on { $date } now takes place at { $location }
+location-venue = { $noun } on { $date }
+suggest-empty =
+    Your suggestion is empty ;(
+    Format - /suggest smth
+suggest-done =
+    Got it. Your suggestion:
+    { $suggestion }
+suggest-choose =
+    Several { $kind ->
+        [films] films
+        [games] games
+        [generic] works
+       *[books] books
+    } match "{ $suggestion }", which one did you mean?
+suggest-choose-none = None of these
+work-title = { $title } by { $author }
+work-title-director = { $title }, directed by { $director }
+work-identifier = { $kind ->
+    [isbn] ISBN
+    [goodreads] Goodreads
+   *[imdb] IMDb
+} { $value }
+work-year = { $kind ->
+    [films] Released in { $year }
+    [games] Released in { $year }
+   *[other] Published in { $year }
+}
+work-pages = { $pages ->
+    [one] { $pages } page
+   *[other] { $pages } pages
+}
+work-runtime = { $kind ->
+    [games] { $minutes } min to play
+   *[other] { $minutes } min
+}
+work-cover = Cover: { $url }
+insights-subject-picked = Unable to toggle insights because subject is already picked
+insights-on = Turned on insights for current event
+insights-off = Turned off insights for current event
+start-club-summary =
+    Here is your [insights summary]({ $link })\.
+    Have a great club\!
+start-club-summary-expiring =
+    Here is your [insights summary]({ $link }), it's available until { $date }\.
+    Have a great club\!
+start-club-rejected = Insights couldn't start the event: { $reason }
+start-club-pending = The event is started, insights are unavailable right now so the summary link will be sent here later
+start-club-no-questions = The event has started, no discussion questions were submitted
+achieve-done = Ok, event on { $date } is achieved
+rating-round = How was { $subject }? Rate it from 1 to 5 stars, and add a short review with /review if you like
+rating-saved = You gave { $subject } { $stars }★
+review-usage =
+    Write your review after the command:
+    /review Slow start, great ending
+review-saved = Thanks, your review of { $subject } is saved
+pick-headline = { $mode ->
+    [ranked] The most suggested is
+    [poll] The poll picked
+   *[random] Randomly picked
+}
+pick-result =
+    { $headline }
+    { $subject }
+pick-result-insights =
+    { $headline }
+    { $subject }
+
+    And here is your [insights link]({ $link })
+pick-result-insights-pending =
+    { $headline }
+    { $subject }
+
+    Insights are unavailable right now, the link will be sent here later
+pick-result-insights-rejected =
+    { $headline }
+    { $subject }
+
+    Insights couldn't register the event: { $reason }
+insights-link-attached = Insights are back, here is the insights link for { $subject }: { $link }
+insights-summary-attached = Insights are back, here is your insights summary: { $link }
+insights-rejected = Insights couldn't { $call ->
+    [register] register
+    [start] start
+   *[finish] finish
+} the event: { $reason }
+insights-summary-ready = The insights summary for { $subject } is ready: { $link }
+insights-submitted = { $count ->
+    [one] { $count } insight was submitted for { $subject }
+   *[other] { $count } insights were submitted for { $subject }
+}
+pick-poll-question = { $kind ->
+    [books] Which book should the next { $noun } be about?
+    [films] Which film should we watch at the next { $noun }?
+    [games] Which game should we play at the next { $noun }?
+   *[generic] What should the next { $noun } be about?
+}
+pick-filter-usage =
+    Please add a number to the filter -
+    /pick under 150
+pick-poll-started = Vote in the poll and hit /pick again to close it
+pick-unknown-skipped = { $count ->
+    [one] { $count } suggestion with an unknown { $kind ->
+        [books] page count
+       *[other] runtime
+    } was left out
+   *[other] { $count } suggestions with an unknown { $kind ->
+        [books] page count
+       *[other] runtime
+    } were left out
+}
+current-not-picked =
+    The next { $noun } is on { $date }\.
+    The subject hasn't been picked yet
+current-picked =
+    The next { $noun } is on { $date }\.
+    The subject is \- { $subject }
+current-insights =
+    { $message }
+    Here is the [insights link]({ $link })
+current-insights-pending =
+    { $message }
+    The insights link will be sent once insights are available
+current-progress =
+    { $message }
+
+    { $lines }
+current-host =
+    { $message }
+    Host: { $host }
+current-location =
+    { $message }
+    Place: { $location }
+progress-usage =
+    Tell how far along you are:
+    /progress 45%
+    /progress p.120
+progress-saved = { $name } is { $percent }% through { $subject }
+progress-saved-page = { $name } is on page { $page } of { $pages } of { $subject }, { $percent }%
+progress-line = { $bar } { $percent }% { $name }
+checkin = Weekly check-in before the next { $noun }: how far along are you with { $subject }? Tap below or send /progress 45% or /progress p.120
+checkin-progress =
+    { $checkin }
+
+    { $lines }
+reminder = Reminder: the next { $noun } is in { $offset }, on { $date }
+reminder-not-picked =
+    { $reminder }
+    The subject hasn't been picked yet, /suggest something
+reminder-subject =
+    { $reminder }
+    The subject is { $subject }
+reminder-behind =
+    { $reminder }
+    Still catching up: { $members }
+reminder-host =
+    { $reminder }
+    Host: { $host }
+reminder-location =
+    { $reminder }
+    Place: { $location }
+question-usage =
+    Write the question after the command:
+    /question What would you have done in their place?
+    /question anon Did anyone else find the ending rushed?
+question-saved = Question added, { $count } so far. They are shuffled and posted at /startclub
+question-saved-anonymous = Anonymous question added, { $count } so far. They are shuffled and posted at /startclub
+questions-title =
+    Discussion questions \({ $count }\), tap a number once it's covered:
+    { $lines }
+question-line = { $number }\. { $question } — { $name }
+question-line-anonymous = { $number }\. { $question }
+spoiler-usage =
+    Tell how far the spoiler goes, then write it:
+    /spoiler 12 the twist in chapter twelve
+    /spoiler 45% what happens halfway
+    /spoiler p.120 the scene on page 120
+discussion-topic = Spoilers: { $subject }
+discussion-announcement = Discussion of { $subject } goes here, spoilers stay hidden until tapped
+spoiler-label-chapter = chapter { $chapter }
+spoiler-label-percent = { $percent }%
+spoiler-label-page = page { $page }
+spoiler-warning = Only open it once you've reached { $label }
+spoiler-behind = { $members }, you haven't reached { $label } yet, don't open it
+spoiler-post =
+    Spoiler up to { $label } from { $name }
+    { $warning }
+    ||{ $text }||
+log-empty = Nothing happened in this club yet
+log-title =
+    Last { $count } changes:
+    { $lines }
+log-failed = Unable to get club log
+log-line = { $date } - user { $user } { $action }
+member-fallback = user { $user }
+history-empty = No events have been achieved yet
+history-failed = Unable to get the club history
+history-title =
+    Past events ({ $count }):
+    { $lines }
+history-line = { $date } - { $subject } - { $rating }
+history-rating = { $average }★ from { $count ->
+    [one] { $count } rating
+   *[other] { $count } ratings
+}
+history-not-rated = not rated
+history-review = — { $name }, { $stars }★: { $review }
+stats-empty = No ratings yet, they are collected after /achieve
+stats-failed = Unable to get the club stats
+stats-title = Club stats
+stats-best =
+    Best rated picks:
+    { $lines }
+stats-best-line = { $subject }: { $average }★ ({ $count })
+stats-critic = Harshest critic: { $name }, { $average }★ on average over { $count ->
+    [one] { $count } rating
+   *[other] { $count } ratings
+}
+stats-suggesters =
+    Suggesters' average rating:
+    { $lines }
+stats-suggester-line = { $name }: { $average }★ for { $picks ->
+    [one] { $picks } pick
+   *[other] { $picks } picks
+}
+hosts-empty = Nobody is in the host rotation yet, join it with /hosts add
+hosts-title =
+    Host rotation:
+    { $lines }
+hosts-line = { $number }. { $name }
+hosts-line-next = { $number }. { $name } - next
+hosts-usage =
+    Format -
+    /hosts to see the rotation
+    /hosts add to join it, or reply to someone's message to add them
+    /hosts remove to leave it, /hosts remove 2 to remove the second host
+host-added = { $name } joined the host rotation as number { $number }
+host-removed = { $name } left the host rotation
+host-swapped = { $name } now hosts the { $noun } on { $date }
+hosts-admins-only = Only chat admins can change the rotation for someone else
+swaphost-usage = Reply to the new host's message with /swaphost, or add a track name - /swaphost books
+host-only = Only { $host } or a chat admin can do this while host-only is on
+calendar-name = Club events
+calendar-summary = { $subject } - { $noun }
+calendar-summary-not-picked = { $noun } - subject not picked
+calendar-host = Host: { $host }
+calendar-insights = Insights: { $link }
+calendar-link =
+    Follow club events in your calendar app with this link:
+    { $link }
+    Anyone who has it can see the events, so keep it in the club
+calendar-reset =
+    The old calendar link no longer works, here is the new one:
+    { $link }
+calendar-usage =
+    Format -
+    /calendar to get the link
+    /calendar reset to replace it
+calendar-admins-only = Only chat admins can replace the calendar link
+
+## Audit log actions
+
+audit-club-registered = started the club
+audit-event-created = created an event on { $date }
+audit-track-event-created = created a { $track } event on { $date }
+audit-suggestion-added = suggested { $suggestion }
+audit-insights-on = turned insights on
+audit-insights-off = turned insights off
+audit-subject-picked = picked { $subject }
+audit-event-started = started the event
+audit-event-achieved = achieved the event
+audit-settings-changed = changed club settings
+audit-host-added = added { $name } to the host rotation
+audit-host-removed = removed { $name } from the host rotation
+audit-host-swapped = handed the event to { $name }
+audit-location-changed = moved the event to { $location }
+audit-rating-given = rated { $subject } { $stars }★
+audit-review-added = reviewed { $subject }
+audit-question-asked = asked a question
+audit-question-covered = marked question { $number } as covered
+audit-question-uncovered = unmarked question { $number }
+audit-calendar-created = made the calendar link
+audit-calendar-reset = reset the calendar link
+audit-progress-marked = marked { $percent }% read
+audit-poll-started = started a poll to pick the subject
+audit-work-chosen = matched a suggestion to { $title }
+audit-discussion-opened = opened the discussion thread
+
+## Settings
+
+settings-title = Club settings
+settings-usage =
+    Format -
+    /settings
+    /settings timezone Europe/Berlin
+    /settings limit 3
+    /settings kind books, films, games or generic
+settings-admins-only = Only chat admins can change settings
+settings-failed = Unable to save settings, did you /start the club?
+settings-timezone-hint = Use /settings timezone Europe/Berlin to change the time zone
+settings-reminders-hint = Tap an option below to turn the reminder on or off
+settings-kind = Club picks: { $kind ->
+    [books] books
+    [films] films
+    [games] board games
+   *[generic] anything
+}
+settings-insights = Insights by default: { $enabled ->
+    [true] on
+   *[false] off
+}
+settings-pick-mode = Pick mode: { $mode ->
+    [poll] poll
+    [ranked] most suggested
+   *[random] random
+}
+settings-limit = Suggestions per member: { $limit ->
+    [0] no limit
+   *[other] { $limit }
+}
+settings-host-only = Only the host picks and achieves: { $enabled ->
+    [true] on
+   *[false] off
+}
+settings-language = Language: { $language ->
+    [en] English
+    [ru] Русский
+   *[auto] auto
+}
+settings-time-zone = Time zone: { $tz }
+settings-reminders = Reminders before the event:
+
+## Durations
+
+duration-weeks = { $count ->
+    [one] { $count } week
+   *[other] { $count } weeks
+}
+duration-days = { $count ->
+    [one] { $count } day
+   *[other] { $count } days
+}
+duration-hours = { $count ->
+    [one] { $count } hour
+   *[other] { $count } hours
+}
+duration-minutes = { $count ->
+    [one] { $count } minute
+   *[other] { $count } minutes
+}
+duration-weeks-short = { $count }w
+duration-days-short = { $count }d
+duration-hours-short = { $count }h
+duration-minutes-short = { $count }m
+
+## Dates
+
+date-full = { $weekday }, { $day }{ $day ->
+    [1] st
+    [21] st
+    [31] st
+    [2] nd
+    [22] nd
+    [3] rd
+    [23] rd
+   *[other] th
+} of { $month } at { $time }
+weekday-1 = Monday
+weekday-2 = Tuesday
+weekday-3 = Wednesday
+weekday-4 = Thursday
+weekday-5 = Friday
+weekday-6 = Saturday
+weekday-7 = Sunday
+month-1 = January
+month-2 = February
+month-3 = March
+month-4 = April
+month-5 = May
+month-6 = June
+month-7 = July
+month-8 = August
+month-9 = September
+month-10 = October
+month-11 = November
+month-12 = December
+
+## Errors
+
+error-no-active-event = No active event found
+error-active-event-found = Already have an active event on { $date }
+error-no-suggestions = No suggestions found
+error-already-picked = Already picked { $subject }
+error-wrong-date-format = Wrong format, sorry
+error-event-in-past = Unfortunately, you can't go forward to the past
+error-no-active-event-in-track = No active event found in { $track } track
+error-track-required = There are several active tracks: { $tracks }; please add one to the command
+error-wrong-track-name = Track name should be a single word
+error-suggestion-limit = { $limit ->
+    [one] You've already made a suggestion for this event
+   *[other] You've already made { $limit } suggestions for this event
+}
+error-unknown-time-zone = Unknown time zone { $tz }
+error-wrong-setting-value = This value can't be used for the setting
+error-suggestion-not-found = This suggestion no longer exists
+error-not-your-suggestion = Only the member who made the suggestion can choose
+error-already-suggested = You've already suggested this for the event
+error-pick-filter-without-kind = Filters need a club kind, set one with /settings kind books
+error-no-suggestions-under = No suggestions are known to fit under { $limit } { $kind ->
+    [books] pages
+   *[other] minutes
+}
+error-subject-not-picked = The subject hasn't been picked yet, there's nothing to keep up with
+error-wrong-progress = Send a percentage or a page, e.g. /progress 45% or /progress p.120
+error-progress-pages-unknown = The page count of the pick is unknown, send a percentage instead, e.g. /progress 45%
+error-nothing-to-rate = There is no achieved event to rate
+error-rating-required = Rate { $subject } with the stars first, then add a review
+error-review-too-long = The review is too long, keep it under { $limit } characters
+error-questions-closed = The event has already started, questions are closed
+error-question-too-long = The question is too long, keep it under { $limit } characters
+error-question-limit = The event already has { $limit } questions, that's as many as one list fits
+error-question-not-found = This question is no longer there
+error-already-host = { $name } is already in the host rotation
+error-not-a-host = There's no such host in the rotation
+error-swap-not-allowed = Only the current host or a chat admin can hand the event over
+error-calendar-disabled = Calendar feeds aren't set up for this bot, /ics still sends the active event
+error-club-not-found = There's no club in this chat yet, use /start first
+error-unexpected = Something went wrong, please try again later
+error-host-only = Only the host or a chat admin can mark questions
+error-wrong-spoiler = Start with a chapter, a percentage or a page, e.g. /spoiler 12 text, /spoiler 45% text or /spoiler p.120 text
@@ -0,0 +1,481 @@
+## Commands
+
+commands-title = Доступные команды:
+command-help = показать этот текст
+command-start = зарегистрировать клуб
+command-event = создать встречу, можно в отдельном треке
+command-suggest = предложить тему
+command-achieve = завершить текущую встречу
+command-review = добавить короткий отзыв к своей оценке прошлой встречи
+command-pick = выбрать тему для текущей встречи, /pick under 150 пропустит более длинные
+command-current = информация о текущей встрече
+command-where = где проходит встреча, /where Кафе «Пушкин» или ответом на геопозицию, чтобы указать место
+command-ics = прислать текущую встречу файлом для календаря, например /ics books
+command-calendar = ссылка, чтобы следить за встречами клуба в календаре, /calendar reset создаст новую
+command-progress = отметить, сколько пройдено, например /progress 45% или /progress стр. 120
+command-spoiler = скрытый спойлер, например /spoiler 12 текст или /spoiler 45% текст
+command-question = добавить вопрос для обсуждения, /question анон текст скроет ваше имя
+command-insights = включить или выключить инсайты для текущей встречи
+command-startclub = начать встречу: опубликовать вопросы и получить ссылку на итоги инсайтов
+command-log = последние изменения в клубе, например /log 20
+command-hosts = очередь ведущих: /hosts add, /hosts remove или /hosts list
+command-swaphost = передать встречу другому ведущему, ответьте на его сообщение командой /swaphost
+command-history = прошедшие встречи и их оценки, например /history 20
+command-stats = лучшие темы, самый строгий критик и средние оценки авторов предложений
+command-settings = настройки клуба, а также /settings timezone Europe/Moscow, /settings limit 3 или /settings kind films
+
+## Replies
+
+start-done = Всё готово! Теперь можно создать встречу клуба
+start-already = Клуб уже зарегистрирован
+event-usage =
+    Укажите дату в формате
+    /event 2023.07.16 15:00
+    или добавьте название трека
+    /event books 2023.07.16 15:00
+    а после даты можно указать место или ссылку на звонок
+    /event 2023.07.16 15:00 Кафе «Пушкин», Тверской бульвар 26
+event-noun = { $track ->
+    [main] встреча клуба
+   *[other] встреча трека { $track }
+}
+event-created = Новая { $noun } назначена: { $date }
+event-created-host =
+    { $created }
+    Ведущий: { $host }
+event-created-location =
+    { $created }
+    Место: { $location }
+location-none = Место не указано · { $noun }, { $date }. Укажите его: /where Кафе «Пушкин» или ответом /where на геопозицию
+location-current = Место: { $location } · { $noun }, { $date }
+location-set = Новое место: { $location } · { $noun }, { $date }
+location-venue = { $noun }, { $date }
+suggest-empty =
+    Предложение пустое ;(
+    Формат: /suggest тема
+suggest-done =
+    Принято. Ваше предложение:
+    { $suggestion }
+suggest-choose =
+    Под «{ $suggestion }» подходят несколько { $kind ->
+        [films] фильмов
+        [games] игр
+        [generic] вариантов
+       *[books] книг
+    }, какой из них вы имели в виду?
+suggest-choose-none = Ни одну из этих
+work-title = { $title }, { $author }
+work-title-director = { $title }, реж. { $director }
+work-identifier = { $kind ->
+    [isbn] ISBN
+    [goodreads] Goodreads
+   *[imdb] IMDb
+} { $value }
+work-year = { $kind ->
+    [films] Вышел в { $year } году
+    [games] Вышла в { $year } году
+   *[other] Издана в { $year } году
+}
+work-pages = { $pages ->
+    [one] { $pages } страница
+    [few] { $pages } страницы
+   *[many] { $pages } страниц
+}
+work-runtime = { $kind ->
+    [games] партия на { $minutes } мин
+   *[other] { $minutes } мин
+}
+work-cover = Обложка: { $url }
+insights-subject-picked = Нельзя переключить инсайты, тема уже выбрана
+insights-on = Инсайты для текущей встречи включены
+insights-off = Инсайты для текущей встречи выключены
+start-club-summary =
+    Вот [итоги инсайтов]({ $link })\.
+    Хорошей встречи\!
+start-club-summary-expiring =
+    Вот [итоги инсайтов]({ $link }), они доступны до { $date }\.
+    Хорошей встречи\!
+start-club-rejected = Инсайты не смогли начать встречу: { $reason }
+start-club-pending = Встреча началась, инсайты сейчас недоступны, ссылка на итоги придёт сюда позже
+start-club-no-questions = Встреча началась, вопросов для обсуждения никто не прислал
+achieve-done = Готово, встреча завершена: { $date }
+rating-round = Как вам { $subject }? Оцените от 1 до 5 звёзд и, если хотите, добавьте короткий отзыв через /review
+rating-saved = Ваша оценка «{ $subject }»: { $stars }★
+review-usage =
+    Напишите отзыв после команды:
+    /review Медленное начало, отличный финал
+review-saved = Спасибо, отзыв о «{ $subject }» сохранён
+pick-headline = { $mode ->
+    [ranked] Чаще всего предлагали
+    [poll] Голосование выбрало
+   *[random] Случайный выбор
+}
+pick-result =
+    { $headline }
+    { $subject }
+pick-result-insights =
+    { $headline }
+    { $subject }
+
+    А вот [ссылка для инсайтов]({ $link })
+pick-result-insights-pending =
+    { $headline }
+    { $subject }
+
+    Инсайты сейчас недоступны, ссылка придёт сюда позже
+pick-result-insights-rejected =
+    { $headline }
+    { $subject }
+
+    Инсайты не смогли зарегистрировать встречу: { $reason }
+insights-link-attached = Инсайты снова доступны, вот ссылка для { $subject }: { $link }
+insights-summary-attached = Инсайты снова доступны, вот итоги инсайтов: { $link }
+insights-rejected = Инсайты не смогли { $call ->
+    [register] зарегистрировать
+    [start] начать
+   *[finish] завершить
+} встречу: { $reason }
+insights-summary-ready = Итоги инсайтов для { $subject } готовы: { $link }
+insights-submitted = { $count ->
+    [one] Для { $subject } отправлен { $count } инсайт
+    [few] Для { $subject } отправлено { $count } инсайта
+   *[many] Для { $subject } отправлено { $count } инсайтов
+}
+pick-poll-question = { $kind ->
+    [books] Какую книгу обсудим на следующей встрече?
+    [films] Какой фильм посмотрим на следующей встрече?
+    [games] В какую игру сыграем на следующей встрече?
+   *[generic] О чём будет следующая { $noun }?
+}
+pick-filter-usage =
+    Добавьте к фильтру число:
+    /pick under 150
+pick-poll-started = Голосуйте и снова отправьте /pick, чтобы закрыть опрос
+pick-unknown-skipped = { $count ->
+    [one] { $count } предложение с неизвестным { $kind ->
+        [books] числом страниц
+       *[other] хронометражем
+    } не учтено
+    [few] { $count } предложения с неизвестным { $kind ->
+        [books] числом страниц
+       *[other] хронометражем
+    } не учтены
+   *[other] { $count } предложений с неизвестным { $kind ->
+        [books] числом страниц
+       *[other] хронометражем
+    } не учтены
+}
+current-not-picked =
+    Следующая { $noun }: { $date }\.
+    Тема ещё не выбрана
+current-picked =
+    Следующая { $noun }: { $date }\.
+    Тема: { $subject }
+current-insights =
+    { $message }
+    Вот [ссылка для инсайтов]({ $link })
+current-insights-pending =
+    { $message }
+    Ссылка для инсайтов придёт, когда они станут доступны
+current-progress =
+    { $message }
+
+    { $lines }
+current-host =
+    { $message }
+    Ведущий: { $host }
+current-location =
+    { $message }
+    Место: { $location }
+progress-usage =
+    Укажите, сколько пройдено:
+    /progress 45%
+    /progress стр. 120
+progress-saved = { $name }: { $percent }% из «{ $subject }»
+progress-saved-page = { $name }: страница { $page } из { $pages } в «{ $subject }», { $percent }%
+progress-line = { $bar } { $percent }% { $name }
+checkin = Еженедельная проверка, скоро { $noun }: сколько пройдено из «{ $subject }»? Нажмите кнопку или отправьте /progress 45% или /progress стр. 120
+checkin-progress =
+    { $checkin }
+
+    { $lines }
+reminder = Напоминание: следующая { $noun } через { $offset }, { $date }
+reminder-not-picked =
+    { $reminder }
+    Тема ещё не выбрана, предложите свою через /suggest
+reminder-subject =
+    { $reminder }
+    Тема: { $subject }
+reminder-behind =
+    { $reminder }
+    Пока отстают: { $members }
+reminder-host =
+    { $reminder }
+    Ведущий: { $host }
+reminder-location =
+    { $reminder }
+    Место: { $location }
+question-usage =
+    Напишите вопрос после команды:
+    /question Как бы вы поступили на месте героя?
+    /question анон Кому ещё показалось, что финал скомкан?
+question-saved = Вопрос добавлен, всего вопросов: { $count }. Они перемешаются и появятся после /startclub
+question-saved-anonymous = Анонимный вопрос добавлен, всего вопросов: { $count }. Они перемешаются и появятся после /startclub
+questions-title =
+    Вопросы для обсуждения \({ $count }\), нажмите на номер, когда вопрос обсудили:
+    { $lines }
+question-line = { $number }\. { $question } — { $name }
+question-line-anonymous = { $number }\. { $question }
+spoiler-usage =
+    Укажите, докуда спойлер, и напишите его:
+    /spoiler 12 поворот в двенадцатой главе
+    /spoiler 45% что происходит в середине
+    /spoiler стр. 120 сцена на 120 странице
+discussion-topic = Спойлеры: { $subject }
+discussion-announcement = Здесь обсуждаем «{ $subject }», спойлеры скрыты, пока на них не нажать
+spoiler-label-chapter = главы { $chapter }
+spoiler-label-percent = { $percent }%
+spoiler-label-page = страницы { $page }
+spoiler-warning = Открывайте, только если дошли до { $label }
+spoiler-behind = { $members }, вы ещё не дошли до { $label }, не открывайте
+spoiler-post =
+    Спойлер до { $label } от { $name }
+    { $warning }
+    ||{ $text }||
+log-empty = В клубе пока ничего не происходило
+log-title =
+    Последние изменения ({ $count }):
+    { $lines }
+log-failed = Не удалось получить журнал клуба
+log-line = { $date } · пользователь { $user } { $action }
+member-fallback = пользователь { $user }
+history-empty = Завершённых встреч пока нет
+history-failed = Не удалось получить историю клуба
+history-title =
+    Прошедшие встречи ({ $count }):
+    { $lines }
+history-line = { $date } · { $subject } · { $rating }
+history-rating = { $average }★, { $count ->
+    [one] { $count } оценка
+    [few] { $count } оценки
+   *[many] { $count } оценок
+}
+history-not-rated = без оценок
+history-review = — { $name }, { $stars }★: { $review }
+stats-empty = Оценок пока нет, их собирают после /achieve
+stats-failed = Не удалось получить статистику клуба
+stats-title = Статистика клуба
+stats-best =
+    Лучшие темы:
+    { $lines }
+stats-best-line = { $subject }: { $average }★ ({ $count })
+stats-critic = Самый строгий критик: { $name }, в среднем { $average }★ за { $count ->
+    [one] { $count } оценку
+    [few] { $count } оценки
+   *[many] { $count } оценок
+}
+stats-suggesters =
+    Средняя оценка предложенного:
+    { $lines }
+stats-suggester-line = { $name }: { $average }★ за { $picks ->
+    [one] { $picks } тему
+    [few] { $picks } темы
+   *[many] { $picks } тем
+}
+hosts-empty = В очереди ведущих пока никого нет, встаньте в неё командой /hosts add
+hosts-title =
+    Очередь ведущих:
+    { $lines }
+hosts-line = { $number }. { $name }
+hosts-line-next = { $number }. { $name } · следующий
+hosts-usage =
+    Формат:
+    /hosts, чтобы посмотреть очередь
+    /hosts add, чтобы встать в неё, или ответом на сообщение, чтобы добавить участника
+    /hosts remove, чтобы выйти из неё, /hosts remove 2, чтобы убрать второго ведущего
+host-added = { $name } теперь в очереди ведущих под номером { $number }
+host-removed = { $name } больше не в очереди ведущих
+host-swapped = Новый ведущий: { $name } · { $noun }, { $date }
+hosts-admins-only = Менять очередь за других могут только администраторы чата
+swaphost-usage = Ответьте на сообщение нового ведущего командой /swaphost или добавьте трек: /swaphost books
+host-only = Пока включён режим ведущего, это может сделать только { $host } или администратор чата
+calendar-name = Встречи клуба
+calendar-summary = { $subject } · { $noun }
+calendar-summary-not-picked = { $noun } · тема не выбрана
+calendar-host = Ведущий: { $host }
+calendar-insights = Инсайты: { $link }
+calendar-link =
+    Добавьте встречи клуба в календарь по этой ссылке:
+    { $link }
+    Встречи видит любой, у кого она есть, так что не выносите её за пределы клуба
+calendar-reset =
+    Старая ссылка на календарь больше не работает, вот новая:
+    { $link }
+calendar-usage =
+    Формат:
+    /calendar, чтобы получить ссылку
+    /calendar reset, чтобы заменить её
+calendar-admins-only = Заменить ссылку на календарь могут только администраторы чата
+
+## Audit log actions
+
+audit-club-registered = зарегистрировал клуб
+audit-event-created = создал встречу на { $date }
+audit-track-event-created = создал встречу трека { $track } на { $date }
+audit-suggestion-added = предложил { $suggestion }
+audit-insights-on = включил инсайты
+audit-insights-off = выключил инсайты
+audit-subject-picked = выбрал { $subject }
+audit-event-started = начал встречу
+audit-event-achieved = завершил встречу
+audit-settings-changed = изменил настройки клуба
+audit-host-added = добавил { $name } в очередь ведущих
+audit-host-removed = убрал { $name } из очереди ведущих
+audit-host-swapped = передал встречу: { $name }
+audit-location-changed = перенёс встречу: { $location }
+audit-rating-given = оценил «{ $subject }»: { $stars }★
+audit-review-added = написал отзыв о «{ $subject }»
+audit-question-asked = задал вопрос
+audit-question-covered = отметил вопрос { $number } как обсуждённый
+audit-question-uncovered = снял отметку с вопроса { $number }
+audit-calendar-created = создал ссылку на календарь
+audit-calendar-reset = сбросил ссылку на календарь
+audit-progress-marked = отметил прогресс: { $percent }%
+audit-poll-started = запустил голосование за тему
+audit-work-chosen = уточнил предложение: { $title }
+audit-discussion-opened = открыл обсуждение
+
+## Settings
+
+settings-title = Настройки клуба
+settings-usage =
+    Формат:
+    /settings
+    /settings timezone Europe/Moscow
+    /settings limit 3
+    /settings kind books, films, games или generic
+settings-admins-only = Менять настройки могут только администраторы чата
+settings-failed = Не удалось сохранить настройки, клуб зарегистрирован через /start?
+settings-timezone-hint = Часовой пояс меняется командой /settings timezone Europe/Moscow
+settings-reminders-hint = Нажмите на вариант ниже, чтобы включить или выключить напоминание
+settings-kind = Клуб выбирает: { $kind ->
+    [books] книги
+    [films] фильмы
+    [games] настольные игры
+   *[generic] что угодно
+}
+settings-insights = Инсайты по умолчанию: { $enabled ->
+    [true] вкл
+   *[false] выкл
+}
+settings-pick-mode = Выбор темы: { $mode ->
+    [poll] голосование
+    [ranked] самая популярная
+   *[random] случайно
+}
+settings-limit = Предложений на участника: { $limit ->
+    [0] без ограничений
+   *[other] { $limit }
+}
+settings-host-only = Выбирает и завершает только ведущий: { $enabled ->
+    [true] вкл
+   *[false] выкл
+}
+settings-language = Язык: { $language ->
+    [en] English
+    [ru] Русский
+   *[auto] как в Telegram
+}
+settings-time-zone = Часовой пояс: { $tz }
+settings-reminders = Напоминания до встречи:
+
+## Durations
+
+duration-weeks = { $count ->
+    [one] { $count } неделю
+    [few] { $count } недели
+   *[many] { $count } недель
+}
+duration-days = { $count ->
+    [one] { $count } день
+    [few] { $count } дня
+   *[many] { $count } дней
+}
+duration-hours = { $count ->
+    [one] { $count } час
+    [few] { $count } часа
+   *[many] { $count } часов
+}
+duration-minutes = { $count ->
+    [one] { $count } минуту
+    [few] { $count } минуты
+   *[many] { $count } минут
+}
+duration-weeks-short = { $count } нед
+duration-days-short = { $count } д
+duration-hours-short = { $count } ч
+duration-minutes-short = { $count } мин
+
+## Dates
+
+date-full = { $weekday }, { $day } { $month } в { $time }
+weekday-1 = понедельник
+weekday-2 = вторник
+weekday-3 = среда
+weekday-4 = четверг
+weekday-5 = пятница
+weekday-6 = суббота
+weekday-7 = воскресенье
+month-1 = января
+month-2 = февраля
+month-3 = марта
+month-4 = апреля
+month-5 = мая
+month-6 = июня
+month-7 = июля
+month-8 = августа
+month-9 = сентября
+month-10 = октября
+month-11 = ноября
+month-12 = декабря
+
+## Errors
+
+error-no-active-event = Активная встреча не найдена
+error-active-event-found = Уже есть активная встреча: { $date }
+error-no-suggestions = Предложений пока нет
+error-already-picked = Тема уже выбрана: { $subject }
+error-wrong-date-format = Неверный формат, извините
+error-event-in-past = К сожалению, в прошлое не вернуться
+error-no-active-event-in-track = В треке { $track } нет активной встречи
+error-track-required = Сейчас идут несколько треков: { $tracks }; укажите один из них в команде
+error-wrong-track-name = Название трека должно быть одним словом
+error-suggestion-limit = Вы уже сделали предложений для этой встречи: { $limit }
+error-unknown-time-zone = Неизвестный часовой пояс { $tz }
+error-wrong-setting-value = Это значение нельзя использовать для настройки
+error-suggestion-not-found = Этого предложения больше нет
+error-not-your-suggestion = Выбрать может только тот, кто сделал предложение
+error-already-suggested = Вы уже предлагали это для встречи
+error-pick-filter-without-kind = Фильтру нужен тип клуба, задайте его командой /settings kind books
+error-no-suggestions-under = Нет предложений, которые точно укладываются в { $limit } { $kind ->
+    [books] стр.
+   *[other] мин
+}
+error-subject-not-picked = Тема ещё не выбрана, отмечать пока нечего
+error-wrong-progress = Отправьте процент или страницу, например /progress 45% или /progress стр. 120
+error-progress-pages-unknown = Число страниц у выбранной темы неизвестно, отправьте процент, например /progress 45%
+error-nothing-to-rate = Нет завершённой встречи, которую можно оценить
+error-rating-required = Сначала оцените «{ $subject }» звёздами, потом добавьте отзыв
+error-review-too-long = Отзыв слишком длинный, уложитесь в { $limit } символов
+error-questions-closed = Встреча уже началась, вопросы больше не принимаются
+error-question-too-long = Вопрос слишком длинный, уложитесь в { $limit } символов
+error-question-limit = У встречи уже { $limit } вопросов, больше в один список не поместится
+error-question-not-found = Этого вопроса больше нет
+error-already-host = { $name } уже в очереди ведущих
+error-not-a-host = Такого ведущего в очереди нет
+error-swap-not-allowed = Передать встречу может только текущий ведущий или администратор чата
+error-calendar-disabled = Календари для этого бота не настроены, но /ics по-прежнему пришлёт текущую встречу
+error-club-not-found = В этом чате ещё нет клуба, сначала выполните /start
+error-unexpected = Что-то пошло не так, попробуйте позже
+error-host-only = Отмечать вопросы может только ведущий или администратор чата
+error-wrong-spoiler = Начните с главы, процента или страницы, например /spoiler 12 текст, /spoiler 45% текст или /spoiler стр. 120 текст
@@ -0,0 +1,20 @@
+CREATE TABLE IF NOT EXISTS "club_audit" (
+                              "id" bigserial PRIMARY KEY NOT NULL,
+                              "chat_id" int8 NOT NULL REFERENCES "club" ("chat_id"),
+                              "actor_id" int8 NOT NULL,
+                              "action" text NOT NULL,
+                              "payload" jsonb NOT NULL DEFAULT '{}',
+                              "created_at" timestamptz NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS "club_audit_chat_id_idx" ON "club_audit" ("chat_id", "created_at" DESC);
+
+CREATE OR REPLACE FUNCTION club_audit_append_only() RETURNS trigger AS $$
+BEGIN
+    RAISE EXCEPTION 'club_audit is append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE TRIGGER "club_audit_append_only"
+    BEFORE UPDATE OR DELETE ON "club_audit"
+    FOR EACH ROW EXECUTE FUNCTION club_audit_append_only();
@@ -0,0 +1,3 @@
+ALTER TABLE "events" ADD COLUMN IF NOT EXISTS "track" text NOT NULL DEFAULT 'main';
+
+CREATE INDEX IF NOT EXISTS "events_active_track_idx" ON "events" ("chat_id", "track") WHERE "active";
@@ -0,0 +1,19 @@
+CREATE TABLE IF NOT EXISTS "club_settings" (
+                                 "chat_id" int8 PRIMARY KEY NOT NULL REFERENCES "club" ("chat_id"),
+                                 "default_insights" boolean NOT NULL DEFAULT false,
+                                 "pick_mode" text NOT NULL DEFAULT 'random',
+                                 "suggestion_limit" int4 NOT NULL DEFAULT 0,
+                                 "time_zone" text NOT NULL DEFAULT 'UTC',
+                                 "language" text,
+                                 "reminder_offsets" int4[] NOT NULL DEFAULT '{}',
+                                 "updated_at" timestamptz NOT NULL DEFAULT NOW()
+);
+
+ALTER TABLE "events" ADD COLUMN IF NOT EXISTS "poll_message_id" int4;
+
+CREATE TABLE IF NOT EXISTS "event_reminders" (
+                                   "event_id" uuid NOT NULL REFERENCES "events" ("id"),
+                                   "offset_minutes" int4 NOT NULL,
+                                   "sent_at" timestamptz NOT NULL DEFAULT NOW(),
+                                   PRIMARY KEY ("event_id", "offset_minutes")
+);
@@ -0,0 +1,10 @@
+-- Suggestions and picked subjects used to be stored with '-' escaped for MarkdownV2.
+-- Text is stored raw now and escaped when a reply is rendered, so old rows are unescaped once.
+-- club_audit is append-only and keeps the escaped text of past records as is.
+UPDATE "suggestions"
+SET "suggestion" = replace("suggestion", '\-', '-')
+WHERE "suggestion" LIKE '%\\-%';
+
+UPDATE "events"
+SET "subject" = replace("subject", '\-', '-')
+WHERE "subject" LIKE '%\\-%';
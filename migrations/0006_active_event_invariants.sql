@@ -0,0 +1,17 @@
+-- Only the soonest active event of a track stays active, the rest could only appear through a race.
+UPDATE "events" e SET "active" = false
+WHERE e."active" AND EXISTS (
+    SELECT 1 FROM "events" o
+    WHERE o."active" AND o."chat_id" = e."chat_id" AND o."track" = e."track"
+        AND (o."event_date", o."id") < (e."event_date", e."id")
+);
+
+UPDATE "club" c SET
+    "active_event" = (SELECT "id" FROM "events" WHERE "chat_id" = c."chat_id" AND "active" ORDER BY "event_date" LIMIT 1),
+    "next_event" = (SELECT min("event_date") AT TIME ZONE 'UTC' FROM "events" WHERE "chat_id" = c."chat_id" AND "active");
+
+DROP INDEX IF EXISTS "events_active_track_idx";
+
+CREATE UNIQUE INDEX IF NOT EXISTS "events_one_active_per_track_idx" ON "events" ("chat_id", "track") WHERE "active";
+
+ALTER TABLE "club" ADD CONSTRAINT "club_active_event_fkey" FOREIGN KEY ("active_event") REFERENCES "events" ("id");
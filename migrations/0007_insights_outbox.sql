@@ -0,0 +1,24 @@
+-- Insights calls are recorded together with the change they belong to and delivered by a worker.
+-- The id is sent as the idempotency key, seq keeps the calls of one event in order.
+CREATE TABLE IF NOT EXISTS "insights_outbox" (
+                                   "seq" bigserial PRIMARY KEY,
+                                   "id" uuid UNIQUE NOT NULL,
+                                   "event_id" uuid NOT NULL REFERENCES "events" ("id"),
+                                   "chat_id" int8 NOT NULL,
+                                   "kind" text NOT NULL,
+                                   "payload" jsonb NOT NULL DEFAULT '{}',
+                                   "status" text NOT NULL DEFAULT 'pending',
+                                   "attempts" int4 NOT NULL DEFAULT 0,
+                                   "next_attempt_at" timestamptz NOT NULL DEFAULT NOW(),
+                                   "last_error" text,
+                                   "created_at" timestamptz NOT NULL DEFAULT NOW(),
+                                   "delivered_at" timestamptz
+);
+
+CREATE INDEX IF NOT EXISTS "insights_outbox_pending_idx" ON "insights_outbox" ("next_attempt_at") WHERE "status" = 'pending';
+
+-- Picks insights couldn't register before the outbox existed.
+INSERT INTO "insights_outbox" ("id", "event_id", "chat_id", "kind", "payload")
+SELECT gen_random_uuid(), "id", "chat_id", 'register', jsonb_build_object('subject', "subject")
+FROM "events"
+WHERE "active" AND "insights" AND "subject" <> '' AND "insights_link" IS NULL;
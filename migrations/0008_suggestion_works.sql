@@ -0,0 +1,5 @@
+-- Suggestions get an id to be referred to from chat buttons and the catalog work they were resolved to.
+ALTER TABLE "suggestions" ADD COLUMN IF NOT EXISTS "id" bigserial;
+ALTER TABLE "suggestions" ADD COLUMN IF NOT EXISTS "work" jsonb;
+
+CREATE UNIQUE INDEX IF NOT EXISTS "suggestions_id_idx" ON "suggestions" ("id");
@@ -0,0 +1,5 @@
+-- A recognized isbn or link, so differently written suggestions of the same work are counted together.
+ALTER TABLE "suggestions" ADD COLUMN IF NOT EXISTS "identifier_kind" text;
+ALTER TABLE "suggestions" ADD COLUMN IF NOT EXISTS "identifier" text;
+
+CREATE INDEX IF NOT EXISTS "suggestions_identifier_idx" ON "suggestions" ("event_id", "identifier_kind", "identifier");
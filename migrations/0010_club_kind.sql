@@ -0,0 +1,2 @@
+-- What the club picks, it decides the wording and the metadata collected for suggestions.
+ALTER TABLE "club_settings" ADD COLUMN IF NOT EXISTS "kind" text NOT NULL DEFAULT 'books';
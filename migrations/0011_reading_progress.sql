@@ -0,0 +1,22 @@
+-- When the subject was picked and what it resolved to, progress is measured against both.
+ALTER TABLE "events" ADD COLUMN IF NOT EXISTS "picked_at" timestamptz;
+ALTER TABLE "events" ADD COLUMN IF NOT EXISTS "work" jsonb;
+ALTER TABLE "events" ADD COLUMN IF NOT EXISTS "checkin_at" timestamptz;
+
+-- Picks made before this migration are dated by their audit record.
+UPDATE "events" e SET "picked_at" = (
+    SELECT max(a."created_at") FROM "club_audit" a
+    WHERE a."action" = 'subject_picked' AND a."payload"->>'event_id' = e."id"::text
+)
+WHERE e."subject" <> '' AND e."picked_at" IS NULL;
+
+-- Latest progress of each member, the name is the one telegram showed at the time.
+CREATE TABLE IF NOT EXISTS "progress" (
+                            "event_id" uuid NOT NULL REFERENCES "events" ("id"),
+                            "user_id" int8 NOT NULL,
+                            "name" text NOT NULL,
+                            "percent" int4 NOT NULL,
+                            "page" int4,
+                            "updated_at" timestamptz NOT NULL DEFAULT NOW(),
+                            PRIMARY KEY ("event_id", "user_id")
+);
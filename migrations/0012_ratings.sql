@@ -0,0 +1,14 @@
+-- Who suggested the pick, so ratings can be credited to them.
+ALTER TABLE "suggestions" ADD COLUMN IF NOT EXISTS "name" text;
+ALTER TABLE "events" ADD COLUMN IF NOT EXISTS "suggested_by" jsonb NOT NULL DEFAULT '[]';
+
+-- What each member thought of an achieved event, the review is optional.
+CREATE TABLE IF NOT EXISTS "ratings" (
+                           "event_id" uuid NOT NULL REFERENCES "events" ("id"),
+                           "user_id" int8 NOT NULL,
+                           "name" text NOT NULL,
+                           "stars" int4 NOT NULL CHECK ("stars" BETWEEN 1 AND 5),
+                           "review" text,
+                           "created_at" timestamptz NOT NULL DEFAULT NOW(),
+                           PRIMARY KEY ("event_id", "user_id")
+);
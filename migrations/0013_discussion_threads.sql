@@ -0,0 +1,3 @@
+-- Where spoilers of the event are posted: a forum topic or replies to a message of the bot.
+ALTER TABLE "events" ADD COLUMN IF NOT EXISTS "discussion_kind" text;
+ALTER TABLE "events" ADD COLUMN IF NOT EXISTS "discussion_id" int4;
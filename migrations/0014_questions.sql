@@ -0,0 +1,26 @@
+-- Who ran /startclub, questions are closed once an event has started.
+ALTER TABLE "events" ADD COLUMN IF NOT EXISTS "started_by" int8;
+
+-- Events started before this migration are credited to their first start record.
+UPDATE "events" e SET "started_by" = (
+    SELECT a."actor_id" FROM "club_audit" a
+    WHERE a."action" = 'event_started' AND a."payload"->>'event_id' = e."id"::text
+    ORDER BY a."created_at"
+    LIMIT 1
+)
+WHERE e."started_by" IS NULL;
+
+-- Discussion questions of an event, the name is null for anonymous ones
+-- and the number is given when the event starts.
+CREATE TABLE IF NOT EXISTS "questions" (
+                             "id" uuid PRIMARY KEY NOT NULL,
+                             "event_id" uuid NOT NULL REFERENCES "events" ("id"),
+                             "user_id" int8 NOT NULL,
+                             "name" text,
+                             "question" text NOT NULL,
+                             "number" int4,
+                             "covered" boolean NOT NULL DEFAULT false,
+                             "created_at" timestamptz NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS "questions_event_id_idx" ON "questions" ("event_id");
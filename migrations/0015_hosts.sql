@@ -0,0 +1,16 @@
+-- Who hosts the event, assigned from the club's rotation when the event is created.
+ALTER TABLE "events" ADD COLUMN IF NOT EXISTS "host_id" int8;
+ALTER TABLE "events" ADD COLUMN IF NOT EXISTS "host_name" text;
+
+-- Only the host and chat admins may pick or achieve an event when this is on.
+ALTER TABLE "club_settings" ADD COLUMN IF NOT EXISTS "host_only" boolean NOT NULL DEFAULT false;
+
+-- The club's host rotation, events get their hosts in position order.
+CREATE TABLE IF NOT EXISTS "hosts" (
+                         "chat_id" int8 NOT NULL REFERENCES "club" ("chat_id"),
+                         "user_id" int8 NOT NULL,
+                         "name" text NOT NULL,
+                         "position" int4 NOT NULL,
+                         "added_at" timestamptz NOT NULL DEFAULT NOW(),
+                         PRIMARY KEY ("chat_id", "user_id")
+);
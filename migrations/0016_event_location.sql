@@ -0,0 +1,5 @@
+-- Where the event takes place: an address, a venue name or a video call link,
+-- coordinates are kept when a map location was shared.
+ALTER TABLE "events" ADD COLUMN IF NOT EXISTS "location" text;
+ALTER TABLE "events" ADD COLUMN IF NOT EXISTS "latitude" float8;
+ALTER TABLE "events" ADD COLUMN IF NOT EXISTS "longitude" float8;
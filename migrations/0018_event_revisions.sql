@@ -0,0 +1,9 @@
+-- Calendars replace an event they already have only when its SEQUENCE grows,
+-- so every change a feed shows bumps the revision and the time of the change.
+ALTER TABLE "events" ADD COLUMN IF NOT EXISTS "revision" integer NOT NULL DEFAULT 0;
+ALTER TABLE "events" ADD COLUMN IF NOT EXISTS "updated_at" timestamptz;
+
+UPDATE "events" SET "updated_at" = "created_at" WHERE "updated_at" IS NULL;
+
+ALTER TABLE "events" ALTER COLUMN "updated_at" SET DEFAULT NOW();
+ALTER TABLE "events" ALTER COLUMN "updated_at" SET NOT NULL;
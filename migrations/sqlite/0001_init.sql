@@ -0,0 +1,70 @@
+CREATE TABLE IF NOT EXISTS "club" (
+                            "chat_id" integer PRIMARY KEY NOT NULL,
+                            "last_event" text,
+                            "next_event" text,
+                            "created_at" text NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                            "active_event" text
+);
+
+CREATE TABLE IF NOT EXISTS "events" (
+                          "id" text PRIMARY KEY NOT NULL,
+                          "chat_id" integer NOT NULL REFERENCES "club" ("chat_id"),
+                          "subject" text,
+                          "active" boolean,
+                          "event_date" text NOT NULL,
+                          "achieved_on" text,
+                          "created_at" text NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                          "insights" boolean NOT NULL DEFAULT false,
+                          "insights_link" text,
+                          "track" text NOT NULL DEFAULT 'main',
+                          "poll_message_id" integer
+);
+
+CREATE INDEX IF NOT EXISTS "events_active_track_idx" ON "events" ("chat_id", "track") WHERE "active";
+
+CREATE TABLE IF NOT EXISTS "suggestions" (
+                               "event_id" text NOT NULL REFERENCES "events" ("id"),
+                               "chat_id" integer NOT NULL,
+                               "user_id" integer NOT NULL,
+                               "suggestion" text,
+                               "created_at" text NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS "club_audit" (
+                              "id" integer PRIMARY KEY AUTOINCREMENT,
+                              "chat_id" integer NOT NULL REFERENCES "club" ("chat_id"),
+                              "actor_id" integer NOT NULL,
+                              "action" text NOT NULL,
+                              "payload" text NOT NULL DEFAULT '{}',
+                              "created_at" text NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS "club_audit_chat_id_idx" ON "club_audit" ("chat_id", "created_at" DESC);
+
+CREATE TRIGGER IF NOT EXISTS "club_audit_no_update" BEFORE UPDATE ON "club_audit"
+BEGIN
+    SELECT RAISE(ABORT, 'club_audit is append-only');
+END;
+
+CREATE TRIGGER IF NOT EXISTS "club_audit_no_delete" BEFORE DELETE ON "club_audit"
+BEGIN
+    SELECT RAISE(ABORT, 'club_audit is append-only');
+END;
+
+CREATE TABLE IF NOT EXISTS "club_settings" (
+                                 "chat_id" integer PRIMARY KEY NOT NULL REFERENCES "club" ("chat_id"),
+                                 "default_insights" boolean NOT NULL DEFAULT false,
+                                 "pick_mode" text NOT NULL DEFAULT 'random',
+                                 "suggestion_limit" integer NOT NULL DEFAULT 0,
+                                 "time_zone" text NOT NULL DEFAULT 'UTC',
+                                 "language" text,
+                                 "reminder_offsets" text NOT NULL DEFAULT '[]',
+                                 "updated_at" text NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS "event_reminders" (
+                                   "event_id" text NOT NULL REFERENCES "events" ("id"),
+                                   "offset_minutes" integer NOT NULL,
+                                   "sent_at" text NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                                   PRIMARY KEY ("event_id", "offset_minutes")
+);
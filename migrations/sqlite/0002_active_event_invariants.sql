@@ -0,0 +1,20 @@
+DROP INDEX IF EXISTS "events_active_track_idx";
+
+CREATE UNIQUE INDEX IF NOT EXISTS "events_one_active_per_track_idx" ON "events" ("chat_id", "track") WHERE "active";
+
+-- sqlite can't add a foreign key to an existing table, so club is rebuilt
+
+CREATE TABLE "club_new" (
+                            "chat_id" integer PRIMARY KEY NOT NULL,
+                            "last_event" text,
+                            "next_event" text,
+                            "created_at" text NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                            "active_event" text REFERENCES "events" ("id")
+);
+
+INSERT INTO "club_new" ("chat_id", "last_event", "next_event", "created_at", "active_event")
+SELECT "chat_id", "last_event", "next_event", "created_at", "active_event" FROM "club";
+
+DROP TABLE "club";
+
+ALTER TABLE "club_new" RENAME TO "club";
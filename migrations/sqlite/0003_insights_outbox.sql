@@ -0,0 +1,22 @@
+CREATE TABLE IF NOT EXISTS "insights_outbox" (
+                                   "seq" integer PRIMARY KEY AUTOINCREMENT,
+                                   "id" text UNIQUE NOT NULL,
+                                   "event_id" text NOT NULL REFERENCES "events" ("id"),
+                                   "chat_id" integer NOT NULL,
+                                   "kind" text NOT NULL,
+                                   "payload" text NOT NULL DEFAULT '{}',
+                                   "status" text NOT NULL DEFAULT 'pending',
+                                   "attempts" integer NOT NULL DEFAULT 0,
+                                   "next_attempt_at" text NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                                   "last_error" text,
+                                   "created_at" text NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                                   "delivered_at" text
+);
+
+CREATE INDEX IF NOT EXISTS "insights_outbox_pending_idx" ON "insights_outbox" ("next_attempt_at") WHERE "status" = 'pending';
+
+-- ids are compared as text, so they get the same hyphenated form uuid produces
+INSERT INTO "insights_outbox" ("id", "event_id", "chat_id", "kind", "payload")
+SELECT lower(substr(h, 1, 8) || '-' || substr(h, 9, 4) || '-' || substr(h, 13, 4) || '-' || substr(h, 17, 4) || '-' || substr(h, 21)),
+    "id", "chat_id", 'register', json_object('subject', "subject")
+FROM (SELECT *, hex(randomblob(16)) AS h FROM "events" WHERE "active" AND "insights" AND "subject" <> '' AND "insights_link" IS NULL);
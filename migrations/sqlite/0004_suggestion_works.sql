@@ -0,0 +1,2 @@
+-- suggestions are referred to by rowid, only the resolved work is new
+ALTER TABLE "suggestions" ADD COLUMN "work" text;
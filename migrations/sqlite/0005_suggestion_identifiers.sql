@@ -0,0 +1,5 @@
+-- a recognized isbn or link, so differently written suggestions of the same work are counted together
+ALTER TABLE "suggestions" ADD COLUMN "identifier_kind" text;
+ALTER TABLE "suggestions" ADD COLUMN "identifier" text;
+
+CREATE INDEX IF NOT EXISTS "suggestions_identifier_idx" ON "suggestions" ("event_id", "identifier_kind", "identifier");
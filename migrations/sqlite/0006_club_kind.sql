@@ -0,0 +1,2 @@
+-- what the club picks, it decides the wording and the metadata collected for suggestions
+ALTER TABLE "club_settings" ADD COLUMN "kind" text NOT NULL DEFAULT 'books';
@@ -0,0 +1,22 @@
+-- when the subject was picked and what it resolved to, progress is measured against both
+ALTER TABLE "events" ADD COLUMN "picked_at" text;
+ALTER TABLE "events" ADD COLUMN "work" text;
+ALTER TABLE "events" ADD COLUMN "checkin_at" text;
+
+-- picks made before this migration are dated by their audit record
+UPDATE "events" SET "picked_at" = (
+    SELECT max(a."created_at") FROM "club_audit" a
+    WHERE a."action" = 'subject_picked' AND json_extract(a."payload", '$.event_id') = "events"."id"
+)
+WHERE "subject" <> '' AND "picked_at" IS NULL;
+
+-- latest progress of each member, the name is the one telegram showed at the time
+CREATE TABLE IF NOT EXISTS "progress" (
+                            "event_id" text NOT NULL REFERENCES "events" ("id"),
+                            "user_id" integer NOT NULL,
+                            "name" text NOT NULL,
+                            "percent" integer NOT NULL,
+                            "page" integer,
+                            "updated_at" text NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                            PRIMARY KEY ("event_id", "user_id")
+);
@@ -0,0 +1,14 @@
+-- who suggested the pick, so ratings can be credited to them
+ALTER TABLE "suggestions" ADD COLUMN "name" text;
+ALTER TABLE "events" ADD COLUMN "suggested_by" text NOT NULL DEFAULT '[]';
+
+-- what each member thought of an achieved event, the review is optional
+CREATE TABLE IF NOT EXISTS "ratings" (
+                           "event_id" text NOT NULL REFERENCES "events" ("id"),
+                           "user_id" integer NOT NULL,
+                           "name" text NOT NULL,
+                           "stars" integer NOT NULL CHECK ("stars" BETWEEN 1 AND 5),
+                           "review" text,
+                           "created_at" text NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                           PRIMARY KEY ("event_id", "user_id")
+);
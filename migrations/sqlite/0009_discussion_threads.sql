@@ -0,0 +1,3 @@
+-- where spoilers of the event are posted: a forum topic or replies to a message of the bot
+ALTER TABLE "events" ADD COLUMN "discussion_kind" text;
+ALTER TABLE "events" ADD COLUMN "discussion_id" integer;
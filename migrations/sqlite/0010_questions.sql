@@ -0,0 +1,26 @@
+-- who ran /startclub, questions are closed once an event has started
+ALTER TABLE "events" ADD COLUMN "started_by" integer;
+
+-- events started before this migration are credited to their first start record
+UPDATE "events" SET "started_by" = (
+    SELECT a."actor_id" FROM "club_audit" a
+    WHERE a."action" = 'event_started' AND json_extract(a."payload", '$.event_id') = "events"."id"
+    ORDER BY a."created_at"
+    LIMIT 1
+)
+WHERE "started_by" IS NULL;
+
+-- discussion questions of an event, the name is null for anonymous ones
+-- and the number is given when the event starts
+CREATE TABLE IF NOT EXISTS "questions" (
+                             "id" text PRIMARY KEY NOT NULL,
+                             "event_id" text NOT NULL REFERENCES "events" ("id"),
+                             "user_id" integer NOT NULL,
+                             "name" text,
+                             "question" text NOT NULL,
+                             "number" integer,
+                             "covered" boolean NOT NULL DEFAULT false,
+                             "created_at" text NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS "questions_event_id_idx" ON "questions" ("event_id");
@@ -0,0 +1,16 @@
+-- who hosts the event, assigned from the club's rotation when the event is created
+ALTER TABLE "events" ADD COLUMN "host_id" integer;
+ALTER TABLE "events" ADD COLUMN "host_name" text;
+
+-- only the host and chat admins may pick or achieve an event when this is on
+ALTER TABLE "club_settings" ADD COLUMN "host_only" boolean NOT NULL DEFAULT false;
+
+-- the club's host rotation, events get their hosts in position order
+CREATE TABLE IF NOT EXISTS "hosts" (
+                         "chat_id" integer NOT NULL REFERENCES "club" ("chat_id"),
+                         "user_id" integer NOT NULL,
+                         "name" text NOT NULL,
+                         "position" integer NOT NULL,
+                         "added_at" text NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                         PRIMARY KEY ("chat_id", "user_id")
+);
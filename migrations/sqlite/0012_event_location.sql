@@ -0,0 +1,5 @@
+-- where the event takes place: an address, a venue name or a video call link,
+-- coordinates are kept when a map location was shared
+ALTER TABLE "events" ADD COLUMN "location" text;
+ALTER TABLE "events" ADD COLUMN "latitude" real;
+ALTER TABLE "events" ADD COLUMN "longitude" real;
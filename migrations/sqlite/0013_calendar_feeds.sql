@@ -0,0 +1,4 @@
+-- the secret part of the club's calendar feed link, created on the first /calendar
+ALTER TABLE "club" ADD COLUMN "calendar_token" text;
+
+CREATE UNIQUE INDEX IF NOT EXISTS "club_calendar_token" ON "club" ("calendar_token");
@@ -0,0 +1,7 @@
+-- calendars replace an event they already have only when its SEQUENCE grows,
+-- so every change a feed shows bumps the revision and the time of the change
+ALTER TABLE "events" ADD COLUMN "revision" integer NOT NULL DEFAULT 0;
+-- sqlite can't add a column defaulting to CURRENT_TIMESTAMP, new events set it on insert
+ALTER TABLE "events" ADD COLUMN "updated_at" text;
+
+UPDATE "events" SET "updated_at" = "created_at" WHERE "updated_at" IS NULL;
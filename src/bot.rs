@@ -1,11 +1,26 @@
+use crate::calendar;
+use crate::config::Config;
 use crate::err::CustomError as Err;
-use crate::service::{default_service, Service};
-use dotenv::dotenv;
-use lazy_static::lazy_static;
+use crate::i18n::{self, tr, DEFAULT_LANGUAGE, LANGUAGES};
+use crate::markdown;
+use crate::models::ClubSettings;
+use crate::models::Work;
+use crate::models::{Coordinates, DiscussionKind, DiscussionThread, Location, Member};
+use crate::repository;
+use crate::service::{
+    describe_offset, skipped_note, Discussion, HostRef, Pick, QuestionList, Scheduled, Service,
+    SettingChange, Suggestion, MAX_STARS, PROGRESS_STEPS, REMINDER_OFFSETS,
+};
+use crate::webhook;
+use std::sync::Arc;
+use std::time::Duration;
 use teloxide::types::ParseMode::MarkdownV2;
+use teloxide::types::{
+    BotCommand, Chat, ChatKind, ChatPublic, InlineKeyboardButton, InlineKeyboardMarkup, InputFile,
+    MessageEntityKind, MessageId, PublicChatKind, User,
+};
 use teloxide::{prelude::*, types::Message, utils::command::BotCommands};
-use tokio::runtime::Handle;
-use tokio_postgres::error::SqlState;
+use uuid::Uuid;
 
 #[derive(BotCommands, Clone)]
 #[command(
@@ -17,48 +32,121 @@ enum Command {
     Help,
     #[command(description = "starts club", parse_with = "split")]
     Start,
-    #[command(description = "create new event")]
+    #[command(description = "create new event, optionally in a named track")]
     Event(String),
     #[command(description = "make new suggestion")]
     Suggest(String),
     #[command(description = "achieves active event")]
-    Achieve,
-    #[command(description = "picks a subject for active event")]
-    Pick,
+    Achieve(String),
+    #[command(description = "adds a short review to your rating of the last event")]
+    Review(String),
+    #[command(description = "picks a subject for active event, /pick under 150 skips longer ones")]
+    Pick(String),
     #[command(description = "current event info")]
-    Current,
+    Current(String),
+    #[command(
+        description = "where the event takes place, /where Cafe Central or reply to a location to set it"
+    )]
+    Where(String),
+    #[command(description = "sends the active event as a calendar file, e.g. /ics books")]
+    Ics(String),
+    #[command(
+        description = "shows the link to follow club events in a calendar, /calendar reset makes a new one"
+    )]
+    Calendar(String),
+    #[command(description = "shares how far along you are, e.g. /progress 45% or /progress p.120")]
+    Progress(String),
+    #[command(description = "posts a hidden spoiler, e.g. /spoiler 12 text or /spoiler 45% text")]
+    Spoiler(String),
+    #[command(description = "adds a discussion question, /question anon text hides your name")]
+    Question(String),
     #[command(description = "turns insights on/off for current event")]
-    Insights,
-    #[command(description = "starts current event only if insights enabled (to get summary link)")]
-    StartClub,
+    Insights(String),
+    #[command(
+        description = "starts current event, posts the questions and the insights summary link"
+    )]
+    StartClub(String),
+    #[command(description = "shows last club changes, e.g. /log 20")]
+    Log(String),
+    #[command(description = "manages the host rotation: /hosts add, /hosts remove or /hosts list")]
+    Hosts(String),
+    #[command(
+        description = "hands the event to another host, reply to their message with /swaphost"
+    )]
+    SwapHost(String),
+    #[command(description = "shows past events with their ratings, e.g. /history 20")]
+    History(String),
+    #[command(
+        description = "shows best rated picks, the harshest critic and suggesters' averages"
+    )]
+    Stats,
+    #[command(
+        description = "club settings, also /settings timezone Europe/Berlin, /settings limit 3 or /settings kind films"
+    )]
+    Settings(String),
 }
 
-fn default_service_blocking() -> Service {
-    let rt = Handle::current();
-    tokio::task::block_in_place(|| rt.block_on(default_service()))
-}
-
-lazy_static! {
-    static ref SERVICE: Service = default_service_blocking();
-}
+const DEFAULT_LOG_LIMIT: i64 = 10;
+const MAX_LOG_LIMIT: i64 = 50;
+const DEFAULT_HISTORY_LIMIT: i64 = 10;
+const MAX_HISTORY_LIMIT: i64 = 50;
+const SETTINGS_PREFIX: &str = "settings:";
+// followed by the suggestion id and the chosen work key, or - for none of them
+const WORK_PREFIX: &str = "work:";
+// followed by the event id and the percentage
+const PROGRESS_PREFIX: &str = "progress:";
+// followed by the event id and the stars
+const RATING_PREFIX: &str = "rating:";
+// followed by the event id and the question number
+const QUESTION_PREFIX: &str = "question:";
+// question buttons in one row
+const QUESTION_BUTTONS_ROW: usize = 5;
+const REMINDERS_INTERVAL: Duration = Duration::from_secs(60);
+const CHECKINS_INTERVAL: Duration = Duration::from_secs(600);
+// one of the colors telegram allows for topic icons
+const TOPIC_ICON_COLOR: u32 = 0x6FB9F0;
+const INSIGHTS_OUTBOX_INTERVAL: Duration = Duration::from_secs(30);
 
-async fn command_handler(bot: Bot, msg: Message, cmd: Command) -> ResponseResult<()> {
+async fn command_handler(
+    bot: Bot,
+    msg: Message,
+    cmd: Command,
+    service: Arc<Service>,
+) -> ResponseResult<()> {
     let mut message: String;
+    let user_id = msg.from().map(|user| user.id.0 as i64).unwrap_or_default();
+    let (settings, lang) = club_settings(
+        &service,
+        msg.chat.id,
+        msg.from().and_then(|user| user.language_code.as_deref()),
+    )
+    .await;
+
+    let settings = match settings {
+        Ok(settings) => settings,
+        Err(message) => {
+            bot.send_message(msg.chat.id, message)
+                .disable_notification(true)
+                .await?;
+
+            return Ok(());
+        }
+    };
 
     match cmd {
         Command::Help => {
-            bot.send_message(msg.chat.id, Command::descriptions().to_string())
+            bot.send_message(msg.chat.id, help_text(lang))
                 .disable_notification(true)
                 .await?
         }
         Command::Start => {
-            message = "You're all set up! Now you can create event for your club".to_string();
+            message = tr!(lang, "start-done");
 
-            if let Err(err) = SERVICE.register_new_club(msg.chat.id.0).await {
-                let db_err = err.downcast_ref::<tokio_postgres::Error>().unwrap();
-                if db_err.code().unwrap() == &SqlState::UNIQUE_VIOLATION {
-                    message = "You're already started a club".to_string();
-                }
+            if let Err(err) = service.register_new_club(msg.chat.id.0, user_id).await {
+                message = match err.downcast_ref() {
+                    Some(repository::Error::Conflict) => tr!(lang, "start-already"),
+                    _ => localize(err, lang),
+                };
             }
 
             bot.send_message(msg.chat.id, message)
@@ -67,89 +155,509 @@ async fn command_handler(bot: Bot, msg: Message, cmd: Command) -> ResponseResult
         }
         Command::Event(date) => {
             if date.is_empty() {
-                bot.send_message(
-                    msg.chat.id,
-                    "Please write a date in format -\n/event 2023.07.16 15:00".to_string(),
-                )
-                .await?;
+                bot.send_message(msg.chat.id, tr!(lang, "event-usage"))
+                    .await?;
 
                 return Ok(());
             }
 
-            match SERVICE.new_club_event(msg.chat.id.0, date.as_str()).await {
-                Ok(date) => message = format!("New club event created on {}", date),
-                Err(err) => {
-                    let er = err.downcast_ref::<Err>().unwrap();
-                    message = er.to_string()
+            let scheduled = service
+                .new_club_event(user_id, date.as_str(), &settings, lang)
+                .await
+                .map_err(|err| localize(err, lang));
+
+            match scheduled {
+                Ok(scheduled) => send_scheduled(&bot, msg.chat.id, scheduled).await?,
+                Err(message) => {
+                    bot.send_message(msg.chat.id, message)
+                        .disable_notification(true)
+                        .await?
+                }
+            }
+        }
+        Command::Suggest(suggestion) => {
+            if suggestion.is_empty() {
+                bot.send_message(msg.chat.id, tr!(lang, "suggest-empty"))
+                    .await?;
+
+                return Ok(());
+            }
+
+            let mut keyboard = None;
+            let name = msg.from().map(|user| user.full_name()).unwrap_or_default();
+
+            match service
+                .new_member_suggestion(user_id, &name, suggestion.as_str(), &settings, lang)
+                .await
+            {
+                Ok(Suggestion::Added(text)) => {
+                    message = tr!(lang, "suggest-done", suggestion = text)
                 }
+                Ok(Suggestion::Choose {
+                    suggestion_id,
+                    text,
+                    works,
+                    kind,
+                }) => {
+                    message = tr!(
+                        lang,
+                        "suggest-choose",
+                        suggestion = text,
+                        kind = kind.as_str()
+                    );
+                    keyboard = Some(works_keyboard(suggestion_id, &works, lang));
+                }
+                Err(err) => message = localize(err, lang),
+            }
+
+            let request = bot
+                .send_message(msg.chat.id, message)
+                .disable_notification(true);
+
+            match keyboard {
+                Some(keyboard) => request.reply_markup(keyboard).await?,
+                None => request.await?,
+            }
+        }
+        Command::Insights(track) => {
+            match service
+                .toggle_with_insights(msg.chat.id.0, user_id, track.as_str(), lang)
+                .await
+            {
+                Ok(text) => message = text,
+                Err(err) => message = localize(err, lang),
             }
 
             bot.send_message(msg.chat.id, message)
                 .disable_notification(true)
                 .await?
         }
-        Command::Suggest(suggestion) => {
-            if suggestion.is_empty() {
-                bot.send_message(
-                    msg.chat.id,
-                    "Your suggestion is empty ;(\nFormat - /suggest smth".to_string(),
-                )
+        Command::StartClub(track) => {
+            let mut questions = None;
+
+            match service
+                .start_active_event(user_id, track.as_str(), &settings, lang)
+                .await
+            {
+                Ok(started) => {
+                    message = started.text.unwrap_or_default();
+                    questions = started.questions;
+                }
+                Err(err) => message = markdown::escape(&localize(err, lang)),
+            }
+
+            match questions {
+                Some(list) => {
+                    if !message.is_empty() {
+                        bot.send_message(msg.chat.id, message)
+                            .parse_mode(MarkdownV2)
+                            .disable_notification(true)
+                            .await?;
+                    }
+
+                    bot.send_message(msg.chat.id, list.text.clone())
+                        .parse_mode(MarkdownV2)
+                        .reply_markup(questions_keyboard(&list))
+                        .await?
+                }
+                None => {
+                    bot.send_message(msg.chat.id, message)
+                        .parse_mode(MarkdownV2)
+                        .disable_notification(true)
+                        .await?
+                }
+            }
+        }
+        Command::Question(text) => {
+            if text.trim().is_empty() {
+                bot.send_message(msg.chat.id, tr!(lang, "question-usage"))
+                    .await?;
+
+                return Ok(());
+            }
+
+            let name = msg.from().map(|user| user.full_name()).unwrap_or_default();
+            let asked = service
+                .new_question(msg.chat.id.0, user_id, &name, text.as_str(), lang)
+                .await
+                .map_err(|err| localize(err, lang));
+
+            match asked {
+                Ok(asked) => {
+                    if asked.anonymous {
+                        if let Err(err) = bot.delete_message(msg.chat.id, msg.id).await {
+                            log::warn!("unable to delete anonymous question: {}", err);
+                        }
+                    }
+
+                    message = asked.text;
+                }
+                Err(text) => message = text,
+            }
+
+            bot.send_message(msg.chat.id, message)
+                .disable_notification(true)
+                .await?
+        }
+        Command::Achieve(track) => {
+            if !may_run_event(&bot, &msg, &service, track.as_str(), &settings, lang).await? {
+                return Ok(());
+            }
+
+            let mut rating = None;
+
+            match service
+                .achieve_active_event(user_id, track.as_str(), &settings, lang)
+                .await
+            {
+                Ok(achieved) => {
+                    message = tr!(lang, "achieve-done", date = achieved.date);
+                    rating = achieved.rating;
+                }
+                Err(err) => message = localize(err, lang),
+            }
+
+            let sent = bot
+                .send_message(msg.chat.id, message)
+                .disable_notification(true)
                 .await?;
 
+            match rating {
+                Some(round) => {
+                    bot.send_message(msg.chat.id, round.text)
+                        .reply_markup(rating_keyboard(round.event_id))
+                        .await?
+                }
+                None => sent,
+            }
+        }
+        Command::Review(review) => {
+            if review.trim().is_empty() {
+                bot.send_message(msg.chat.id, tr!(lang, "review-usage"))
+                    .await?;
+
                 return Ok(());
             }
 
-            message = format!("Got it. Your suggestion:\n{}", suggestion);
+            match service
+                .review_event(msg.chat.id.0, user_id, review.as_str(), lang)
+                .await
+            {
+                Ok(text) => message = text,
+                Err(err) => message = localize(err, lang),
+            }
+
+            bot.send_message(msg.chat.id, message)
+                .disable_notification(true)
+                .await?
+        }
+        Command::Pick(args) => {
+            let words: Vec<&str> = args.split_whitespace().collect();
+
+            // "/pick under 150" leaves out what is longer than 150 pages or minutes
+            let (track, under) = match words.as_slice() {
+                [track @ .., "under", limit] => match limit.parse() {
+                    Ok(limit) if limit > 0 => (track.join(" "), Some(limit)),
+                    _ => {
+                        bot.send_message(msg.chat.id, tr!(lang, "pick-filter-usage"))
+                            .disable_notification(true)
+                            .await?;
+
+                        return Ok(());
+                    }
+                },
+                _ => (args.trim().to_string(), None),
+            };
+
+            if !may_run_event(&bot, &msg, &service, track.as_str(), &settings, lang).await? {
+                return Ok(());
+            }
 
-            if let Err(err) = SERVICE
-                .new_member_suggestion(
-                    msg.chat.id.0,
-                    msg.from().unwrap().id.0 as u32,
-                    suggestion.as_str(),
+            let pick = service
+                .pick_from_suggestions(
+                    user_id,
+                    track.as_str(),
+                    under,
+                    msg.chat.title().unwrap_or_default(),
+                    &settings,
+                    lang,
                 )
                 .await
+                .map_err(|err| localize(err, lang));
+
+            let mut picked = false;
+
+            match pick {
+                Ok(Pick::Picked(text)) => {
+                    message = text;
+                    picked = true;
+                }
+                Ok(Pick::StartPoll {
+                    event_id,
+                    question,
+                    options,
+                    skipped,
+                }) => {
+                    let poll = bot
+                        .send_poll(msg.chat.id, question, options)
+                        .is_anonymous(false)
+                        .disable_notification(true)
+                        .await?;
+
+                    if let Err(err) = service
+                        .save_pick_poll(msg.chat.id.0, user_id, event_id, poll.id.0)
+                        .await
+                    {
+                        log::error!("unable to save pick poll: {}", err);
+                    }
+
+                    message = match skipped {
+                        0 => markdown::escape(&tr!(lang, "pick-poll-started")),
+                        _ => markdown::escape(&format!(
+                            "{}\n{}",
+                            tr!(lang, "pick-poll-started"),
+                            skipped_note(skipped, settings.kind, lang)
+                        )),
+                    }
+                }
+                Ok(Pick::ClosePoll { message_id }) => {
+                    // a poll closed by hand can't be stopped, so the pick falls back to random
+                    let votes = match bot.stop_poll(msg.chat.id, MessageId(message_id)).await {
+                        Ok(poll) => poll
+                            .options
+                            .into_iter()
+                            .map(|option| (option.text, option.voter_count))
+                            .collect(),
+                        Err(err) => {
+                            log::warn!("unable to stop pick poll: {}", err);
+                            vec![]
+                        }
+                    };
+
+                    match service
+                        .pick_from_poll(
+                            user_id,
+                            track.as_str(),
+                            msg.chat.title().unwrap_or_default(),
+                            votes,
+                            &settings,
+                            lang,
+                        )
+                        .await
+                    {
+                        Ok(text) => {
+                            message = text;
+                            picked = true;
+                        }
+                        Err(err) => message = markdown::escape(&localize(err, lang)),
+                    }
+                }
+                Err(text) => message = markdown::escape(&text),
+            }
+
+            let sent = bot
+                .send_message(msg.chat.id, message)
+                .disable_web_page_preview(true)
+                .disable_notification(true)
+                .parse_mode(MarkdownV2)
+                .await?;
+
+            // the thread is opened right away, so talk about the pick has a place to go
+            if picked {
+                let discussion = service
+                    .discussion(msg.chat.id.0, track.as_str(), lang)
+                    .await
+                    .map_err(|err| err.to_string());
+
+                match discussion {
+                    Ok(discussion) => {
+                        open_discussion(&bot, &msg.chat, user_id, &service, discussion).await?;
+                    }
+                    Err(err) => log::error!("unable to open discussion: {}", err),
+                }
+            }
+
+            sent
+        }
+        Command::Current(track) => {
+            match service
+                .get_current_event_info(track.as_str(), &settings, lang)
+                .await
             {
-                let er = err.downcast_ref::<Err>().unwrap();
-                message = er.to_string()
+                Ok(text) => message = text,
+                Err(err) => message = markdown::escape(&localize(err, lang)),
             }
 
             bot.send_message(msg.chat.id, message)
+                .parse_mode(MarkdownV2)
                 .disable_notification(true)
                 .await?
         }
-        Command::Insights => {
-            match SERVICE.toggle_with_insights(msg.chat.id.0).await {
-                Ok(text) => message = text,
-                Err(err) => {
-                    let er = err.downcast_ref::<Err>().unwrap();
-                    message = er.to_string()
+        Command::Where(args) => {
+            let scheduled = service
+                .event_location(
+                    user_id,
+                    args.as_str(),
+                    shared_location(&msg),
+                    &settings,
+                    lang,
+                )
+                .await
+                .map_err(|err| localize(err, lang));
+
+            match scheduled {
+                Ok(scheduled) => send_scheduled(&bot, msg.chat.id, scheduled).await?,
+                Err(message) => {
+                    bot.send_message(msg.chat.id, message)
+                        .disable_notification(true)
+                        .await?
+                }
+            }
+        }
+        Command::Ics(track) => {
+            let file = service
+                .event_calendar(track.as_str(), &settings, lang)
+                .await
+                .map_err(|err| localize(err, lang));
+
+            match file {
+                Ok(file) => {
+                    bot.send_document(
+                        msg.chat.id,
+                        InputFile::memory(file.content.into_bytes()).file_name(file.name),
+                    )
+                    .disable_notification(true)
+                    .await?
                 }
+                Err(message) => {
+                    bot.send_message(msg.chat.id, message)
+                        .disable_notification(true)
+                        .await?
+                }
+            }
+        }
+        Command::Calendar(args) => {
+            let reset = match args.trim() {
+                "" => false,
+                "reset" => true,
+                _ => {
+                    bot.send_message(msg.chat.id, tr!(lang, "calendar-usage"))
+                        .disable_notification(true)
+                        .await?;
+
+                    return Ok(());
+                }
+            };
+
+            // anyone with the old link keeps seeing the club's events, so only admins replace it
+            if reset {
+                let Some(user) = msg.from() else {
+                    return Ok(());
+                };
+
+                if !is_admin(&bot, &msg.chat, user.id).await? {
+                    bot.send_message(msg.chat.id, tr!(lang, "calendar-admins-only"))
+                        .disable_notification(true)
+                        .await?;
+
+                    return Ok(());
+                }
+            }
+
+            match service
+                .calendar_link(msg.chat.id.0, user_id, reset, lang)
+                .await
+            {
+                Ok(text) => message = text,
+                Err(err) => message = localize(err, lang),
             }
 
             bot.send_message(msg.chat.id, message)
+                .disable_web_page_preview(true)
                 .disable_notification(true)
                 .await?
         }
-        Command::StartClub => {
-            match SERVICE.start_active_event(msg.chat.id.0).await {
+        Command::Progress(args) => {
+            if args.trim().is_empty() {
+                bot.send_message(msg.chat.id, tr!(lang, "progress-usage"))
+                    .await?;
+
+                return Ok(());
+            }
+
+            let name = msg.from().map(|user| user.full_name()).unwrap_or_default();
+
+            match service
+                .record_progress(msg.chat.id.0, user_id, &name, args.as_str(), lang)
+                .await
+            {
                 Ok(text) => message = text,
-                Err(err) => {
-                    let er = err.downcast_ref::<Err>().unwrap();
-                    message = er.to_string()
-                }
+                Err(err) => message = localize(err, lang),
             }
 
             bot.send_message(msg.chat.id, message)
-                .parse_mode(MarkdownV2)
                 .disable_notification(true)
                 .await?
         }
-        Command::Achieve => {
-            match SERVICE.achieve_active_event(msg.chat.id.0).await {
-                Ok(date) => message = format!("Ok, event on {} is achieved", date),
+        Command::Spoiler(args) => {
+            if args.trim().is_empty() {
+                bot.send_message(msg.chat.id, tr!(lang, "spoiler-usage"))
+                    .await?;
+
+                return Ok(());
+            }
+
+            let name = msg.from().map(|user| user.full_name()).unwrap_or_default();
+            let spoiler = service
+                .spoiler(msg.chat.id.0, &name, args.as_str(), lang)
+                .await
+                .map_err(|err| localize(err, lang));
+
+            let spoiler = match spoiler {
+                Ok(spoiler) => spoiler,
+                Err(text) => {
+                    bot.send_message(msg.chat.id, text)
+                        .disable_notification(true)
+                        .await?;
+
+                    return Ok(());
+                }
+            };
+
+            // the command itself shows the spoiler in the open
+            if let Err(err) = bot.delete_message(msg.chat.id, msg.id).await {
+                log::warn!("unable to delete spoiler command: {}", err);
+            }
+
+            let thread =
+                open_discussion(&bot, &msg.chat, user_id, &service, spoiler.discussion).await?;
+            let request = bot
+                .send_message(msg.chat.id, spoiler.text)
+                .parse_mode(MarkdownV2)
+                .disable_notification(true);
+
+            match thread.kind {
+                DiscussionKind::Topic => request.message_thread_id(thread.id).await?,
+                DiscussionKind::Reply => {
+                    request
+                        .reply_to_message_id(MessageId(thread.id))
+                        .allow_sending_without_reply(true)
+                        .await?
+                }
+            }
+        }
+        Command::Log(limit) => {
+            let limit = limit
+                .trim()
+                .parse::<i64>()
+                .unwrap_or(DEFAULT_LOG_LIMIT)
+                .clamp(1, MAX_LOG_LIMIT);
+
+            match service.get_audit_log(limit, &settings, lang).await {
+                Ok(text) => message = text,
                 Err(err) => {
-                    let er = err.downcast_ref::<Err>().unwrap();
-                    message = er.to_string()
+                    log::error!("unable to get audit log: {}", err);
+                    message = tr!(lang, "log-failed")
                 }
             }
 
@@ -157,59 +665,1053 @@ async fn command_handler(bot: Bot, msg: Message, cmd: Command) -> ResponseResult
                 .disable_notification(true)
                 .await?
         }
-        Command::Pick => {
-            match SERVICE.pick_from_suggestions(msg.chat.id.0).await {
+        Command::History(limit) => {
+            let limit = limit
+                .trim()
+                .parse::<i64>()
+                .unwrap_or(DEFAULT_HISTORY_LIMIT)
+                .clamp(1, MAX_HISTORY_LIMIT);
+
+            match service.get_history(limit, &settings, lang).await {
                 Ok(text) => message = text,
                 Err(err) => {
-                    let er = err.downcast_ref::<Err>().unwrap();
-                    message = er.to_string()
+                    log::error!("unable to get history: {}", err);
+                    message = tr!(lang, "history-failed")
                 }
             }
 
             bot.send_message(msg.chat.id, message)
-                .disable_web_page_preview(true)
                 .disable_notification(true)
-                .parse_mode(MarkdownV2)
                 .await?
         }
-        Command::Current => {
-            match SERVICE.get_current_event_info(msg.chat.id.0).await {
+        Command::Stats => {
+            match service.get_stats(msg.chat.id.0, lang).await {
                 Ok(text) => message = text,
                 Err(err) => {
-                    let er = err.downcast_ref::<Err>().unwrap();
-                    message = er.to_string()
+                    log::error!("unable to get stats: {}", err);
+                    message = tr!(lang, "stats-failed")
                 }
             }
 
             bot.send_message(msg.chat.id, message)
-                .parse_mode(MarkdownV2)
                 .disable_notification(true)
                 .await?
         }
+        Command::Hosts(args) => {
+            let words: Vec<&str> = args.split_whitespace().collect();
+            let Some(user) = msg.from() else {
+                return Ok(());
+            };
+
+            let localized = |err: Box<dyn std::error::Error>| localize(err, lang);
+
+            // anyone can join or leave the rotation, changing it for others is up to admins
+            let result = match words.as_slice() {
+                [] | ["list"] => service.hosts(msg.chat.id.0, lang).await.map_err(localized),
+                ["add"] => {
+                    let target = target_user(&msg).unwrap_or(user);
+
+                    if target.id != user.id && !is_admin(&bot, &msg.chat, user.id).await? {
+                        bot.send_message(msg.chat.id, tr!(lang, "hosts-admins-only"))
+                            .disable_notification(true)
+                            .await?;
+
+                        return Ok(());
+                    }
+
+                    service
+                        .add_host(
+                            msg.chat.id.0,
+                            user_id,
+                            target.id.0 as i64,
+                            &target.full_name(),
+                            lang,
+                        )
+                        .await
+                        .map_err(localized)
+                }
+                ["remove", rest @ ..] => {
+                    let target = match rest {
+                        [number] => match number.parse() {
+                            Ok(number) => HostRef::Number(number),
+                            Err(_) => HostRef::User(user_id),
+                        },
+                        _ => HostRef::User(
+                            target_user(&msg).map_or(user_id, |target| target.id.0 as i64),
+                        ),
+                    };
+
+                    if !matches!(target, HostRef::User(id) if id == user_id)
+                        && !is_admin(&bot, &msg.chat, user.id).await?
+                    {
+                        bot.send_message(msg.chat.id, tr!(lang, "hosts-admins-only"))
+                            .disable_notification(true)
+                            .await?;
+
+                        return Ok(());
+                    }
+
+                    service
+                        .remove_host(msg.chat.id.0, user_id, target, lang)
+                        .await
+                        .map_err(localized)
+                }
+                _ => {
+                    bot.send_message(msg.chat.id, tr!(lang, "hosts-usage"))
+                        .disable_notification(true)
+                        .await?;
+
+                    return Ok(());
+                }
+            };
+
+            message = result.unwrap_or_else(|err| err);
+
+            bot.send_message(msg.chat.id, message)
+                .disable_notification(true)
+                .await?
+        }
+        Command::SwapHost(args) => {
+            let (Some(user), Some(target)) = (msg.from(), target_user(&msg)) else {
+                bot.send_message(msg.chat.id, tr!(lang, "swaphost-usage"))
+                    .disable_notification(true)
+                    .await?;
+
+                return Ok(());
+            };
+
+            let host = Member {
+                user_id: target.id.0 as i64,
+                name: Some(target.full_name()),
+            };
+            let privileged = is_admin(&bot, &msg.chat, user.id).await?;
+
+            match service
+                .swap_host(user_id, privileged, args.as_str(), host, &settings, lang)
+                .await
+            {
+                Ok(text) => message = text,
+                Err(err) => message = localize(err, lang),
+            }
+
+            bot.send_message(msg.chat.id, message)
+                .disable_notification(true)
+                .await?
+        }
+        Command::Settings(args) => {
+            let args: Vec<&str> = args.split_whitespace().collect();
+
+            let change = match args.as_slice() {
+                [] => None,
+                ["timezone", name] => Some(SettingChange::TimeZone(name.to_string())),
+                ["kind", name] => Some(SettingChange::Kind(name.to_string())),
+                ["limit", limit] => match limit.parse() {
+                    Ok(limit) => Some(SettingChange::SuggestionLimit(limit)),
+                    Err(_) => Some(SettingChange::SuggestionLimit(-1)),
+                },
+                _ => {
+                    bot.send_message(msg.chat.id, tr!(lang, "settings-usage"))
+                        .await?;
+
+                    return Ok(());
+                }
+            };
+
+            let settings = match change {
+                None => Ok(settings),
+                Some(change) => {
+                    let allowed = match msg.from() {
+                        Some(user) => is_admin(&bot, &msg.chat, user.id).await?,
+                        None => false,
+                    };
+
+                    if !allowed {
+                        bot.send_message(msg.chat.id, tr!(lang, "settings-admins-only"))
+                            .disable_notification(true)
+                            .await?;
+
+                        return Ok(());
+                    }
+
+                    service.update_settings(user_id, &settings, change).await
+                }
+            }
+            .map_err(|err| match err.downcast_ref::<Err>() {
+                Some(er) => er.localize(lang),
+                None => {
+                    log::error!("unable to change settings: {}", err);
+                    tr!(lang, "settings-failed")
+                }
+            });
+
+            match settings {
+                Ok(settings) => {
+                    bot.send_message(msg.chat.id, tr!(lang, "settings-title"))
+                        .reply_markup(settings_keyboard(&settings, lang))
+                        .disable_notification(true)
+                        .await?
+                }
+                Err(text) => {
+                    bot.send_message(msg.chat.id, text)
+                        .disable_notification(true)
+                        .await?
+                }
+            }
+        }
     };
 
     Ok(())
 }
 
-pub async fn run() {
-    dotenv().ok();
-    pretty_env_logger::init();
+async fn callback_handler(bot: Bot, q: CallbackQuery, service: Arc<Service>) -> ResponseResult<()> {
+    let (Some(data), Some(msg)) = (q.data.as_ref(), q.message.as_ref()) else {
+        return Ok(());
+    };
+
+    if let Some(choice) = data.strip_prefix(WORK_PREFIX) {
+        return work_chosen(bot, &q, msg, choice, service).await;
+    }
+
+    if let Some(mark) = data.strip_prefix(PROGRESS_PREFIX) {
+        return progress_marked(bot, &q, msg, mark, service).await;
+    }
 
-    let bot = Bot::from_env();
+    if let Some(rating) = data.strip_prefix(RATING_PREFIX) {
+        return rating_given(bot, &q, msg, rating, service).await;
+    }
 
-    bot.set_my_commands(Command::bot_commands())
+    if let Some(question) = data.strip_prefix(QUESTION_PREFIX) {
+        return question_covered(bot, &q, msg, question, service).await;
+    }
+
+    let Some(setting) = data.strip_prefix(SETTINGS_PREFIX) else {
+        return Ok(());
+    };
+
+    let (settings, lang) =
+        club_settings(&service, msg.chat.id, q.from.language_code.as_deref()).await;
+
+    let settings = match settings {
+        Ok(settings) => settings,
+        Err(text) => {
+            bot.answer_callback_query(q.id.clone())
+                .text(text)
+                .show_alert(true)
+                .await?;
+
+            return Ok(());
+        }
+    };
+
+    let change = match setting.split_once(':') {
+        None if setting == "insights" => SettingChange::ToggleDefaultInsights,
+        None if setting == "kind" => SettingChange::NextKind,
+        None if setting == "host_only" => SettingChange::ToggleHostOnly,
+        None if setting == "pick_mode" => SettingChange::NextPickMode,
+        None if setting == "limit" => SettingChange::NextSuggestionLimit,
+        None if setting == "language" => SettingChange::NextLanguage,
+        Some(("reminder", offset)) => match offset.parse() {
+            Ok(offset) => SettingChange::ToggleReminder(offset),
+            Err(_) => return Ok(()),
+        },
+        None if setting == "timezone" => {
+            bot.answer_callback_query(q.id)
+                .text(tr!(lang, "settings-timezone-hint"))
+                .show_alert(true)
+                .await?;
+
+            return Ok(());
+        }
+        _ => {
+            bot.answer_callback_query(q.id)
+                .text(tr!(lang, "settings-reminders-hint"))
+                .await?;
+
+            return Ok(());
+        }
+    };
+
+    if !is_admin(&bot, &msg.chat, q.from.id).await? {
+        bot.answer_callback_query(q.id)
+            .text(tr!(lang, "settings-admins-only"))
+            .await?;
+
+        return Ok(());
+    }
+
+    let settings = service
+        .update_settings(q.from.id.0 as i64, &settings, change)
+        .await
+        .map_err(|err| err.to_string());
+
+    match settings {
+        Ok(settings) => {
+            // language may have just changed, so the menu is rendered in the new one
+            let lang = i18n::resolve_language(
+                settings.language.as_deref(),
+                q.from.language_code.as_deref(),
+            );
+
+            bot.edit_message_text(msg.chat.id, msg.id, tr!(lang, "settings-title"))
+                .reply_markup(settings_keyboard(&settings, lang))
+                .await?;
+
+            bot.answer_callback_query(q.id).await?;
+        }
+        Err(err) => {
+            log::error!("unable to change settings: {}", err);
+
+            bot.answer_callback_query(q.id)
+                .text(tr!(lang, "settings-failed"))
+                .show_alert(true)
+                .await?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn work_chosen(
+    bot: Bot,
+    q: &CallbackQuery,
+    msg: &Message,
+    choice: &str,
+    service: Arc<Service>,
+) -> ResponseResult<()> {
+    let Some((Ok(suggestion_id), key)) = choice
+        .split_once(':')
+        .map(|(id, key)| (id.parse::<i64>(), key))
+    else {
+        return Ok(());
+    };
+
+    let (settings, lang) =
+        club_settings(&service, msg.chat.id, q.from.language_code.as_deref()).await;
+
+    let settings = match settings {
+        Ok(settings) => settings,
+        Err(text) => {
+            bot.answer_callback_query(q.id.clone())
+                .text(text)
+                .show_alert(true)
+                .await?;
+
+            return Ok(());
+        }
+    };
+
+    let result = service
+        .choose_work(
+            q.from.id.0 as i64,
+            suggestion_id,
+            (key != "-").then_some(key),
+            &settings,
+            lang,
+        )
+        .await
+        .map_err(|err| match err.downcast_ref::<Err>() {
+            Some(er) => Ok(er.localize(lang)),
+            None => Err(err.to_string()),
+        });
+
+    match result {
+        Ok(text) => {
+            bot.edit_message_text(
+                msg.chat.id,
+                msg.id,
+                tr!(lang, "suggest-done", suggestion = text),
+            )
+            .await?;
+
+            bot.answer_callback_query(q.id.clone()).await?;
+        }
+        // someone else tapped the button, the question stays for the suggester
+        Err(Ok(text)) => {
+            bot.answer_callback_query(q.id.clone())
+                .text(text)
+                .show_alert(true)
+                .await?;
+        }
+        Err(Err(err)) => {
+            log::error!("unable to save chosen work: {}", err);
+
+            bot.answer_callback_query(q.id.clone()).await?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn progress_marked(
+    bot: Bot,
+    q: &CallbackQuery,
+    msg: &Message,
+    mark: &str,
+    service: Arc<Service>,
+) -> ResponseResult<()> {
+    let Some((Ok(event_id), Ok(percent))) = mark
+        .split_once(':')
+        .map(|(id, percent)| (Uuid::parse_str(id), percent.parse::<i32>()))
+    else {
+        return Ok(());
+    };
+
+    let lang = service
+        .language(msg.chat.id.0, q.from.language_code.as_deref())
+        .await;
+
+    let result = service
+        .checkin_progress(
+            msg.chat.id.0,
+            q.from.id.0 as i64,
+            &q.from.full_name(),
+            event_id,
+            percent,
+            lang,
+        )
+        .await
+        .map_err(|err| match err.downcast_ref::<Err>() {
+            Some(er) => Ok(er.localize(lang)),
+            None => Err(err.to_string()),
+        });
+
+    match result {
+        Ok(text) => {
+            bot.answer_callback_query(q.id.clone()).text(text).await?;
+        }
+        // the event of an old check-in is already over
+        Err(Ok(text)) => {
+            bot.answer_callback_query(q.id.clone())
+                .text(text)
+                .show_alert(true)
+                .await?;
+        }
+        Err(Err(err)) => {
+            log::error!("unable to save progress: {}", err);
+
+            bot.answer_callback_query(q.id.clone()).await?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn rating_given(
+    bot: Bot,
+    q: &CallbackQuery,
+    msg: &Message,
+    rating: &str,
+    service: Arc<Service>,
+) -> ResponseResult<()> {
+    let Some((Ok(event_id), Ok(stars))) = rating
+        .split_once(':')
+        .map(|(id, stars)| (Uuid::parse_str(id), stars.parse::<i32>()))
+    else {
+        return Ok(());
+    };
+
+    let lang = service
+        .language(msg.chat.id.0, q.from.language_code.as_deref())
+        .await;
+
+    let result = service
+        .rate_event(
+            msg.chat.id.0,
+            q.from.id.0 as i64,
+            &q.from.full_name(),
+            event_id,
+            stars,
+            lang,
+        )
+        .await
+        .map_err(|err| match err.downcast_ref::<Err>() {
+            Some(er) => Ok(er.localize(lang)),
+            None => Err(err.to_string()),
+        });
+
+    match result {
+        Ok(text) => {
+            bot.answer_callback_query(q.id.clone()).text(text).await?;
+        }
+        Err(Ok(text)) => {
+            bot.answer_callback_query(q.id.clone())
+                .text(text)
+                .show_alert(true)
+                .await?;
+        }
+        Err(Err(err)) => {
+            log::error!("unable to save rating: {}", err);
+
+            bot.answer_callback_query(q.id.clone()).await?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn question_covered(
+    bot: Bot,
+    q: &CallbackQuery,
+    msg: &Message,
+    question: &str,
+    service: Arc<Service>,
+) -> ResponseResult<()> {
+    let Some((Ok(event_id), Ok(number))) = question
+        .split_once(':')
+        .map(|(id, number)| (Uuid::parse_str(id), number.parse::<i32>()))
+    else {
+        return Ok(());
+    };
+
+    let lang = service
+        .language(msg.chat.id.0, q.from.language_code.as_deref())
+        .await;
+    let privileged = is_admin(&bot, &msg.chat, q.from.id).await?;
+
+    let result = service
+        .cover_question(
+            msg.chat.id.0,
+            q.from.id.0 as i64,
+            privileged,
+            event_id,
+            number,
+            lang,
+        )
+        .await
+        .map_err(|err| match err.downcast_ref::<Err>() {
+            Some(er) => Ok(er.localize(lang)),
+            None => Err(err.to_string()),
+        });
+
+    match result {
+        Ok(list) => {
+            bot.edit_message_text(msg.chat.id, msg.id, list.text.clone())
+                .parse_mode(MarkdownV2)
+                .reply_markup(questions_keyboard(&list))
+                .await?;
+
+            bot.answer_callback_query(q.id.clone()).await?;
+        }
+        Err(Ok(text)) => {
+            bot.answer_callback_query(q.id.clone())
+                .text(text)
+                .show_alert(true)
+                .await?;
+        }
+        Err(Err(err)) => {
+            log::error!("unable to mark question: {}", err);
+
+            bot.answer_callback_query(q.id.clone()).await?;
+        }
+    }
+
+    Ok(())
+}
+
+// a forum topic where topics are on and the bot may create them, replies to its own message otherwise
+async fn open_discussion(
+    bot: &Bot,
+    chat: &Chat,
+    user_id: i64,
+    service: &Service,
+    discussion: Discussion,
+) -> ResponseResult<DiscussionThread> {
+    if let Some(thread) = discussion.thread {
+        return Ok(thread);
+    }
+
+    let topic = match is_forum(chat) {
+        true => bot
+            .create_forum_topic(chat.id, discussion.topic, TOPIC_ICON_COLOR, "")
+            .await
+            .map_err(|err| log::warn!("unable to create forum topic: {}", err))
+            .ok(),
+        false => None,
+    };
+
+    let thread = match topic {
+        Some(topic) => {
+            bot.send_message(chat.id, discussion.announcement)
+                .message_thread_id(topic.message_thread_id)
+                .await?;
+
+            DiscussionThread {
+                kind: DiscussionKind::Topic,
+                id: topic.message_thread_id,
+            }
+        }
+        None => {
+            let sent = bot
+                .send_message(chat.id, discussion.announcement)
+                .disable_notification(true)
+                .await?;
+
+            DiscussionThread {
+                kind: DiscussionKind::Reply,
+                id: sent.id.0,
+            }
+        }
+    };
+
+    if let Err(err) = service
+        .save_discussion(chat.id.0, user_id, discussion.event_id, thread)
+        .await
+    {
+        log::error!("unable to save discussion thread: {}", err);
+    }
+
+    Ok(thread)
+}
+
+// club errors explain themselves, anything else is logged and gets a generic reply
+fn localize(err: Box<dyn std::error::Error>, lang: &str) -> String {
+    match err.downcast_ref::<Err>() {
+        Some(er) => er.localize(lang),
+        None => {
+            log::error!("unable to handle command: {}", err);
+            tr!(lang, "error-unexpected")
+        }
+    }
+}
+
+fn is_forum(chat: &Chat) -> bool {
+    matches!(
+        &chat.kind,
+        ChatKind::Public(ChatPublic {
+            kind: PublicChatKind::Supergroup(supergroup),
+            ..
+        }) if supergroup.is_forum
+    )
+}
+
+// the reply goes first, a map pin follows it when the place has coordinates
+async fn send_scheduled(
+    bot: &Bot,
+    chat_id: ChatId,
+    scheduled: Scheduled,
+) -> ResponseResult<Message> {
+    let sent = bot
+        .send_message(chat_id, scheduled.text)
+        .disable_notification(true)
+        .await?;
+
+    if let Some(venue) = scheduled.venue {
+        bot.send_venue(
+            chat_id,
+            venue.coordinates.latitude,
+            venue.coordinates.longitude,
+            venue.title,
+            venue.address,
+        )
+        .disable_notification(true)
+        .await?;
+    }
+
+    Ok(sent)
+}
+
+// a venue or a map location the command replies to
+fn shared_location(msg: &Message) -> Option<Location> {
+    let reply = msg.reply_to_message()?;
+
+    if let Some(venue) = reply.venue() {
+        let address = match venue.address.is_empty() {
+            true => venue.title.clone(),
+            false => format!("{}, {}", venue.title, venue.address),
+        };
+
+        return Some(Location {
+            address,
+            coordinates: Some(Coordinates {
+                latitude: venue.location.latitude,
+                longitude: venue.location.longitude,
+            }),
+        });
+    }
+
+    let location = reply.location()?;
+
+    Some(Location {
+        address: format!("{}, {}", location.latitude, location.longitude),
+        coordinates: Some(Coordinates {
+            latitude: location.latitude,
+            longitude: location.longitude,
+        }),
+    })
+}
+
+// settings are read once per update, its language and every service call share them
+async fn club_settings(
+    service: &Service,
+    chat_id: ChatId,
+    user_language: Option<&str>,
+) -> (Result<ClubSettings, String>, &'static str) {
+    let settings = service.settings(chat_id.0).await;
+    let club_language = settings
+        .as_ref()
+        .ok()
+        .and_then(|settings| settings.language.as_deref());
+    let lang = i18n::resolve_language(club_language, user_language);
+
+    (settings.map_err(|err| localize(err, lang)), lang)
+}
+
+// with host_only on, only the event's host and chat admins may pick or achieve it
+async fn may_run_event(
+    bot: &Bot,
+    msg: &Message,
+    service: &Service,
+    track: &str,
+    settings: &ClubSettings,
+    lang: &str,
+) -> ResponseResult<bool> {
+    let Some(user) = msg.from() else {
+        return Ok(true);
+    };
+
+    let host = service
+        .host_required(user.id.0 as i64, track, settings, lang)
         .await
-        .expect("Failed to set bot commands");
+        .map_err(|err| err.to_string());
+
+    let host = match host {
+        Ok(Some(host)) => host,
+        Ok(None) => return Ok(true),
+        Err(err) => {
+            log::error!("unable to check the event's host: {}", err);
+            return Ok(true);
+        }
+    };
+
+    if is_admin(bot, &msg.chat, user.id).await? {
+        return Ok(true);
+    }
+
+    bot.send_message(msg.chat.id, tr!(lang, "host-only", host = host))
+        .disable_notification(true)
+        .await?;
+
+    Ok(false)
+}
+
+// the member a command is about: the author of the message it replies to or someone
+// mentioned without a username, telegram gives no id for @username mentions
+fn target_user(msg: &Message) -> Option<&User> {
+    let replied = msg
+        .reply_to_message()
+        // in forum topics every message replies to the topic's first one
+        .filter(|reply| Some(reply.id.0) != msg.thread_id)
+        .and_then(|reply| reply.from())
+        .filter(|user| !user.is_bot);
+
+    replied.or_else(|| {
+        msg.entities()?
+            .iter()
+            .find_map(|entity| match &entity.kind {
+                MessageEntityKind::TextMention { user } => Some(user),
+                _ => None,
+            })
+    })
+}
+
+async fn is_admin(bot: &Bot, chat: &Chat, user_id: UserId) -> ResponseResult<bool> {
+    if chat.is_private() {
+        return Ok(true);
+    }
+
+    let member = bot.get_chat_member(chat.id, user_id).await?;
+
+    Ok(member.is_privileged())
+}
+
+fn works_keyboard(suggestion_id: i64, works: &[Work], lang: &str) -> InlineKeyboardMarkup {
+    let button = |text: String, key: &str| {
+        InlineKeyboardButton::callback(text, format!("{}{}:{}", WORK_PREFIX, suggestion_id, key))
+    };
+
+    let mut rows: Vec<Vec<InlineKeyboardButton>> = works
+        .iter()
+        .map(|work| {
+            let text = match (&work.author, work.year) {
+                (Some(author), Some(year)) => format!("{}, {} ({})", work.title, author, year),
+                (Some(author), None) => format!("{}, {}", work.title, author),
+                (None, Some(year)) => format!("{} ({})", work.title, year),
+                (None, None) => work.title.clone(),
+            };
+
+            vec![button(text, &work.key)]
+        })
+        .collect();
+
+    rows.push(vec![button(tr!(lang, "suggest-choose-none"), "-")]);
+
+    InlineKeyboardMarkup::new(rows)
+}
+
+fn progress_keyboard(event_id: Uuid) -> InlineKeyboardMarkup {
+    let buttons = PROGRESS_STEPS
+        .iter()
+        .map(|percent| {
+            InlineKeyboardButton::callback(
+                format!("{}%", percent),
+                format!("{}{}:{}", PROGRESS_PREFIX, event_id, percent),
+            )
+        })
+        .collect::<Vec<_>>();
 
-    let handler = dptree::entry().branch(
-        Update::filter_message()
-            .filter_command::<Command>()
-            .endpoint(command_handler),
-    );
+    InlineKeyboardMarkup::new(vec![buttons])
+}
+
+fn questions_keyboard(list: &QuestionList) -> InlineKeyboardMarkup {
+    let buttons = list
+        .numbers
+        .iter()
+        .map(|(number, covered)| {
+            let text = match covered {
+                true => format!("✓ {}", number),
+                false => number.to_string(),
+            };
+
+            InlineKeyboardButton::callback(
+                text,
+                format!("{}{}:{}", QUESTION_PREFIX, list.event_id, number),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    InlineKeyboardMarkup::new(buttons.chunks(QUESTION_BUTTONS_ROW).map(|row| row.to_vec()))
+}
+
+fn rating_keyboard(event_id: Uuid) -> InlineKeyboardMarkup {
+    let buttons = (1..=MAX_STARS)
+        .map(|stars| {
+            InlineKeyboardButton::callback(
+                format!("{}★", stars),
+                format!("{}{}:{}", RATING_PREFIX, event_id, stars),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    InlineKeyboardMarkup::new(vec![buttons])
+}
+
+fn settings_keyboard(settings: &ClubSettings, lang: &str) -> InlineKeyboardMarkup {
+    let button = |text: String, setting: &str| {
+        InlineKeyboardButton::callback(text, format!("{}{}", SETTINGS_PREFIX, setting))
+    };
+
+    let reminders = REMINDER_OFFSETS
+        .iter()
+        .map(|offset| {
+            let label = describe_offset(*offset, lang, true);
+            let text = if settings.reminder_offsets.contains(offset) {
+                format!("✓ {}", label)
+            } else {
+                label
+            };
+
+            button(text, format!("reminder:{}", offset).as_str())
+        })
+        .collect::<Vec<_>>();
+
+    InlineKeyboardMarkup::new(vec![
+        vec![button(
+            tr!(lang, "settings-kind", kind = settings.kind.as_str()),
+            "kind",
+        )],
+        vec![button(
+            tr!(
+                lang,
+                "settings-insights",
+                enabled = settings.default_insights.to_string()
+            ),
+            "insights",
+        )],
+        vec![button(
+            tr!(
+                lang,
+                "settings-pick-mode",
+                mode = settings.pick_mode.as_str()
+            ),
+            "pick_mode",
+        )],
+        vec![button(
+            tr!(lang, "settings-limit", limit = settings.suggestion_limit),
+            "limit",
+        )],
+        vec![button(
+            tr!(
+                lang,
+                "settings-host-only",
+                enabled = settings.host_only.to_string()
+            ),
+            "host_only",
+        )],
+        vec![button(
+            tr!(
+                lang,
+                "settings-language",
+                language = settings.language.as_deref().unwrap_or("auto")
+            ),
+            "language",
+        )],
+        vec![button(
+            tr!(lang, "settings-time-zone", tz = settings.time_zone.as_str()),
+            "timezone",
+        )],
+        vec![button(tr!(lang, "settings-reminders"), "reminders")],
+        reminders,
+    ])
+}
+
+fn help_text(lang: &str) -> String {
+    let lines: Vec<String> = localized_commands(lang)
+        .into_iter()
+        .map(|command| format!("{} — {}", command.command, command.description))
+        .collect();
+
+    format!("{}\n\n{}", tr!(lang, "commands-title"), lines.join("\n"))
+}
+
+fn localized_commands(lang: &str) -> Vec<BotCommand> {
+    Command::bot_commands()
+        .into_iter()
+        .map(|command| {
+            let key = format!("command-{}", command.command.trim_start_matches('/'));
+            BotCommand::new(command.command, tr!(lang, &key))
+        })
+        .collect()
+}
+
+async fn send_reminders(bot: Bot, service: Arc<Service>) {
+    let mut interval = tokio::time::interval(REMINDERS_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        let reminders = match service.due_reminders().await {
+            Ok(reminders) => reminders,
+            Err(err) => {
+                log::error!("unable to get due reminders: {}", err);
+                continue;
+            }
+        };
+
+        for reminder in reminders {
+            if let Err(err) = bot
+                .send_message(ChatId(reminder.chat_id), reminder.text)
+                .parse_mode(MarkdownV2)
+                .await
+            {
+                log::error!("unable to send reminder to {}: {}", reminder.chat_id, err);
+            }
+        }
+    }
+}
+
+async fn send_checkins(bot: Bot, service: Arc<Service>) {
+    let mut interval = tokio::time::interval(CHECKINS_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        let checkins = match service.due_checkins().await {
+            Ok(checkins) => checkins,
+            Err(err) => {
+                log::error!("unable to get due check-ins: {}", err);
+                continue;
+            }
+        };
+
+        for checkin in checkins {
+            if let Err(err) = bot
+                .send_message(ChatId(checkin.chat_id), checkin.text)
+                .reply_markup(progress_keyboard(checkin.event_id))
+                .await
+            {
+                log::error!("unable to send check-in to {}: {}", checkin.chat_id, err);
+            }
+        }
+    }
+}
+
+async fn deliver_insights_outbox(bot: Bot, service: Arc<Service>) {
+    let mut interval = tokio::time::interval(INSIGHTS_OUTBOX_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        let notices = match service.deliver_insights_outbox().await {
+            Ok(notices) => notices,
+            Err(err) => {
+                log::error!("unable to deliver insights outbox: {}", err);
+                continue;
+            }
+        };
+
+        for notice in notices {
+            if let Err(err) = bot
+                .send_message(ChatId(notice.chat_id), notice.text)
+                .disable_notification(true)
+                .await
+            {
+                log::error!(
+                    "unable to send insights notice to {}: {}",
+                    notice.chat_id,
+                    err
+                );
+            }
+        }
+    }
+}
+
+// an error means the service never started, main exits non-zero on it
+pub async fn run(config: Config) -> Result<(), Box<dyn std::error::Error>> {
+    let service = Arc::new(Service::new(&config).await?);
+
+    let bot = Bot::new(config.telegram_token);
+
+    bot.set_my_commands(localized_commands(DEFAULT_LANGUAGE))
+        .await
+        .map_err(|err| format!("unable to set bot commands: {}", err))?;
+
+    for lang in LANGUAGES {
+        bot.set_my_commands(localized_commands(lang))
+            .language_code(lang)
+            .await
+            .map_err(|err| format!("unable to set {} bot commands: {}", lang, err))?;
+    }
+
+    tokio::spawn(send_reminders(bot.clone(), service.clone()));
+    tokio::spawn(send_checkins(bot.clone(), service.clone()));
+    tokio::spawn(deliver_insights_outbox(bot.clone(), service.clone()));
+
+    if let Some(webhook) = config.webhook {
+        tokio::spawn(webhook::serve(webhook, bot.clone(), service.clone()));
+    }
+
+    if let Some(feed) = config.calendar {
+        tokio::spawn(calendar::serve(feed, service.clone()));
+    }
+
+    let handler = dptree::entry()
+        .filter(webhook::first_delivery)
+        .branch(
+            Update::filter_message()
+                .filter_command::<Command>()
+                .endpoint(command_handler),
+        )
+        .branch(Update::filter_callback_query().endpoint(callback_handler));
 
     Dispatcher::builder(bot, handler)
+        .dependencies(dptree::deps![
+            service,
+            Arc::new(webhook::RecentIds::<i32>::new(webhook::RECENT_DELIVERIES))
+        ])
         .enable_ctrlc_handler()
         .build()
         .dispatch()
         .await;
+
+    Ok(())
 }
@@ -0,0 +1,49 @@
+use crate::service::Service;
+use axum::extract::{Path, State};
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::Router;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+pub struct CalendarOptions {
+    pub address: SocketAddr,
+    // where members reach the feed, the links in /calendar start with it
+    pub url: String,
+}
+
+pub async fn serve(opts: CalendarOptions, service: Arc<Service>) {
+    let app = Router::new()
+        .route("/calendar/:file", get(feed))
+        .with_state(service);
+
+    log::info!("serving calendar feeds on {}", opts.address);
+
+    if let Err(err) = axum::Server::bind(&opts.address)
+        .serve(app.into_make_service())
+        .await
+    {
+        log::error!("calendar feed stopped: {}", err);
+    }
+}
+
+// "/calendar/<token>.ics", an unknown token looks the same as a missing page
+async fn feed(State(service): State<Arc<Service>>, Path(file): Path<String>) -> Response {
+    let Some(token) = file.strip_suffix(".ics") else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    match service.calendar_feed(token).await {
+        Ok(Some(calendar)) => (
+            [(header::CONTENT_TYPE, "text/calendar; charset=utf-8")],
+            calendar,
+        )
+            .into_response(),
+        Ok(None) => StatusCode::NOT_FOUND.into_response(),
+        Err(err) => {
+            log::error!("unable to build calendar feed: {}", err);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
@@ -0,0 +1,684 @@
+use crate::identifier::Identifier;
+use crate::models::{ClubKind, Work};
+use async_trait::async_trait;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::fmt;
+use std::fs;
+use std::time::Duration;
+
+// works offered to choose from when a suggestion is ambiguous
+pub const MAX_MATCHES: usize = 5;
+
+const OPEN_LIBRARY_FIELDS: &str =
+    "key,title,author_name,first_publish_year,number_of_pages_median,cover_i";
+const OPEN_LIBRARY_COVERS: &str = "https://covers.openlibrary.org/b/id";
+// omdb writes "N/A" for what it doesn't know
+const OMDB_UNKNOWN: &str = "N/A";
+
+pub enum CatalogSource {
+    None,
+    OpenLibrary {
+        address: String,
+        timeout: Duration,
+    },
+    // films from an omdb compatible api
+    Omdb {
+        address: String,
+        api_key: String,
+        timeout: Duration,
+    },
+    // a json array of works, for running without network access
+    Fixture(String),
+}
+
+#[derive(Debug)]
+pub enum CatalogError {
+    Transport(reqwest::Error),
+    Fixture(String),
+    Rejected(String),
+}
+
+impl fmt::Display for CatalogError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Transport(err) => write!(f, "catalog request failed: {}", err),
+            Self::Fixture(err) => write!(f, "cannot load catalog fixture: {}", err),
+            Self::Rejected(reason) => write!(f, "catalog rejected the request: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for CatalogError {}
+
+#[async_trait]
+pub trait CatalogProvider: Send + Sync {
+    // best matches of the club's kind first, at most MAX_MATCHES
+    async fn search(&self, query: &str, kind: ClubKind) -> Result<Vec<Work>, CatalogError>;
+    // none when the catalog doesn't know the identifier or its kind
+    async fn lookup(&self, identifier: &Identifier) -> Result<Option<Work>, CatalogError>;
+}
+
+// none when suggestions are kept as typed, several sources are asked in turn
+pub fn new_catalog(
+    sources: &[&CatalogSource],
+) -> Result<Option<Box<dyn CatalogProvider>>, CatalogError> {
+    let mut providers = vec![];
+    for source in sources {
+        if let Some(provider) = new_provider(source)? {
+            providers.push(provider);
+        }
+    }
+
+    match providers.len() {
+        0 => Ok(None),
+        1 => Ok(providers.pop()),
+        _ => Ok(Some(Box::new(Catalogs { providers }))),
+    }
+}
+
+fn new_provider(source: &CatalogSource) -> Result<Option<Box<dyn CatalogProvider>>, CatalogError> {
+    match source {
+        CatalogSource::None => Ok(None),
+        CatalogSource::OpenLibrary { address, timeout } => Ok(Some(Box::new(OpenLibrary {
+            client: client(*timeout),
+            address: address.clone(),
+        }))),
+        CatalogSource::Omdb {
+            address,
+            api_key,
+            timeout,
+        } => Ok(Some(Box::new(Omdb {
+            client: client(*timeout),
+            address: address.clone(),
+            api_key: api_key.clone(),
+        }))),
+        CatalogSource::Fixture(path) => Ok(Some(Box::new(Fixture::load(path)?))),
+    }
+}
+
+fn client(timeout: Duration) -> reqwest::Client {
+    reqwest::Client::builder()
+        .timeout(timeout)
+        .build()
+        .expect("Failed to build catalog client")
+}
+
+// the works worth asking about, an exact title match wins over the rest
+pub fn candidates(query: &str, works: Vec<Work>) -> Vec<Work> {
+    let query = normalize(query);
+    let exact: Vec<Work> = works
+        .iter()
+        .filter(|work| normalize(&work.title) == query)
+        .cloned()
+        .collect();
+
+    match exact.is_empty() {
+        true => works,
+        false => exact,
+    }
+}
+
+fn normalize(text: &str) -> String {
+    text.split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+// books from one catalog and films from another, the first one that knows the work answers
+pub struct Catalogs {
+    providers: Vec<Box<dyn CatalogProvider>>,
+}
+
+#[async_trait]
+impl CatalogProvider for Catalogs {
+    async fn search(&self, query: &str, kind: ClubKind) -> Result<Vec<Work>, CatalogError> {
+        let mut failed = None;
+
+        for provider in &self.providers {
+            match provider.search(query, kind).await {
+                Ok(works) if !works.is_empty() => return Ok(works),
+                Ok(_) => {}
+                Err(err) => failed = Some(err),
+            }
+        }
+
+        failed.map_or(Ok(vec![]), Err)
+    }
+
+    async fn lookup(&self, identifier: &Identifier) -> Result<Option<Work>, CatalogError> {
+        let mut failed = None;
+
+        for provider in &self.providers {
+            match provider.lookup(identifier).await {
+                Ok(Some(work)) => return Ok(Some(work)),
+                Ok(None) => {}
+                Err(err) => failed = Some(err),
+            }
+        }
+
+        failed.map_or(Ok(None), Err)
+    }
+}
+
+pub struct OpenLibrary {
+    client: reqwest::Client,
+    address: String,
+}
+
+#[derive(Deserialize)]
+struct SearchResponse {
+    docs: Vec<SearchDoc>,
+}
+
+#[derive(Deserialize)]
+struct SearchDoc {
+    key: String,
+    title: String,
+    author_name: Option<Vec<String>>,
+    first_publish_year: Option<i32>,
+    number_of_pages_median: Option<i32>,
+    cover_i: Option<i64>,
+}
+
+#[async_trait]
+impl CatalogProvider for OpenLibrary {
+    async fn search(&self, query: &str, kind: ClubKind) -> Result<Vec<Work>, CatalogError> {
+        match kind {
+            ClubKind::Books => self.query(query, MAX_MATCHES).await,
+            _ => Ok(vec![]),
+        }
+    }
+
+    // open library only has books, so films from imdb are never found
+    async fn lookup(&self, identifier: &Identifier) -> Result<Option<Work>, CatalogError> {
+        let query = match identifier {
+            Identifier::Isbn(isbn) => format!("isbn:{}", isbn),
+            Identifier::Goodreads(id) => format!("id_goodreads:{}", id),
+            Identifier::Imdb(_) => return Ok(None),
+        };
+
+        Ok(self.query(&query, 1).await?.into_iter().next())
+    }
+}
+
+impl OpenLibrary {
+    async fn query(&self, query: &str, limit: usize) -> Result<Vec<Work>, CatalogError> {
+        let limit = limit.to_string();
+        let response: SearchResponse = self
+            .client
+            .get(format!("{}/search.json", self.address))
+            .query(&[
+                ("q", query),
+                ("limit", limit.as_str()),
+                ("fields", OPEN_LIBRARY_FIELDS),
+            ])
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(CatalogError::Transport)?
+            .json()
+            .await
+            .map_err(CatalogError::Transport)?;
+
+        Ok(response
+            .docs
+            .into_iter()
+            .map(|doc| Work {
+                // "/works/OL45804W" is kept as "OL45804W"
+                key: doc.key.rsplit('/').next().unwrap_or_default().to_string(),
+                title: doc.title,
+                author: doc.author_name.and_then(|names| names.into_iter().next()),
+                year: doc.first_publish_year,
+                pages: doc.number_of_pages_median,
+                cover_url: doc
+                    .cover_i
+                    .map(|id| format!("{}/{}-M.jpg", OPEN_LIBRARY_COVERS, id)),
+                director: None,
+                runtime: None,
+            })
+            .collect())
+    }
+}
+
+pub struct Omdb {
+    client: reqwest::Client,
+    address: String,
+    api_key: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct OmdbSearch {
+    #[serde(default)]
+    search: Vec<OmdbFound>,
+}
+
+#[derive(Deserialize)]
+struct OmdbFound {
+    #[serde(rename = "imdbID")]
+    imdb_id: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct OmdbFilm {
+    response: String,
+    error: Option<String>,
+    title: Option<String>,
+    year: Option<String>,
+    runtime: Option<String>,
+    director: Option<String>,
+    poster: Option<String>,
+    #[serde(rename = "imdbID")]
+    imdb_id: Option<String>,
+}
+
+#[async_trait]
+impl CatalogProvider for Omdb {
+    // search results carry only titles and years, every match is looked up for the rest
+    async fn search(&self, query: &str, kind: ClubKind) -> Result<Vec<Work>, CatalogError> {
+        if kind != ClubKind::Films {
+            return Ok(vec![]);
+        }
+
+        let found: OmdbSearch = self.get(&[("s", query), ("type", "movie")]).await?;
+        let mut works = vec![];
+
+        for film in found.search.into_iter().take(MAX_MATCHES) {
+            if let Some(work) = self.film(&film.imdb_id).await? {
+                works.push(work);
+            }
+        }
+
+        Ok(works)
+    }
+
+    async fn lookup(&self, identifier: &Identifier) -> Result<Option<Work>, CatalogError> {
+        match identifier {
+            Identifier::Imdb(id) => self.film(id).await,
+            Identifier::Isbn(_) | Identifier::Goodreads(_) => Ok(None),
+        }
+    }
+}
+
+impl Omdb {
+    async fn film(&self, imdb_id: &str) -> Result<Option<Work>, CatalogError> {
+        let film: OmdbFilm = self.get(&[("i", imdb_id)]).await?;
+
+        if film.response != "True" {
+            return match film.error {
+                // unknown ids are answered like that, not with a 404
+                Some(error)
+                    if error.contains("not found") || error.contains("Incorrect IMDb ID") =>
+                {
+                    Ok(None)
+                }
+                error => Err(CatalogError::Rejected(error.unwrap_or_default())),
+            };
+        }
+
+        let known = |value: Option<String>| value.filter(|value| value != OMDB_UNKNOWN);
+
+        Ok(Some(Work {
+            key: film.imdb_id.unwrap_or_else(|| imdb_id.to_string()),
+            title: film.title.unwrap_or_default(),
+            author: None,
+            // "2019–2022" for series, the first year is kept
+            year: known(film.year).and_then(|year| leading_number(&year)),
+            pages: None,
+            cover_url: known(film.poster),
+            director: known(film.director),
+            // "155 min"
+            runtime: known(film.runtime).and_then(|runtime| leading_number(&runtime)),
+        }))
+    }
+
+    async fn get<T: DeserializeOwned>(&self, query: &[(&str, &str)]) -> Result<T, CatalogError> {
+        self.client
+            .get(format!("{}/", self.address))
+            .query(&[("apikey", self.api_key.as_str())])
+            .query(query)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(CatalogError::Transport)?
+            .json()
+            .await
+            .map_err(CatalogError::Transport)
+    }
+}
+
+fn leading_number(text: &str) -> Option<i32> {
+    let digits: String = text.chars().take_while(|c| c.is_ascii_digit()).collect();
+    digits.parse().ok()
+}
+
+pub struct Fixture {
+    works: Vec<FixtureWork>,
+}
+
+#[derive(Deserialize)]
+struct FixtureWork {
+    #[serde(flatten)]
+    work: Work,
+    // books, films or games, books when missing
+    kind: Option<String>,
+    // "isbn:9780441172719", "goodreads:234225" or "imdb:tt0087182"
+    #[serde(default)]
+    identifiers: Vec<String>,
+}
+
+impl Fixture {
+    fn load(path: &str) -> Result<Fixture, CatalogError> {
+        let text =
+            fs::read_to_string(path).map_err(|err| CatalogError::Fixture(err.to_string()))?;
+        let works =
+            serde_json::from_str(&text).map_err(|err| CatalogError::Fixture(err.to_string()))?;
+
+        Ok(Fixture { works })
+    }
+}
+
+#[async_trait]
+impl CatalogProvider for Fixture {
+    // a work matches when every word of the query is in its title or author,
+    // short words like "by" or "of" only count when there is nothing else
+    async fn search(&self, query: &str, kind: ClubKind) -> Result<Vec<Work>, CatalogError> {
+        let query = normalize(query);
+        let all: Vec<&str> = query.split(' ').filter(|word| !word.is_empty()).collect();
+        let long: Vec<&str> = all
+            .iter()
+            .copied()
+            .filter(|word| word.chars().count() > 2)
+            .collect();
+        let words = match long.is_empty() {
+            true => all,
+            false => long,
+        };
+
+        if words.is_empty() {
+            return Ok(vec![]);
+        }
+
+        Ok(self
+            .works
+            .iter()
+            .filter(|fixture| {
+                fixture.kind.as_deref().unwrap_or(ClubKind::Books.as_str()) == kind.as_str()
+            })
+            .map(|fixture| &fixture.work)
+            .filter(|work| {
+                let text = normalize(&format!(
+                    "{} {}",
+                    work.title,
+                    work.author.as_deref().unwrap_or_default()
+                ));
+                words.iter().all(|word| text.contains(word))
+            })
+            .take(MAX_MATCHES)
+            .cloned()
+            .collect())
+    }
+
+    async fn lookup(&self, identifier: &Identifier) -> Result<Option<Work>, CatalogError> {
+        let wanted = format!("{}:{}", identifier.kind(), identifier.value());
+
+        Ok(self
+            .works
+            .iter()
+            .find(|fixture| fixture.identifiers.contains(&wanted))
+            .map(|fixture| fixture.work.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::extract::Query;
+    use axum::routing::get;
+    use axum::Router;
+    use serde_json::{json, Value};
+    use std::collections::HashMap;
+    use std::net::TcpListener;
+
+    const API_KEY: &str = "omdb-key";
+
+    // omdb answers every query on / and tells failures apart by Response
+    fn fake_omdb() -> Omdb {
+        let app = Router::new().route("/", get(omdb));
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = format!("http://{}", listener.local_addr().unwrap());
+        let server = axum::Server::from_tcp(listener)
+            .unwrap()
+            .serve(app.into_make_service());
+        tokio::spawn(server);
+
+        Omdb {
+            client: client(Duration::from_secs(5)),
+            address,
+            api_key: API_KEY.to_string(),
+        }
+    }
+
+    async fn omdb(Query(query): Query<HashMap<String, String>>) -> axum::Json<Value> {
+        let param = |name: &str| query.get(name).map(String::as_str);
+
+        if param("apikey") != Some(API_KEY) {
+            return axum::Json(json!({"Response": "False", "Error": "Invalid API key!"}));
+        }
+
+        axum::Json(match (param("s"), param("i")) {
+            (Some("dune"), _) => json!({
+                "Search": [
+                    {"Title": "Dune", "Year": "2021", "imdbID": "tt1160419", "Type": "movie"},
+                    {"Title": "Dune", "Year": "1984", "imdbID": "tt0087182", "Type": "movie"},
+                ],
+                "totalResults": "2",
+                "Response": "True",
+            }),
+            (Some(_), _) => json!({"Response": "False", "Error": "Movie not found!"}),
+            (_, Some("tt1160419")) => json!({
+                "Title": "Dune",
+                "Year": "2021",
+                "Runtime": "155 min",
+                "Director": "Denis Villeneuve",
+                "Poster": "https://example.com/dune.jpg",
+                "imdbID": "tt1160419",
+                "Response": "True",
+            }),
+            (_, Some("tt0087182")) => json!({
+                "Title": "Dune",
+                "Year": "1984",
+                "Runtime": "137 min",
+                "Director": "David Lynch",
+                "Poster": "N/A",
+                "imdbID": "tt0087182",
+                "Response": "True",
+            }),
+            (_, Some("tt0944947")) => json!({
+                "Title": "Game of Thrones",
+                "Year": "2011–2019",
+                "Runtime": "N/A",
+                "Director": "N/A",
+                "Poster": "N/A",
+                "imdbID": "tt0944947",
+                "Response": "True",
+            }),
+            _ => json!({"Response": "False", "Error": "Incorrect IMDb ID."}),
+        })
+    }
+
+    #[tokio::test]
+    async fn omdb_finds_films_with_runtime_and_director() {
+        let omdb = fake_omdb();
+
+        let works = omdb.search("dune", ClubKind::Films).await.unwrap();
+
+        let described: Vec<_> = works
+            .iter()
+            .map(|work| {
+                (
+                    work.key.as_str(),
+                    work.year,
+                    work.runtime,
+                    work.director.as_deref(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            described,
+            [
+                ("tt1160419", Some(2021), Some(155), Some("Denis Villeneuve")),
+                ("tt0087182", Some(1984), Some(137), Some("David Lynch")),
+            ]
+        );
+        assert_eq!(works[1].cover_url, None);
+        assert!(omdb
+            .search("nothing", ClubKind::Films)
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(omdb
+            .search("dune", ClubKind::Books)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn omdb_looks_up_imdb_titles() {
+        let omdb = fake_omdb();
+
+        let work = omdb
+            .lookup(&Identifier::Imdb("tt1160419".to_string()))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(work.title, "Dune");
+        assert_eq!(work.runtime, Some(155));
+
+        // a series keeps its first year and nothing of what omdb doesn't know
+        let series = omdb
+            .lookup(&Identifier::Imdb("tt0944947".to_string()))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            (series.year, series.runtime, series.director),
+            (Some(2011), None, None)
+        );
+
+        let unknown = omdb
+            .lookup(&Identifier::Imdb("tt0000000".to_string()))
+            .await;
+        assert!(matches!(unknown, Ok(None)));
+        let isbn = omdb
+            .lookup(&Identifier::Isbn("9780441172719".to_string()))
+            .await;
+        assert!(matches!(isbn, Ok(None)));
+    }
+
+    #[tokio::test]
+    async fn omdb_rejects_a_wrong_key() {
+        let mut omdb = fake_omdb();
+        omdb.api_key = "wrong".to_string();
+
+        let film = omdb
+            .lookup(&Identifier::Imdb("tt1160419".to_string()))
+            .await;
+
+        assert!(matches!(film, Err(CatalogError::Rejected(_))));
+    }
+
+    #[tokio::test]
+    async fn catalogs_ask_the_film_catalog_for_films() {
+        let books = new_catalog(&[&CatalogSource::OpenLibrary {
+            // nothing listens on the discard port, books never get that far
+            address: "http://127.0.0.1:9".to_string(),
+            timeout: Duration::from_secs(1),
+        }])
+        .unwrap()
+        .unwrap();
+        let catalogs = Catalogs {
+            providers: vec![books, Box::new(fake_omdb())],
+        };
+
+        let works = catalogs.search("dune", ClubKind::Films).await.unwrap();
+        assert_eq!(works.len(), 2);
+
+        let film = catalogs
+            .lookup(&Identifier::Imdb("tt0087182".to_string()))
+            .await
+            .unwrap();
+        assert_eq!(film.map(|work| work.runtime), Some(Some(137)));
+    }
+
+    fn keys(works: &[Work]) -> Vec<&str> {
+        works.iter().map(|work| work.key.as_str()).collect()
+    }
+
+    #[tokio::test]
+    async fn fixture_searches_titles_and_authors_of_one_kind() {
+        let fixture = Fixture::load("fixtures/catalog.json").unwrap();
+        let search = |query: &'static str, kind| {
+            let fixture = &fixture;
+            async move { fixture.search(query, kind).await.unwrap() }
+        };
+
+        assert_eq!(
+            keys(&search("dune", ClubKind::Books).await),
+            ["OL893415W", "OL893526W"]
+        );
+        assert_eq!(
+            keys(&search("Dune", ClubKind::Films).await),
+            ["tt0087182", "tt1160419"]
+        );
+        assert_eq!(
+            keys(&search("orwell", ClubKind::Books).await),
+            ["OL1168083W", "OL1168007W"]
+        );
+        // short words only count when there's nothing else
+        assert_eq!(
+            keys(&search("the lord of the rings", ClubKind::Books).await),
+            ["OL27448W"]
+        );
+        assert!(search("solaris", ClubKind::Books).await.is_empty());
+        assert!(search("  ", ClubKind::Books).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn fixture_looks_up_every_identifier_of_a_work() {
+        let fixture = Fixture::load("fixtures/catalog.json").unwrap();
+        let lookup = |identifier| {
+            let fixture = &fixture;
+            async move {
+                fixture
+                    .lookup(&identifier)
+                    .await
+                    .unwrap()
+                    .map(|work| work.key)
+            }
+        };
+
+        for identifier in [
+            Identifier::Isbn("9780441172719".to_string()),
+            Identifier::Isbn("9780340960196".to_string()),
+            Identifier::Goodreads("234225".to_string()),
+        ] {
+            assert_eq!(lookup(identifier).await.as_deref(), Some("OL893415W"));
+        }
+        assert_eq!(
+            lookup(Identifier::Imdb("tt1160419".to_string()))
+                .await
+                .as_deref(),
+            Some("tt1160419")
+        );
+        assert_eq!(
+            lookup(Identifier::Isbn("9780000000002".to_string())).await,
+            None
+        );
+    }
+}
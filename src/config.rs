@@ -0,0 +1,470 @@
+use crate::calendar::CalendarOptions;
+use crate::catalog::CatalogSource;
+use crate::insights::{self, InsightsAuth, InsightsOptions};
+use crate::repository::PostgresOptions;
+use crate::webhook::WebhookOptions;
+use serde::Deserialize;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
+
+const DEFAULT_CONFIG_FILE: &str = "clubvent.toml";
+
+// everything the bot needs to start, loaded once in main
+pub struct Config {
+    pub telegram_token: String,
+    pub database: DatabaseConfig,
+    pub insights: InsightsOptions,
+    // the callback endpoint is off unless an address is set
+    pub webhook: Option<WebhookOptions>,
+    pub catalog: CatalogSource,
+    // resolves film suggestions, asked after the book catalog
+    pub film_catalog: CatalogSource,
+    // the calendar feed is off unless an address is set
+    pub calendar: Option<CalendarOptions>,
+}
+
+// all the migrate subcommand needs, so it runs without the bot's own settings
+pub struct DatabaseConfig {
+    pub dsn: String,
+    pub postgres: PostgresOptions,
+}
+
+// every problem found while loading, so all of them can be fixed at once
+#[derive(Debug)]
+pub struct ConfigError(Vec<String>);
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid configuration:\n  {}", self.0.join("\n  "))
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct FileConfig {
+    telegram_token: Option<String>,
+    database: DatabaseSection,
+    insights: InsightsSection,
+    catalog: CatalogSection,
+    calendar: CalendarSection,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct DatabaseSection {
+    dsn: Option<String>,
+    ssl_root_cert: Option<String>,
+    ssl_cert: Option<String>,
+    ssl_key: Option<String>,
+    pool_max_size: Option<u32>,
+    pool_min_idle: Option<u32>,
+    pool_connection_timeout: Option<u64>,
+    pool_idle_timeout: Option<u64>,
+    connect_retries: Option<u32>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct InsightsSection {
+    address: Option<String>,
+    timeout: Option<u64>,
+    retries: Option<u32>,
+    token: Option<String>,
+    hmac_secret: Option<String>,
+    webhook_address: Option<SocketAddr>,
+    webhook_secret: Option<String>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct CatalogSection {
+    provider: Option<String>,
+    address: Option<String>,
+    fixture: Option<String>,
+    timeout: Option<u64>,
+    film_provider: Option<String>,
+    film_address: Option<String>,
+    film_api_key: Option<String>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct CalendarSection {
+    address: Option<SocketAddr>,
+    url: Option<String>,
+}
+
+// values come from CONFIG_FILE (clubvent.toml when it exists) and env, env wins
+pub fn load() -> Result<Config, ConfigError> {
+    let mut loader = Loader { errors: vec![] };
+    let file = loader.file();
+
+    let telegram_token = loader.string("TELOXIDE_TOKEN", file.telegram_token);
+    let database = loader.database(file.database);
+
+    let token = loader.optional("INSIGHTS_TOKEN", file.insights.token);
+    let hmac_secret = loader.optional("INSIGHTS_HMAC_SECRET", file.insights.hmac_secret);
+
+    let insights = InsightsOptions {
+        address: loader.string("INSIGHTS_ADDRESS", file.insights.address),
+        timeout: Duration::from_secs(loader.parsed("INSIGHTS_TIMEOUT", file.insights.timeout, 5)),
+        retries: loader.parsed("INSIGHTS_RETRIES", file.insights.retries, 2),
+        auth: match (token, hmac_secret) {
+            (Some(_), Some(_)) => {
+                loader.errors.push(
+                    "INSIGHTS_TOKEN and INSIGHTS_HMAC_SECRET can't be set together".to_string(),
+                );
+                InsightsAuth::None
+            }
+            (Some(token), None) => InsightsAuth::Bearer(token),
+            (None, Some(secret)) => InsightsAuth::Hmac(secret),
+            (None, None) => InsightsAuth::None,
+        },
+    };
+
+    let webhook_secret = loader.optional("INSIGHTS_WEBHOOK_SECRET", file.insights.webhook_secret);
+    let webhook = loader
+        .optional("INSIGHTS_WEBHOOK_ADDRESS", file.insights.webhook_address)
+        .map(|address| WebhookOptions {
+            address,
+            secret: webhook_secret.unwrap_or_default(),
+        });
+
+    let catalog_address = loader.parsed(
+        "CATALOG_ADDRESS",
+        file.catalog.address,
+        "https://openlibrary.org".to_string(),
+    );
+    let catalog_fixture = loader.optional("CATALOG_FIXTURE", file.catalog.fixture);
+    let catalog_timeout = loader.parsed("CATALOG_TIMEOUT", file.catalog.timeout, 5);
+
+    let catalog = match loader
+        .optional("CATALOG_PROVIDER", file.catalog.provider)
+        .as_deref()
+    {
+        None | Some("none") => CatalogSource::None,
+        Some("openlibrary") => CatalogSource::OpenLibrary {
+            address: catalog_address,
+            timeout: Duration::from_secs(catalog_timeout),
+        },
+        Some("fixture") => match catalog_fixture.clone() {
+            Some(path) => CatalogSource::Fixture(path),
+            None => {
+                loader
+                    .errors
+                    .push("CATALOG_FIXTURE is required with the fixture provider".to_string());
+                CatalogSource::None
+            }
+        },
+        Some(provider) => {
+            loader.errors.push(format!(
+                "CATALOG_PROVIDER should be openlibrary, fixture or none, got {:?}",
+                provider
+            ));
+            CatalogSource::None
+        }
+    };
+
+    let film_address = loader.parsed(
+        "CATALOG_FILM_ADDRESS",
+        file.catalog.film_address,
+        "https://www.omdbapi.com".to_string(),
+    );
+    let film_api_key = loader.optional("CATALOG_FILM_API_KEY", file.catalog.film_api_key);
+
+    let film_catalog = match loader
+        .optional("CATALOG_FILM_PROVIDER", file.catalog.film_provider)
+        .as_deref()
+    {
+        None | Some("none") => CatalogSource::None,
+        Some("omdb") => CatalogSource::Omdb {
+            address: film_address,
+            api_key: film_api_key.unwrap_or_default(),
+            timeout: Duration::from_secs(catalog_timeout),
+        },
+        // films are read from the same fixture as books
+        Some("fixture") => match catalog_fixture {
+            Some(path) => CatalogSource::Fixture(path),
+            None => {
+                loader
+                    .errors
+                    .push("CATALOG_FIXTURE is required with the fixture provider".to_string());
+                CatalogSource::None
+            }
+        },
+        Some(provider) => {
+            loader.errors.push(format!(
+                "CATALOG_FILM_PROVIDER should be omdb, fixture or none, got {:?}",
+                provider
+            ));
+            CatalogSource::None
+        }
+    };
+
+    let calendar_url = loader.optional("CALENDAR_URL", file.calendar.url);
+    let calendar = loader
+        .optional("CALENDAR_ADDRESS", file.calendar.address)
+        .map(|address| CalendarOptions {
+            address,
+            url: calendar_url.unwrap_or_default(),
+        });
+
+    let config = Config {
+        telegram_token,
+        database,
+        insights,
+        webhook,
+        catalog,
+        film_catalog,
+        calendar,
+    };
+
+    let mut errors = loader.errors;
+    validate(&config, &mut errors);
+
+    match errors.is_empty() {
+        true => Ok(config),
+        false => Err(ConfigError(errors)),
+    }
+}
+
+// same sources as load, but only the database settings are read and checked
+pub fn load_database() -> Result<DatabaseConfig, ConfigError> {
+    let mut loader = Loader { errors: vec![] };
+    let file = loader.file();
+    let database = loader.database(file.database);
+
+    let mut errors = loader.errors;
+    validate_database(&database, &mut errors);
+
+    match errors.is_empty() {
+        true => Ok(database),
+        false => Err(ConfigError(errors)),
+    }
+}
+
+fn read_file() -> Result<FileConfig, String> {
+    let (path, required) = match env::var("CONFIG_FILE") {
+        Ok(path) => (path, true),
+        Err(_) => (DEFAULT_CONFIG_FILE.to_string(), false),
+    };
+
+    if !required && !Path::new(&path).exists() {
+        return Ok(FileConfig::default());
+    }
+
+    let text = fs::read_to_string(&path).map_err(|err| format!("{}: {}", path, err))?;
+
+    toml::from_str(&text).map_err(|err| format!("{}: {}", path, err.message()))
+}
+
+// reads env over file values, remembering what couldn't be parsed
+struct Loader {
+    errors: Vec<String>,
+}
+
+impl Loader {
+    fn file(&mut self) -> FileConfig {
+        match read_file() {
+            Ok(file) => file,
+            Err(err) => {
+                self.errors.push(err);
+                FileConfig::default()
+            }
+        }
+    }
+
+    fn database(&mut self, db: DatabaseSection) -> DatabaseConfig {
+        let dsn = self.string("DB_DSN", db.dsn);
+
+        let postgres = PostgresOptions {
+            ssl_root_cert: self.optional("DB_SSL_ROOT_CERT", db.ssl_root_cert),
+            ssl_cert: self.optional("DB_SSL_CERT", db.ssl_cert),
+            ssl_key: self.optional("DB_SSL_KEY", db.ssl_key),
+            max_size: self.parsed("DB_POOL_MAX_SIZE", db.pool_max_size, 10),
+            min_idle: self.optional("DB_POOL_MIN_IDLE", db.pool_min_idle),
+            connection_timeout: Duration::from_secs(self.parsed(
+                "DB_POOL_CONNECTION_TIMEOUT",
+                db.pool_connection_timeout,
+                30,
+            )),
+            idle_timeout: match self.parsed("DB_POOL_IDLE_TIMEOUT", db.pool_idle_timeout, 600) {
+                0 => None,
+                secs => Some(Duration::from_secs(secs)),
+            },
+            connect_retries: self.parsed("DB_CONNECT_RETRIES", db.connect_retries, 5),
+        };
+
+        DatabaseConfig { dsn, postgres }
+    }
+
+    fn optional<T: FromStr>(&mut self, name: &str, file: Option<T>) -> Option<T> {
+        match env::var(name) {
+            Ok(value) if value.is_empty() => file,
+            Ok(value) => match value.parse() {
+                Ok(parsed) => Some(parsed),
+                Err(_) => {
+                    self.errors
+                        .push(format!("{} has an invalid value {:?}", name, value));
+                    None
+                }
+            },
+            Err(_) => file,
+        }
+    }
+
+    fn parsed<T: FromStr>(&mut self, name: &str, file: Option<T>, default: T) -> T {
+        self.optional(name, file).unwrap_or(default)
+    }
+
+    fn string(&mut self, name: &str, file: Option<String>) -> String {
+        match self.optional(name, file) {
+            Some(value) => value,
+            None => {
+                self.errors.push(format!("{} is required", name));
+                String::new()
+            }
+        }
+    }
+}
+
+fn validate(config: &Config, errors: &mut Vec<String>) {
+    validate_database(&config.database, errors);
+
+    if !config.insights.address.is_empty() {
+        match reqwest::Url::parse(&config.insights.address) {
+            Ok(url) if url.scheme() == "http" || url.scheme() == "https" => {}
+            _ => errors.push(format!(
+                "INSIGHTS_ADDRESS should be an http(s) url, got {:?}",
+                config.insights.address
+            )),
+        }
+    }
+
+    if config.insights.timeout.is_zero() {
+        errors.push("INSIGHTS_TIMEOUT should be at least 1 second".to_string());
+    }
+
+    if config.insights.retries > insights::MAX_RETRIES {
+        errors.push(format!(
+            "INSIGHTS_RETRIES should be at most {}, got {}",
+            insights::MAX_RETRIES,
+            config.insights.retries
+        ));
+    }
+
+    if config
+        .webhook
+        .as_ref()
+        .is_some_and(|webhook| webhook.secret.is_empty())
+    {
+        errors
+            .push("INSIGHTS_WEBHOOK_SECRET is required with INSIGHTS_WEBHOOK_ADDRESS".to_string());
+    }
+
+    if let Some(calendar) = &config.calendar {
+        match reqwest::Url::parse(&calendar.url) {
+            Ok(url) if url.scheme() == "http" || url.scheme() == "https" => {}
+            _ if calendar.url.is_empty() => {
+                errors.push("CALENDAR_URL is required with CALENDAR_ADDRESS".to_string())
+            }
+            _ => errors.push(format!(
+                "CALENDAR_URL should be an http(s) url, got {:?}",
+                calendar.url
+            )),
+        }
+    }
+
+    validate_catalog(&config.catalog, errors);
+    validate_catalog(&config.film_catalog, errors);
+}
+
+fn validate_catalog(catalog: &CatalogSource, errors: &mut Vec<String>) {
+    match catalog {
+        CatalogSource::OpenLibrary { address, timeout } => {
+            match reqwest::Url::parse(address) {
+                Ok(url) if url.scheme() == "http" || url.scheme() == "https" => {}
+                _ => errors.push(format!(
+                    "CATALOG_ADDRESS should be an http(s) url, got {:?}",
+                    address
+                )),
+            }
+
+            if timeout.is_zero() {
+                errors.push("CATALOG_TIMEOUT should be at least 1 second".to_string());
+            }
+        }
+        CatalogSource::Omdb {
+            address,
+            api_key,
+            timeout,
+        } => {
+            match reqwest::Url::parse(address) {
+                Ok(url) if url.scheme() == "http" || url.scheme() == "https" => {}
+                _ => errors.push(format!(
+                    "CATALOG_FILM_ADDRESS should be an http(s) url, got {:?}",
+                    address
+                )),
+            }
+
+            if api_key.is_empty() {
+                errors.push("CATALOG_FILM_API_KEY is required with the omdb provider".to_string());
+            }
+
+            if timeout.is_zero() {
+                errors.push("CATALOG_TIMEOUT should be at least 1 second".to_string());
+            }
+        }
+        CatalogSource::Fixture(path) if !Path::new(path).is_file() => {
+            errors.push(format!("CATALOG_FIXTURE points to a missing file {}", path));
+        }
+        _ => {}
+    }
+}
+
+fn validate_database(database: &DatabaseConfig, errors: &mut Vec<String>) {
+    if !database.dsn.is_empty() && !database.dsn.starts_with("sqlite://") {
+        if let Err(err) = database.dsn.parse::<tokio_postgres::Config>() {
+            errors.push(format!("DB_DSN is not a valid postgres dsn: {}", err));
+        }
+    }
+
+    let postgres = &database.postgres;
+
+    if postgres.max_size == 0 {
+        errors.push("DB_POOL_MAX_SIZE should be at least 1".to_string());
+    }
+
+    if postgres.min_idle.is_some_and(|min| min > postgres.max_size) {
+        errors.push("DB_POOL_MIN_IDLE can't be bigger than DB_POOL_MAX_SIZE".to_string());
+    }
+
+    if postgres.connection_timeout.is_zero() {
+        errors.push("DB_POOL_CONNECTION_TIMEOUT should be at least 1 second".to_string());
+    }
+
+    if postgres.ssl_cert.is_some() != postgres.ssl_key.is_some() {
+        errors.push("DB_SSL_CERT and DB_SSL_KEY should be set together".to_string());
+    }
+
+    let files = [
+        ("DB_SSL_ROOT_CERT", &postgres.ssl_root_cert),
+        ("DB_SSL_CERT", &postgres.ssl_cert),
+        ("DB_SSL_KEY", &postgres.ssl_key),
+    ];
+
+    for (name, path) in files {
+        if let Some(path) = path {
+            if !Path::new(path).is_file() {
+                errors.push(format!("{} points to a missing file {}", name, path));
+            }
+        }
+    }
+}
@@ -0,0 +1,513 @@
+// one suite for every Repository, so the backends can't drift apart,
+// the postgres half is ignored by default, `make test-postgres` runs it against a throwaway database
+use crate::models::*;
+use crate::repository::{new_postgres_repository, Error, PostgresOptions, Repository};
+use crate::sqlite::new_sqlite_repository;
+use chrono::{Duration as ChronoDuration, Utc};
+use rand::Rng;
+use serde_json::json;
+use std::env;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Barrier;
+use uuid::Uuid;
+
+const POSTGRES_DSN: &str = "TEST_POSTGRES_DSN";
+// concurrent writers, each holding a pooled connection of its own
+const WRITERS: usize = 4;
+
+async fn sqlite(dir: &tempfile::TempDir) -> Box<dyn Repository> {
+    let path = dir.path().join("club.db");
+
+    Box::new(new_sqlite_repository(path.to_str().unwrap()).await.unwrap())
+}
+
+async fn postgres() -> Box<dyn Repository> {
+    let dsn = env::var(POSTGRES_DSN)
+        .unwrap_or_else(|_| panic!("{} should point at a database to test", POSTGRES_DSN));
+
+    new_postgres_repository(
+        &dsn,
+        &PostgresOptions {
+            ssl_root_cert: None,
+            ssl_cert: None,
+            ssl_key: None,
+            max_size: WRITERS as u32,
+            min_idle: Some(WRITERS as u32),
+            connection_timeout: Duration::from_secs(5),
+            idle_timeout: None,
+            connect_retries: 0,
+        },
+    )
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn sqlite_conforms() {
+    let dir = tempfile::tempdir().unwrap();
+
+    conformance(sqlite(&dir).await.as_ref()).await;
+}
+
+#[tokio::test]
+#[ignore = "needs a postgres database in TEST_POSTGRES_DSN"]
+async fn postgres_conforms() {
+    conformance(postgres().await.as_ref()).await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn sqlite_events_conflict_once() {
+    let dir = tempfile::tempdir().unwrap();
+
+    concurrent_events_conflict_once(sqlite(&dir).await.into()).await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+#[ignore = "needs a postgres database in TEST_POSTGRES_DSN"]
+async fn postgres_events_conflict_once() {
+    concurrent_events_conflict_once(postgres().await.into()).await;
+}
+
+async fn conformance(repo: &dyn Repository) {
+    registers_clubs_once(repo).await;
+    new_event_needs_a_club(repo).await;
+    new_event_conflict_writes_nothing(repo).await;
+    achieve_event_is_atomic(repo).await;
+    outbox_keeps_event_order(repo).await;
+    settings_round_trip(repo).await;
+    event_changes_bump_revision(repo).await;
+    work_and_discussion_are_audited(repo).await;
+}
+
+// postgres databases outlive a run, so every check works in a chat of its own
+fn new_chat() -> i64 {
+    -rand::thread_rng().gen_range(1_000_000_000..1_000_000_000_000)
+}
+
+async fn new_club(repo: &dyn Repository) -> i64 {
+    let chat_id = new_chat();
+
+    repo.register_new_club(NewClubRequest {
+        chat_id,
+        actor_id: 1,
+    })
+    .await
+    .unwrap();
+
+    chat_id
+}
+
+fn new_event(chat_id: i64, track: &str) -> NewEventRequest {
+    NewEventRequest {
+        chat_id,
+        actor_id: 1,
+        event_id: Uuid::new_v4(),
+        event_date: (Utc::now() + ChronoDuration::days(7)).naive_utc(),
+        track: track.to_string(),
+        with_insights: false,
+        host: None,
+        location: None,
+    }
+}
+
+async fn active_events(repo: &dyn Repository, chat_id: i64) -> Vec<LastEventResponse> {
+    repo.get_active_events(LastEventRequest { chat_id })
+        .await
+        .unwrap()
+        .events
+}
+
+async fn audit_actions(repo: &dyn Repository, chat_id: i64) -> Vec<String> {
+    repo.get_audit_log(AuditLogRequest {
+        chat_id,
+        limit: 100,
+    })
+    .await
+    .unwrap()
+    .entries
+    .into_iter()
+    .map(|entry| entry.action)
+    .collect()
+}
+
+fn count(actions: &[String], action: AuditAction) -> usize {
+    actions.iter().filter(|a| *a == action.as_str()).count()
+}
+
+// the worker asks a moment ahead, database clocks may round the insert time up
+async fn due_outbox(repo: &dyn Repository, event_id: Uuid) -> Vec<Uuid> {
+    repo.get_due_outbox(DueOutboxRequest {
+        now: Utc::now() + ChronoDuration::seconds(2),
+        event_id: Some(event_id),
+        limit: 10,
+    })
+    .await
+    .unwrap()
+    .entries
+    .into_iter()
+    .map(|entry| entry.message.id)
+    .collect()
+}
+
+async fn registers_clubs_once(repo: &dyn Repository) {
+    let chat_id = new_club(repo).await;
+
+    let again = repo
+        .register_new_club(NewClubRequest {
+            chat_id,
+            actor_id: 2,
+        })
+        .await;
+
+    assert!(matches!(again, Err(Error::Conflict)));
+    assert_eq!(
+        count(
+            &audit_actions(repo, chat_id).await,
+            AuditAction::ClubRegistered
+        ),
+        1
+    );
+}
+
+async fn new_event_needs_a_club(repo: &dyn Repository) {
+    let chat_id = new_chat();
+
+    let result = repo.write_new_event(new_event(chat_id, "main")).await;
+
+    assert!(matches!(result, Err(Error::Postgres(_) | Error::Sqlite(_))));
+    assert!(audit_actions(repo, chat_id).await.is_empty());
+}
+
+async fn new_event_conflict_writes_nothing(repo: &dyn Repository) {
+    let chat_id = new_club(repo).await;
+    let first = new_event(chat_id, "main");
+    let first_id = first.event_id;
+
+    repo.write_new_event(first).await.unwrap();
+    let second = repo.write_new_event(new_event(chat_id, "main")).await;
+    // another track may have its own active event
+    repo.write_new_event(new_event(chat_id, "films"))
+        .await
+        .unwrap();
+
+    assert!(matches!(second, Err(Error::Conflict)));
+
+    let events = active_events(repo, chat_id).await;
+    assert_eq!(events.len(), 2);
+    assert!(events
+        .iter()
+        .any(|event| event.event_id == first_id && event.track == "main"));
+    assert_eq!(
+        count(
+            &audit_actions(repo, chat_id).await,
+            AuditAction::EventCreated
+        ),
+        2
+    );
+}
+
+async fn achieve_event_is_atomic(repo: &dyn Repository) {
+    let chat_id = new_club(repo).await;
+    let first = new_event(chat_id, "main");
+    let first_id = first.event_id;
+    repo.write_new_event(first).await.unwrap();
+
+    let finish = OutboxMessage::new(OutboxKind::Finish, first_id, chat_id, json!({}));
+    repo.achieve_event(AchieveEventRequest {
+        event_id: first_id,
+        chat_id,
+        actor_id: 1,
+        outbox: Some(finish.clone()),
+    })
+    .await
+    .unwrap();
+
+    assert!(active_events(repo, chat_id).await.is_empty());
+    assert_eq!(due_outbox(repo, first_id).await, vec![finish.id]);
+
+    // a message the outbox already has fails the whole achievement
+    let second = new_event(chat_id, "main");
+    let second_id = second.event_id;
+    repo.write_new_event(second).await.unwrap();
+
+    let result = repo
+        .achieve_event(AchieveEventRequest {
+            event_id: second_id,
+            chat_id,
+            actor_id: 1,
+            outbox: Some(OutboxMessage {
+                event_id: second_id,
+                ..finish
+            }),
+        })
+        .await;
+
+    assert!(matches!(result, Err(Error::Conflict)));
+
+    let events = active_events(repo, chat_id).await;
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].event_id, second_id);
+    assert!(due_outbox(repo, second_id).await.is_empty());
+    assert_eq!(
+        count(
+            &audit_actions(repo, chat_id).await,
+            AuditAction::EventAchieved
+        ),
+        1
+    );
+}
+
+async fn outbox_keeps_event_order(repo: &dyn Repository) {
+    let chat_id = new_club(repo).await;
+    let event = new_event(chat_id, "main");
+    let event_id = event.event_id;
+    repo.write_new_event(event).await.unwrap();
+
+    let register = OutboxMessage::new(OutboxKind::Register, event_id, chat_id, json!({}));
+    let start = OutboxMessage::new(OutboxKind::Start, event_id, chat_id, json!({}));
+    let finish = OutboxMessage::new(OutboxKind::Finish, event_id, chat_id, json!({}));
+
+    repo.write_picked_subject(PickedSubjectRequest {
+        event_id,
+        chat_id,
+        actor_id: 1,
+        subject: "Dune".to_string(),
+        work: None,
+        suggested_by: vec![],
+        outbox: Some(register.clone()),
+    })
+    .await
+    .unwrap();
+    repo.write_event_started(EventStartedRequest {
+        event_id,
+        chat_id,
+        actor_id: 1,
+        outbox: Some(start.clone()),
+        numbers: vec![],
+    })
+    .await
+    .unwrap();
+    repo.achieve_event(AchieveEventRequest {
+        event_id,
+        chat_id,
+        actor_id: 1,
+        outbox: Some(finish.clone()),
+    })
+    .await
+    .unwrap();
+
+    // only the oldest pending call of an event is due
+    assert_eq!(due_outbox(repo, event_id).await, vec![register.id]);
+
+    repo.mark_outbox_delivered(OutboxDeliveredRequest {
+        id: register.id,
+        event_id,
+        insights_link: Some("https://insights.example/e/1".to_string()),
+    })
+    .await
+    .unwrap();
+    assert_eq!(due_outbox(repo, event_id).await, vec![start.id]);
+
+    // a call waiting for its retry still holds the later ones back
+    repo.mark_outbox_failed(OutboxFailedRequest {
+        id: start.id,
+        error: "insights is down".to_string(),
+        next_attempt_at: Some(Utc::now() + ChronoDuration::hours(1)),
+    })
+    .await
+    .unwrap();
+    assert!(due_outbox(repo, event_id).await.is_empty());
+
+    repo.mark_outbox_failed(OutboxFailedRequest {
+        id: start.id,
+        error: "insights is down".to_string(),
+        next_attempt_at: None,
+    })
+    .await
+    .unwrap();
+    assert_eq!(due_outbox(repo, event_id).await, vec![finish.id]);
+}
+
+async fn settings_round_trip(repo: &dyn Repository) {
+    let chat_id = new_club(repo).await;
+
+    let defaults = repo
+        .get_club_settings(ClubSettingsRequest { chat_id })
+        .await
+        .unwrap();
+    assert!(defaults.pick_mode == PickMode::Random);
+    assert!(defaults.kind == ClubKind::Books);
+    assert_eq!(defaults.time_zone, "UTC");
+    assert!(defaults.reminder_offsets.is_empty());
+
+    repo.write_club_settings(UpdateClubSettingsRequest {
+        actor_id: 1,
+        settings: ClubSettings {
+            chat_id,
+            default_insights: true,
+            pick_mode: PickMode::Ranked,
+            suggestion_limit: 3,
+            time_zone: "Europe/Moscow".to_string(),
+            language: Some("ru".to_string()),
+            reminder_offsets: vec![1440, 60],
+            kind: ClubKind::Films,
+            host_only: true,
+        },
+    })
+    .await
+    .unwrap();
+
+    let saved = repo
+        .get_club_settings(ClubSettingsRequest { chat_id })
+        .await
+        .unwrap();
+    assert!(saved.default_insights);
+    assert!(saved.pick_mode == PickMode::Ranked);
+    assert_eq!(saved.suggestion_limit, 3);
+    assert_eq!(saved.time_zone, "Europe/Moscow");
+    assert_eq!(saved.language.as_deref(), Some("ru"));
+    assert_eq!(saved.reminder_offsets, vec![1440, 60]);
+    assert!(saved.kind == ClubKind::Films);
+    assert!(saved.host_only);
+    assert_eq!(
+        count(
+            &audit_actions(repo, chat_id).await,
+            AuditAction::SettingsChanged
+        ),
+        1
+    );
+}
+
+async fn event_changes_bump_revision(repo: &dyn Repository) {
+    let chat_id = new_club(repo).await;
+    let event = new_event(chat_id, "main");
+    let event_id = event.event_id;
+    repo.write_new_event(event).await.unwrap();
+
+    let created = active_events(repo, chat_id).await.remove(0);
+    assert_eq!(created.revision, 0);
+
+    repo.write_event_location(EventLocationRequest {
+        event_id,
+        chat_id,
+        actor_id: 1,
+        location: Location {
+            address: "Main Street 1".to_string(),
+            coordinates: None,
+        },
+    })
+    .await
+    .unwrap();
+
+    let moved = active_events(repo, chat_id).await.remove(0);
+    assert_eq!(moved.revision, 1);
+    assert!(moved.updated_at >= created.updated_at);
+}
+
+// members running /event at once, the unique index lets exactly one of them through
+async fn concurrent_events_conflict_once(repo: Arc<dyn Repository>) {
+    for _ in 0..5 {
+        let chat_id = new_club(repo.as_ref()).await;
+        // every writer is spawned before any of them starts, so the inserts really overlap
+        let barrier = Arc::new(Barrier::new(WRITERS));
+
+        let writers: Vec<_> = (0..WRITERS)
+            .map(|_| {
+                let repo = repo.clone();
+                let barrier = barrier.clone();
+
+                tokio::spawn(async move {
+                    barrier.wait().await;
+                    repo.write_new_event(new_event(chat_id, "main")).await
+                })
+            })
+            .collect();
+
+        let mut results = vec![];
+        for writer in writers {
+            results.push(writer.await.unwrap());
+        }
+
+        assert_eq!(results.iter().filter(|result| result.is_ok()).count(), 1);
+        assert_eq!(
+            results
+                .iter()
+                .filter(|result| matches!(result, Err(Error::Conflict)))
+                .count(),
+            WRITERS - 1
+        );
+        assert_eq!(active_events(repo.as_ref(), chat_id).await.len(), 1);
+        assert_eq!(
+            count(
+                &audit_actions(repo.as_ref(), chat_id).await,
+                AuditAction::EventCreated
+            ),
+            1
+        );
+    }
+}
+
+async fn work_and_discussion_are_audited(repo: &dyn Repository) {
+    let chat_id = new_club(repo).await;
+    let event = new_event(chat_id, "main");
+    let event_id = event.event_id;
+    repo.write_new_event(event).await.unwrap();
+
+    let suggestion_id = repo
+        .write_new_member_suggestion(NewMemberSuggestion {
+            event_id,
+            chat_id,
+            user_id: 2,
+            name: "Ann".to_string(),
+            suggestion: "dune".to_string(),
+            work: None,
+            identifier: None,
+        })
+        .await
+        .unwrap();
+
+    repo.write_suggestion_work(SuggestionWorkRequest {
+        id: suggestion_id,
+        chat_id,
+        actor_id: 2,
+        work: Work {
+            key: "OL893415W".to_string(),
+            title: "Dune".to_string(),
+            author: Some("Frank Herbert".to_string()),
+            year: Some(1965),
+            pages: Some(412),
+            cover_url: None,
+            director: None,
+            runtime: None,
+        },
+    })
+    .await
+    .unwrap();
+    repo.write_discussion_thread(DiscussionThreadRequest {
+        event_id,
+        chat_id,
+        actor_id: 1,
+        thread: DiscussionThread {
+            kind: DiscussionKind::Reply,
+            id: 42,
+        },
+    })
+    .await
+    .unwrap();
+
+    let suggestions = repo
+        .get_all_suggestions_for_event(EventSuggestionsRequest { event_id })
+        .await
+        .unwrap()
+        .suggestions;
+    assert_eq!(
+        suggestions[0].work.as_ref().map(|work| work.title.as_str()),
+        Some("Dune")
+    );
+    assert!(active_events(repo, chat_id).await[0].discussion.is_some());
+
+    let actions = audit_actions(repo, chat_id).await;
+    assert_eq!(count(&actions, AuditAction::WorkChosen), 1);
+    assert_eq!(count(&actions, AuditAction::DiscussionOpened), 1);
+}
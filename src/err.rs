@@ -1,3 +1,4 @@
+use crate::i18n::{tr, DEFAULT_LANGUAGE};
 use std::error::Error;
 use std::fmt;
 
@@ -9,26 +10,125 @@ pub enum CustomError {
     AlreadyPickedSubject(String),
     WrongDateFormat,
     EventInPast,
-    EventWithoutInsights,
+    NoActiveEventInTrack(String),
+    TrackRequired(String),
+    WrongTrackName,
+    SuggestionLimitReached(i32),
+    UnknownTimeZone(String),
+    WrongSettingValue,
+    SuggestionNotFound,
+    NotYourSuggestion,
+    AlreadySuggested,
+    PickFilterWithoutKind,
+    // the size limit and the club kind it's measured for
+    NoSuggestionsUnder(i32, String, usize),
+    SubjectNotPicked,
+    WrongProgress,
+    ProgressPagesUnknown,
+    NothingToRate,
+    // the subject that needs stars before a review
+    RatingRequired(String),
+    ReviewTooLong(i32),
+    WrongSpoiler,
+    QuestionsClosed,
+    QuestionTooLong(i32),
+    QuestionLimitReached(i32),
+    QuestionNotFound,
+    HostOnly,
+    // the member who is already in the rotation
+    AlreadyHost(String),
+    NotAHost,
+    SwapNotAllowed,
+    CalendarDisabled,
+    ClubNotFound,
 }
 
-impl fmt::Display for CustomError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+impl CustomError {
+    pub fn localize(&self, lang: &str) -> String {
         match *self {
-            Self::NoActiveEventFound => write!(f, "No active event found"),
+            Self::NoActiveEventFound => tr!(lang, "error-no-active-event"),
             Self::ActiveEventFound(ref date) => {
-                write!(f, "Already have an active event on {}", date)
-            }
-            Self::NoSuggestionsFound => write!(f, "No suggestions found"),
-            Self::AlreadyPickedSubject(ref subject) => write!(f, "Already picked {}", subject),
-            Self::WrongDateFormat => write!(f, "Wrong format, sorry"),
-            Self::EventInPast => write!(f, "Unfortunately, you can't go forward to the past"),
-            Self::EventWithoutInsights => write!(
-                f,
-                "Event was configured without insights, no need to start it"
-            ),
+                tr!(lang, "error-active-event-found", date = date.as_str())
+            }
+            Self::NoSuggestionsFound => tr!(lang, "error-no-suggestions"),
+            Self::AlreadyPickedSubject(ref subject) => {
+                tr!(lang, "error-already-picked", subject = subject.as_str())
+            }
+            Self::WrongDateFormat => tr!(lang, "error-wrong-date-format"),
+            Self::EventInPast => tr!(lang, "error-event-in-past"),
+            Self::NoActiveEventInTrack(ref track) => {
+                tr!(
+                    lang,
+                    "error-no-active-event-in-track",
+                    track = track.as_str()
+                )
+            }
+            Self::TrackRequired(ref tracks) => {
+                tr!(lang, "error-track-required", tracks = tracks.as_str())
+            }
+            Self::WrongTrackName => tr!(lang, "error-wrong-track-name"),
+            Self::SuggestionLimitReached(limit) => {
+                tr!(lang, "error-suggestion-limit", limit = limit)
+            }
+            Self::UnknownTimeZone(ref name) => {
+                tr!(lang, "error-unknown-time-zone", tz = name.as_str())
+            }
+            Self::WrongSettingValue => tr!(lang, "error-wrong-setting-value"),
+            Self::SuggestionNotFound => tr!(lang, "error-suggestion-not-found"),
+            Self::NotYourSuggestion => tr!(lang, "error-not-your-suggestion"),
+            Self::AlreadySuggested => tr!(lang, "error-already-suggested"),
+            Self::PickFilterWithoutKind => tr!(lang, "error-pick-filter-without-kind"),
+            Self::NoSuggestionsUnder(limit, ref kind, skipped) => {
+                let message = tr!(
+                    lang,
+                    "error-no-suggestions-under",
+                    limit = limit,
+                    kind = kind.as_str()
+                );
+
+                match skipped {
+                    0 => message,
+                    _ => format!(
+                        "{}\n{}",
+                        message,
+                        tr!(
+                            lang,
+                            "pick-unknown-skipped",
+                            count = skipped,
+                            kind = kind.as_str()
+                        )
+                    ),
+                }
+            }
+            Self::SubjectNotPicked => tr!(lang, "error-subject-not-picked"),
+            Self::WrongProgress => tr!(lang, "error-wrong-progress"),
+            Self::ProgressPagesUnknown => tr!(lang, "error-progress-pages-unknown"),
+            Self::NothingToRate => tr!(lang, "error-nothing-to-rate"),
+            Self::RatingRequired(ref subject) => {
+                tr!(lang, "error-rating-required", subject = subject.as_str())
+            }
+            Self::ReviewTooLong(limit) => tr!(lang, "error-review-too-long", limit = limit),
+            Self::WrongSpoiler => tr!(lang, "error-wrong-spoiler"),
+            Self::QuestionsClosed => tr!(lang, "error-questions-closed"),
+            Self::QuestionTooLong(limit) => tr!(lang, "error-question-too-long", limit = limit),
+            Self::QuestionLimitReached(limit) => {
+                tr!(lang, "error-question-limit", limit = limit)
+            }
+            Self::QuestionNotFound => tr!(lang, "error-question-not-found"),
+            Self::HostOnly => tr!(lang, "error-host-only"),
+            Self::AlreadyHost(ref name) => tr!(lang, "error-already-host", name = name.as_str()),
+            Self::NotAHost => tr!(lang, "error-not-a-host"),
+            Self::SwapNotAllowed => tr!(lang, "error-swap-not-allowed"),
+            Self::CalendarDisabled => tr!(lang, "error-calendar-disabled"),
+            Self::ClubNotFound => tr!(lang, "error-club-not-found"),
         }
     }
 }
 
+impl fmt::Display for CustomError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.localize(DEFAULT_LANGUAGE))
+    }
+}
+
 impl Error for CustomError {}
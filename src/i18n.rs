@@ -0,0 +1,89 @@
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::{FluentArgs, FluentResource};
+use lazy_static::lazy_static;
+use std::collections::HashMap;
+use unic_langid::LanguageIdentifier;
+
+pub const DEFAULT_LANGUAGE: &str = "en";
+pub const LANGUAGES: [&str; 2] = ["en", "ru"];
+
+// catalogs are embedded so the binary doesn't depend on its working directory
+const CATALOGS: [(&str, &str); 2] = [
+    ("en", include_str!("../locales/en/main.ftl")),
+    ("ru", include_str!("../locales/ru/main.ftl")),
+];
+
+lazy_static! {
+    static ref BUNDLES: HashMap<&'static str, FluentBundle<FluentResource>> = CATALOGS
+        .iter()
+        .map(|(lang, source)| (*lang, new_bundle(lang, source)))
+        .collect();
+}
+
+fn new_bundle(lang: &str, source: &str) -> FluentBundle<FluentResource> {
+    let langid: LanguageIdentifier = lang.parse().expect("Invalid catalog language");
+    let resource = FluentResource::try_new(source.to_string()).expect("Invalid message catalog");
+
+    let mut bundle = FluentBundle::new_concurrent(vec![langid]);
+    // isolation marks would end up in telegram messages as invisible characters
+    bundle.set_use_isolating(false);
+    bundle
+        .add_resource(resource)
+        .expect("Duplicate messages in catalog");
+
+    bundle
+}
+
+// club setting wins, then the member's telegram language, then english
+pub fn resolve_language(club: Option<&str>, user: Option<&str>) -> &'static str {
+    let candidates = [club, user.and_then(|code| code.split(['-', '_']).next())];
+
+    candidates
+        .into_iter()
+        .flatten()
+        .find_map(|lang| {
+            CATALOGS
+                .iter()
+                .find(|(supported, _)| supported.eq_ignore_ascii_case(lang))
+                .map(|(supported, _)| *supported)
+        })
+        .unwrap_or(DEFAULT_LANGUAGE)
+}
+
+// missing messages fall back to english, and to the key itself as a last resort
+pub fn translate(lang: &str, key: &str, args: Option<&FluentArgs>) -> String {
+    for lang in [lang, DEFAULT_LANGUAGE] {
+        let Some(bundle) = BUNDLES.get(lang) else {
+            continue;
+        };
+
+        let Some(pattern) = bundle.get_message(key).and_then(|message| message.value()) else {
+            continue;
+        };
+
+        let mut errors = vec![];
+        let text = bundle.format_pattern(pattern, args, &mut errors);
+
+        if !errors.is_empty() {
+            log::warn!("unable to format {} for {}: {:?}", key, lang, errors);
+        }
+
+        return text.to_string();
+    }
+
+    log::warn!("no message {} in catalogs", key);
+    key.to_string()
+}
+
+macro_rules! tr {
+    ($lang:expr, $key:expr) => {
+        $crate::i18n::translate($lang, $key, None)
+    };
+    ($lang:expr, $key:expr, $($name:ident = $value:expr),+ $(,)?) => {{
+        let mut args = fluent_bundle::FluentArgs::new();
+        $(args.set(stringify!($name), $value);)+
+        $crate::i18n::translate($lang, $key, Some(&args))
+    }};
+}
+
+pub(crate) use tr;
@@ -0,0 +1,157 @@
+use crate::models::Location;
+use chrono::{DateTime, NaiveDateTime, Utc};
+
+// clubs only set when an event starts, calendars show it this long
+const EVENT_DURATION: &str = "PT2H";
+// longer lines are folded onto continuation lines starting with a space
+const MAX_LINE_OCTETS: usize = 75;
+
+pub struct Entry {
+    // stays the same for an event, so calendars update it instead of adding a copy
+    pub uid: String,
+    // in utc
+    pub start: NaiveDateTime,
+    pub summary: String,
+    pub description: Option<String>,
+    pub location: Option<Location>,
+    pub url: Option<String>,
+    // calendars keep their copy of an event unless the sequence grows
+    pub sequence: i32,
+    // in utc
+    pub last_modified: NaiveDateTime,
+}
+
+// an iCalendar (RFC 5545) document with CRLF line endings
+pub fn calendar(name: &str, entries: &[Entry], now: DateTime<Utc>) -> String {
+    let stamp = now.format("%Y%m%dT%H%M%SZ").to_string();
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//clubvent//clubvent//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+        "METHOD:PUBLISH".to_string(),
+        format!("X-WR-CALNAME:{}", escape(name)),
+    ];
+
+    for entry in entries {
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!("UID:{}", entry.uid));
+        lines.push(format!("DTSTAMP:{}", stamp));
+        lines.push(format!(
+            "LAST-MODIFIED:{}",
+            entry.last_modified.format("%Y%m%dT%H%M%SZ")
+        ));
+        lines.push(format!("SEQUENCE:{}", entry.sequence));
+        lines.push(format!("DTSTART:{}", entry.start.format("%Y%m%dT%H%M%SZ")));
+        lines.push(format!("DURATION:{}", EVENT_DURATION));
+        lines.push(format!("SUMMARY:{}", escape(&entry.summary)));
+
+        if let Some(description) = &entry.description {
+            lines.push(format!("DESCRIPTION:{}", escape(description)));
+        }
+
+        if let Some(location) = &entry.location {
+            lines.push(format!("LOCATION:{}", escape(&location.address)));
+
+            if let Some(coordinates) = location.coordinates {
+                lines.push(format!(
+                    "GEO:{};{}",
+                    coordinates.latitude, coordinates.longitude
+                ));
+            }
+        }
+
+        if let Some(url) = &entry.url {
+            lines.push(format!("URL:{}", url));
+        }
+
+        lines.push("END:VEVENT".to_string());
+    }
+
+    lines.push("END:VCALENDAR".to_string());
+
+    lines
+        .iter()
+        .map(|line| fold(line) + "\r\n")
+        .collect::<String>()
+}
+
+// text values escape backslashes, separators and line breaks
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+// splits between characters, never inside a multibyte one
+fn fold(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len());
+    let mut octets = 0;
+
+    for c in line.chars() {
+        if octets + c.len_utf8() > MAX_LINE_OCTETS {
+            folded.push_str("\r\n ");
+            // the leading space counts towards the continuation line
+            octets = 1;
+        }
+
+        folded.push(c);
+        octets += c.len_utf8();
+    }
+
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn entry(summary: &str) -> Entry {
+        Entry {
+            uid: "event@clubvent".to_string(),
+            start: Utc
+                .with_ymd_and_hms(2026, 3, 1, 17, 0, 0)
+                .unwrap()
+                .naive_utc(),
+            summary: summary.to_string(),
+            description: None,
+            location: None,
+            url: None,
+            sequence: 3,
+            last_modified: Utc
+                .with_ymd_and_hms(2026, 2, 20, 9, 30, 0)
+                .unwrap()
+                .naive_utc(),
+        }
+    }
+
+    #[test]
+    fn calendar_versions_events() {
+        let now = Utc.with_ymd_and_hms(2026, 2, 21, 0, 0, 0).unwrap();
+        let text = calendar("Club", &[entry("Dune, part one; again")], now);
+
+        assert!(text.contains("\r\nDTSTART:20260301T170000Z\r\n"));
+        assert!(text.contains("\r\nLAST-MODIFIED:20260220T093000Z\r\n"));
+        assert!(text.contains("\r\nSEQUENCE:3\r\n"));
+        assert!(text.contains("\r\nSUMMARY:Dune\\, part one\\; again\r\n"));
+    }
+
+    #[test]
+    fn escape_separators() {
+        assert_eq!(escape("a,b;c\\d\r\ne"), "a\\,b\\;c\\\\d\\ne");
+    }
+
+    #[test]
+    fn fold_keeps_lines_short() {
+        let line = format!("SUMMARY:{}", "ё".repeat(100));
+        let folded = fold(&line);
+
+        assert!(folded
+            .split("\r\n")
+            .all(|part| part.len() <= MAX_LINE_OCTETS));
+        assert_eq!(folded.replace("\r\n ", ""), line);
+    }
+}
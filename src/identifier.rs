@@ -0,0 +1,241 @@
+use reqwest::Url;
+
+// something a suggestion can be recognized by whatever way it was written
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Identifier {
+    // always the 13 digit form, so an isbn-10 and its isbn-13 are the same book
+    Isbn(String),
+    // the numeric book id from goodreads.com/book/show/<id>
+    Goodreads(String),
+    // the title id from imdb.com/title/<id>, e.g. tt0087182
+    Imdb(String),
+}
+
+impl Identifier {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Isbn(_) => "isbn",
+            Self::Goodreads(_) => "goodreads",
+            Self::Imdb(_) => "imdb",
+        }
+    }
+
+    pub fn value(&self) -> &str {
+        match self {
+            Self::Isbn(value) | Self::Goodreads(value) | Self::Imdb(value) => value,
+        }
+    }
+
+    pub fn parse(kind: &str, value: String) -> Option<Identifier> {
+        match kind {
+            "isbn" => Some(Self::Isbn(value)),
+            "goodreads" => Some(Self::Goodreads(value)),
+            "imdb" => Some(Self::Imdb(value)),
+            _ => None,
+        }
+    }
+}
+
+// the first link or isbn found in a suggestion, plain titles have none
+pub fn detect(text: &str) -> Option<Identifier> {
+    let words: Vec<&str> = text.split_whitespace().collect();
+
+    words
+        .iter()
+        .find_map(|word| link(word))
+        .or_else(|| isbn(text))
+        .or_else(|| words.iter().find_map(|word| isbn(word)))
+}
+
+fn link(word: &str) -> Option<Identifier> {
+    let url = match Url::parse(word) {
+        Ok(url) => url,
+        // links are often pasted without a scheme
+        Err(_) => Url::parse(&format!("https://{}", word)).ok()?,
+    };
+
+    let host = url.host_str()?.to_lowercase();
+    let host = host
+        .strip_prefix("www.")
+        .or_else(|| host.strip_prefix("m."))
+        .unwrap_or(&host);
+    let segments: Vec<&str> = url.path_segments()?.filter(|s| !s.is_empty()).collect();
+
+    match (host, segments.as_slice()) {
+        ("goodreads.com", ["book", "show", id, ..]) => {
+            // ids come with a slug, 234225.Dune or 234225-dune
+            let id: String = id.chars().take_while(char::is_ascii_digit).collect();
+            (!id.is_empty()).then_some(Identifier::Goodreads(id))
+        }
+        ("imdb.com", ["title", id, ..]) => {
+            let digits = id.strip_prefix("tt")?;
+            (digits.len() >= 7 && digits.chars().all(|c| c.is_ascii_digit()))
+                .then(|| Identifier::Imdb(id.to_string()))
+        }
+        _ => None,
+    }
+}
+
+// "ISBN 0-441-17271-7", "isbn:9780441172719" or just the digits, hyphens and spaces are ignored
+fn isbn(text: &str) -> Option<Identifier> {
+    let text = text.trim();
+    let text = match text.get(..4) {
+        Some(prefix) if prefix.eq_ignore_ascii_case("isbn") => {
+            let rest = text[4..].trim_start_matches('-');
+            let rest = ["13:", "10:"]
+                .iter()
+                .find_map(|label| rest.strip_prefix(label))
+                .unwrap_or(rest);
+            rest.trim_start_matches(':').trim_start()
+        }
+        _ => text,
+    };
+
+    if text.is_empty()
+        || !text
+            .chars()
+            .all(|c| c.is_ascii_digit() || c == 'X' || c == 'x' || c == '-' || c == ' ')
+    {
+        return None;
+    }
+
+    let chars: Vec<char> = text
+        .chars()
+        .filter(|c| *c != '-' && *c != ' ')
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+
+    match chars.len() {
+        10 if is_isbn10(&chars) => Some(Identifier::Isbn(isbn10_to_13(&chars))),
+        13 if is_isbn13(&chars) => Some(Identifier::Isbn(chars.iter().collect())),
+        _ => None,
+    }
+}
+
+fn is_isbn10(chars: &[char]) -> bool {
+    let mut sum = 0;
+
+    for (i, c) in chars.iter().enumerate() {
+        let digit = match (c, i) {
+            ('X', 9) => 10,
+            (c, _) => match c.to_digit(10) {
+                Some(digit) => digit,
+                None => return false,
+            },
+        };
+        sum += digit * (10 - i as u32);
+    }
+
+    sum.is_multiple_of(11)
+}
+
+fn is_isbn13(chars: &[char]) -> bool {
+    let digits: Option<Vec<u32>> = chars.iter().map(|c| c.to_digit(10)).collect();
+
+    match digits {
+        Some(digits) if digits.starts_with(&[9, 7, 8]) || digits.starts_with(&[9, 7, 9]) => {
+            isbn13_sum(&digits).is_multiple_of(10)
+        }
+        _ => false,
+    }
+}
+
+fn isbn13_sum(digits: &[u32]) -> u32 {
+    digits
+        .iter()
+        .enumerate()
+        .map(|(i, digit)| match i % 2 {
+            0 => *digit,
+            _ => digit * 3,
+        })
+        .sum()
+}
+
+// isbn-10 books are the 978 prefix of isbn-13, only the check digit is recomputed
+fn isbn10_to_13(chars: &[char]) -> String {
+    let mut digits: Vec<u32> = vec![9, 7, 8];
+    digits.extend(chars[..9].iter().filter_map(|c| c.to_digit(10)));
+
+    let check = (10 - isbn13_sum(&digits) % 10) % 10;
+    digits.push(check);
+
+    digits.iter().map(|digit| digit.to_string()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn isbn_of(value: &str) -> Option<Identifier> {
+        Some(Identifier::Isbn(value.to_string()))
+    }
+
+    #[test]
+    fn isbn_check_digits() {
+        assert_eq!(detect("9780441172719"), isbn_of("9780441172719"));
+        assert_eq!(detect("0441172717"), isbn_of("9780441172719"));
+        assert_eq!(detect("9780441172718"), None);
+        assert_eq!(detect("0441172718"), None);
+        // only 978 and 979 are book prefixes
+        assert_eq!(detect("9770441172712"), None);
+    }
+
+    #[test]
+    fn isbn_with_x_check_digit() {
+        assert_eq!(detect("080442957X"), isbn_of("9780804429573"));
+        assert_eq!(detect("080442957x"), isbn_of("9780804429573"));
+        // X is only a check digit
+        assert_eq!(detect("08044295X7"), None);
+    }
+
+    #[test]
+    fn isbn_with_hyphens_and_prefix() {
+        assert_eq!(detect("978-0-441-17271-9"), isbn_of("9780441172719"));
+        assert_eq!(detect("ISBN 0-441-17271-7"), isbn_of("9780441172719"));
+        assert_eq!(detect("ISBN:9780441172719"), isbn_of("9780441172719"));
+        assert_eq!(
+            detect("isbn-13: 978-0-441-17271-9"),
+            isbn_of("9780441172719")
+        );
+        assert_eq!(detect("Dune isbn:0441172717"), isbn_of("9780441172719"));
+    }
+
+    #[test]
+    fn links_without_scheme() {
+        assert_eq!(
+            detect("goodreads.com/book/show/234225.Dune"),
+            Some(Identifier::Goodreads("234225".to_string()))
+        );
+        assert_eq!(
+            detect("m.imdb.com/title/tt0087182/"),
+            Some(Identifier::Imdb("tt0087182".to_string()))
+        );
+        assert_eq!(
+            detect("https://www.imdb.com/title/tt0087182/?ref_=nv_sr"),
+            Some(Identifier::Imdb("tt0087182".to_string()))
+        );
+        assert_eq!(detect("imdb.com/title/tt12/"), None);
+    }
+
+    #[test]
+    fn goodreads_slugs() {
+        for link in [
+            "https://www.goodreads.com/book/show/234225.Dune",
+            "https://www.goodreads.com/book/show/234225-dune",
+            "https://m.goodreads.com/book/show/234225",
+        ] {
+            assert_eq!(
+                detect(link),
+                Some(Identifier::Goodreads("234225".to_string()))
+            );
+        }
+
+        assert_eq!(detect("https://www.goodreads.com/book/show/dune"), None);
+    }
+
+    #[test]
+    fn plain_titles_have_none() {
+        assert_eq!(detect("Dune by Frank Herbert"), None);
+        assert_eq!(detect("1984"), None);
+    }
+}
@@ -1,80 +1,656 @@
 use crate::models::{
-    ManageEventRequest, RegisterEventRequest, RegisterEventResponse, StartEventResponse,
+    ApiVersionResponse, ManageEventRequest, RegisterEventRequest, RegisterEventRequestV1,
+    RegisterEventResponse, StartEventResponse,
 };
-use std::error::Error;
+use chrono::Utc;
+use openssl::hash::MessageDigest;
+use openssl::pkey::PKey;
+use openssl::sign::Signer;
+use rand::Rng;
+use reqwest::header::CONTENT_TYPE;
+use reqwest::{RequestBuilder, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 use uuid::Uuid;
 
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(200);
+// a single wait never grows past this, however many retries are configured
+const RETRY_MAX_DELAY: Duration = Duration::from_secs(10);
+pub const MAX_RETRIES: u32 = 10;
+// consecutive failures that open the circuit and how long it stays open
+const BREAKER_THRESHOLD: u32 = 5;
+const BREAKER_COOLDOWN: Duration = Duration::from_secs(30);
+
+const REQUEST_ID_HEADER: &str = "X-Request-Id";
+pub const TIMESTAMP_HEADER: &str = "X-Clubvent-Timestamp";
+pub const SIGNATURE_HEADER: &str = "X-Clubvent-Signature";
+// signed requests older than this are refused, so a captured one can't be replayed
+const MAX_SIGNATURE_AGE_SECONDS: i64 = 300;
+
+pub struct InsightsOptions {
+    pub address: String,
+    pub timeout: Duration,
+    pub retries: u32,
+    pub auth: InsightsAuth,
+}
+
+#[derive(Clone)]
+pub enum InsightsAuth {
+    None,
+    Bearer(String),
+    // signs the timestamp and body, so a captured request can't be altered or replayed later
+    Hmac(String),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ApiVersion {
+    V1,
+    V2,
+}
+
+impl ApiVersion {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::V1 => "v1",
+            Self::V2 => "v2",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum InsightsError {
+    // the circuit is open, insights wasn't called at all
+    Unavailable,
+    Transport(reqwest::Error),
+    Status { status: StatusCode, body: String },
+    Decode(String),
+    // insights explained why it refused the call, the reason is meant for the club
+    Rejected(String),
+}
+
+// the error field any insights response may carry
+#[derive(Deserialize)]
+struct ErrorResponse {
+    error: Option<String>,
+}
+
+impl InsightsError {
+    // only failures that may go away on their own are worth another attempt
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Self::Transport(_) => true,
+            Self::Status { status, .. } => {
+                status.is_server_error() || *status == StatusCode::TOO_MANY_REQUESTS
+            }
+            Self::Unavailable | Self::Decode(_) | Self::Rejected(_) => false,
+        }
+    }
+}
+
+impl fmt::Display for InsightsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unavailable => write!(f, "insights is unavailable, circuit is open"),
+            Self::Transport(err) => write!(f, "insights request failed: {}", err),
+            Self::Status { status, body } => write!(f, "insights responded {}: {}", status, body),
+            Self::Decode(err) => write!(f, "cannot parse insights response: {}", err),
+            Self::Rejected(reason) => write!(f, "insights rejected the call: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for InsightsError {}
+
+#[derive(Default)]
+struct BreakerState {
+    failures: u32,
+    opened_at: Option<Instant>,
+    // the half-open call in flight, a probe that never reported back is given up after a cooldown
+    probe_started: Option<Instant>,
+}
+
+// after BREAKER_THRESHOLD failures in a row calls fail fast until the cooldown passes,
+// then a single call is let through and its result closes or reopens the circuit
+#[derive(Default)]
+struct CircuitBreaker {
+    state: Mutex<BreakerState>,
+}
+
+impl CircuitBreaker {
+    fn allow(&self) -> bool {
+        let mut state = self.state.lock().unwrap();
+
+        match state.opened_at {
+            None => true,
+            Some(opened_at) if opened_at.elapsed() < BREAKER_COOLDOWN => false,
+            Some(_) => match state.probe_started {
+                Some(started) if started.elapsed() < BREAKER_COOLDOWN => false,
+                _ => {
+                    state.probe_started = Some(Instant::now());
+                    true
+                }
+            },
+        }
+    }
+
+    fn record(&self, success: bool) {
+        let mut state = self.state.lock().unwrap();
+
+        if success {
+            *state = BreakerState::default();
+            return;
+        }
+
+        state.failures += 1;
+        state.probe_started = None;
+        if state.failures >= BREAKER_THRESHOLD {
+            if state.opened_at.is_none() {
+                log::warn!("insights failed {} times, opening circuit", state.failures);
+            }
+            state.opened_at = Some(Instant::now());
+        }
+    }
+}
+
 pub struct InsightsClient {
     client: reqwest::Client,
     address: String,
+    retries: u32,
+    auth: InsightsAuth,
+    breaker: CircuitBreaker,
+    // discovered on the first call, none until insights gave a definite answer
+    version: Mutex<Option<ApiVersion>>,
 }
 
-pub fn new(address: String) -> InsightsClient {
+pub fn new(opts: &InsightsOptions) -> InsightsClient {
     InsightsClient {
-        client: reqwest::Client::new(),
-        address,
+        client: reqwest::Client::builder()
+            .timeout(opts.timeout)
+            .build()
+            .expect("Failed to build insights client"),
+        address: opts.address.clone(),
+        retries: opts.retries,
+        auth: opts.auth.clone(),
+        breaker: CircuitBreaker::default(),
+        version: Mutex::new(None),
     }
 }
 
 impl InsightsClient {
+    // every call carries an idempotency key, so insights handles a retried call only once
     pub async fn register_event(
         &self,
         req: RegisterEventRequest,
-    ) -> Result<String, Box<dyn Error>> {
-        let response = self
-            .client
-            .post(format!("{}/api/v1/event/register", self.address.clone()))
-            .header("Content-Type", "application/json")
-            .body(serde_json::to_string(&req).unwrap())
-            .send()
+        key: Uuid,
+    ) -> Result<String, InsightsError> {
+        let body = match self.version().await {
+            ApiVersion::V1 => {
+                let req = RegisterEventRequestV1::from(&req);
+                self.call(ApiVersion::V1, "event/register", &req, key)
+                    .await?
+            }
+            ApiVersion::V2 => {
+                self.call(ApiVersion::V2, "event/register", &req, key)
+                    .await?
+            }
+        };
+
+        decode::<RegisterEventResponse>(&body).map(|parsed| parsed.insights_link)
+    }
+
+    pub async fn start_event(
+        &self,
+        event_id: Uuid,
+        key: Uuid,
+    ) -> Result<StartEventResponse, InsightsError> {
+        let version = self.version().await;
+        let body = self
+            .call(
+                version,
+                "event/start",
+                &ManageEventRequest { event_id },
+                key,
+            )
             .await?;
 
-        match response.status() {
-            reqwest::StatusCode::OK => match response.json::<RegisterEventResponse>().await {
-                Ok(parsed) => Ok(parsed.insights_link),
-                Err(_) => Err(Box::<dyn Error>::from("cannot parse body")),
-            },
-            _ => Err(Box::<dyn Error>::from("smth went wrong")),
+        decode::<StartEventResponse>(&body)
+    }
+
+    pub async fn finish_event(&self, event_id: Uuid, key: Uuid) -> Result<(), InsightsError> {
+        let version = self.version().await;
+
+        self.call(
+            version,
+            "event/finish",
+            &ManageEventRequest { event_id },
+            key,
+        )
+        .await
+        .map(|_| ())
+    }
+
+    // insights without /api/version only speaks v1, an unreachable one is asked again next time
+    async fn version(&self) -> ApiVersion {
+        if let Some(version) = *self.version.lock().unwrap() {
+            return version;
+        }
+
+        if !self.breaker.allow() {
+            return ApiVersion::V1;
+        }
+
+        let request = self.client.get(format!("{}/api/version", self.address));
+        let response = self.authorize(request, b"").send().await;
+        // only an unreachable or failing insights counts against the circuit
+        self.breaker.record(match &response {
+            Ok(response) => !response.status().is_server_error(),
+            Err(_) => false,
+        });
+
+        let discovered = match response {
+            Ok(response) if response.status() == StatusCode::NOT_FOUND => Some(ApiVersion::V1),
+            Ok(response) if response.status() == StatusCode::OK => {
+                match response.json::<ApiVersionResponse>().await {
+                    Ok(parsed) if parsed.versions.iter().any(|v| v == "v2") => Some(ApiVersion::V2),
+                    Ok(_) => Some(ApiVersion::V1),
+                    Err(err) => {
+                        log::warn!("cannot parse insights versions: {}", err);
+                        None
+                    }
+                }
+            }
+            Ok(response) => {
+                log::warn!(
+                    "insights responded {} to version discovery",
+                    response.status()
+                );
+                None
+            }
+            Err(err) => {
+                log::warn!("insights version discovery failed: {}", err);
+                None
+            }
+        };
+
+        match discovered {
+            Some(version) => {
+                log::info!("using insights api {}", version.as_str());
+                *self.version.lock().unwrap() = Some(version);
+                version
+            }
+            None => ApiVersion::V1,
         }
     }
 
-    pub async fn start_event(&self, event_id: Uuid) -> Result<String, Box<dyn Error>> {
-        let request = serde_json::to_string(&ManageEventRequest { event_id }).unwrap();
+    async fn call<B: Serialize>(
+        &self,
+        version: ApiVersion,
+        path: &str,
+        body: &B,
+        key: Uuid,
+    ) -> Result<String, InsightsError> {
+        let path = format!("/api/{}/{}", version.as_str(), path);
+        // retries share the request id, so insights logs show them as one call
+        let request_id = Uuid::new_v4();
+        let body = serde_json::to_vec(body).expect("insights request should serialize");
+        let mut attempt = 0;
 
-        let response = self
+        loop {
+            if !self.breaker.allow() {
+                return Err(InsightsError::Unavailable);
+            }
+
+            let result = self.send(&path, &body, key, request_id).await;
+            // bad responses don't mean insights is down
+            self.breaker
+                .record(!matches!(&result, Err(err) if err.is_transient()));
+
+            match result {
+                Err(err) if err.is_transient() && attempt < self.retries => {
+                    attempt += 1;
+                    let delay = backoff(attempt);
+                    log::warn!(
+                        "{}, retrying {} request {} in {:?}",
+                        err,
+                        path,
+                        request_id,
+                        delay
+                    );
+                    tokio::time::sleep(delay).await;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    async fn send(
+        &self,
+        path: &str,
+        body: &[u8],
+        key: Uuid,
+        request_id: Uuid,
+    ) -> Result<String, InsightsError> {
+        let request = self
             .client
-            .post(format!("{}/api/v1/event/start", self.address.clone()))
-            .header("Content-Type", "application/json")
-            .body(request)
+            .post(format!("{}{}", self.address, path))
+            .header(CONTENT_TYPE, "application/json")
+            .header("Idempotency-Key", key.to_string())
+            .header(REQUEST_ID_HEADER, request_id.to_string());
+
+        let response = self
+            .authorize(request, body)
+            .body(body.to_vec())
             .send()
-            .await?;
+            .await
+            .map_err(InsightsError::Transport)?;
 
-        match response.status() {
-            reqwest::StatusCode::OK => match response.json::<StartEventResponse>().await {
-                Ok(parsed) => Ok(parsed.summary_link),
-                Err(_) => Err(Box::<dyn Error>::from("cannot parse body")),
-            },
-            _ => Err(Box::<dyn Error>::from(format!(
-                "smth went wrong: {}",
-                response.status()
-            ))),
+        let status = response.status();
+        let body = response.text().await.map_err(InsightsError::Transport)?;
+
+        // a reason given with a success or a client error won't change on a retry
+        match (status, rejection(&body)) {
+            (StatusCode::OK, None) => Ok(body),
+            (StatusCode::OK, Some(reason)) => Err(InsightsError::Rejected(reason)),
+            (status, Some(reason))
+                if status.is_client_error() && status != StatusCode::TOO_MANY_REQUESTS =>
+            {
+                Err(InsightsError::Rejected(reason))
+            }
+            _ => Err(InsightsError::Status { status, body }),
         }
     }
 
-    pub async fn finish_event(&self, event_id: Uuid) -> Result<(), Box<dyn Error>> {
-        let request = serde_json::to_string(&ManageEventRequest { event_id }).unwrap();
+    fn authorize(&self, request: RequestBuilder, body: &[u8]) -> RequestBuilder {
+        match &self.auth {
+            InsightsAuth::None => request,
+            InsightsAuth::Bearer(token) => request.bearer_auth(token),
+            InsightsAuth::Hmac(secret) => {
+                let timestamp = Utc::now().timestamp().to_string();
+                request
+                    .header(SIGNATURE_HEADER, sign(secret, &timestamp, body))
+                    .header(TIMESTAMP_HEADER, timestamp)
+            }
+        }
+    }
+}
 
-        let response = self
-            .client
-            .post(format!("{}/api/v1/event/finish", self.address.clone()))
-            .header("Content-Type", "application/json")
-            .body(request)
+fn rejection(body: &str) -> Option<String> {
+    serde_json::from_str::<ErrorResponse>(body)
+        .ok()
+        .and_then(|parsed| parsed.error)
+        .filter(|reason| !reason.is_empty())
+}
+
+fn decode<R: DeserializeOwned>(body: &str) -> Result<R, InsightsError> {
+    serde_json::from_str(body).map_err(|err| InsightsError::Decode(err.to_string()))
+}
+
+// "sha256=" and the hex hmac of "<timestamp>.<body>"
+pub fn sign(secret: &str, timestamp: &str, body: &[u8]) -> String {
+    let key = PKey::hmac(secret.as_bytes()).expect("hmac key should be valid");
+    let mut signer = Signer::new(MessageDigest::sha256(), &key).expect("hmac should be available");
+
+    signer.update(timestamp.as_bytes()).unwrap();
+    signer.update(b".").unwrap();
+    signer.update(body).unwrap();
+
+    let mac = signer.sign_to_vec().unwrap();
+    let hex: String = mac.iter().map(|byte| format!("{:02x}", byte)).collect();
+
+    format!("sha256={}", hex)
+}
+
+// checks a request signed by sign, callbacks from insights use the same scheme
+pub fn verify(secret: &str, timestamp: &str, signature: &str, body: &[u8]) -> bool {
+    let fresh = match timestamp.parse::<i64>() {
+        Ok(sent_at) => (Utc::now().timestamp() - sent_at).abs() <= MAX_SIGNATURE_AGE_SECONDS,
+        Err(_) => false,
+    };
+
+    let expected = sign(secret, timestamp, body);
+
+    fresh
+        && expected.len() == signature.len()
+        && openssl::memcmp::eq(expected.as_bytes(), signature.as_bytes())
+}
+
+// exponential backoff with full jitter, so retrying clients don't line up
+fn backoff(attempt: u32) -> Duration {
+    let max = 2u32
+        .checked_pow(attempt.saturating_sub(1))
+        .and_then(|factor| RETRY_BASE_DELAY.checked_mul(factor))
+        .map_or(RETRY_MAX_DELAY, |delay| delay.min(RETRY_MAX_DELAY));
+    rand::thread_rng().gen_range(Duration::ZERO..=max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Bytes;
+    use axum::extract::State;
+    use axum::http::HeaderMap;
+    use axum::routing::post;
+    use axum::Router;
+    use std::net::TcpListener;
+    use std::sync::Arc;
+
+    const SECRET: &str = "insights-secret";
+
+    // what the fake insights got, kept to replay it with changes
+    #[derive(Clone)]
+    struct Signed {
+        timestamp: String,
+        signature: String,
+        body: Vec<u8>,
+    }
+
+    type Received = Arc<Mutex<Vec<Signed>>>;
+
+    // insights without /api/version, so the client stays on v1
+    fn fake_insights() -> (String, Received) {
+        let received = Received::default();
+        let app = Router::new()
+            .route("/api/v1/event/finish", post(finish))
+            .with_state(received.clone());
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = format!("http://{}", listener.local_addr().unwrap());
+        let server = axum::Server::from_tcp(listener)
+            .unwrap()
+            .serve(app.into_make_service());
+        tokio::spawn(server);
+
+        (address, received)
+    }
+
+    async fn finish(
+        State(received): State<Received>,
+        headers: HeaderMap,
+        body: Bytes,
+    ) -> (StatusCode, &'static str) {
+        let header = |name: &str| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .unwrap_or_default()
+                .to_string()
+        };
+
+        let signed = Signed {
+            timestamp: header(TIMESTAMP_HEADER),
+            signature: header(SIGNATURE_HEADER),
+            body: body.to_vec(),
+        };
+        let valid = verify(SECRET, &signed.timestamp, &signed.signature, &signed.body);
+        received.lock().unwrap().push(signed);
+
+        match valid {
+            true => (StatusCode::OK, "{}"),
+            false => (StatusCode::UNAUTHORIZED, ""),
+        }
+    }
+
+    fn client(address: String, secret: &str) -> InsightsClient {
+        new(&InsightsOptions {
+            address,
+            timeout: Duration::from_secs(5),
+            retries: 0,
+            auth: InsightsAuth::Hmac(secret.to_string()),
+        })
+    }
+
+    // sends a request the way the client would, with whatever headers the test wants
+    async fn replay(address: &str, timestamp: &str, signature: &str, body: &[u8]) -> StatusCode {
+        reqwest::Client::new()
+            .post(format!("{}/api/v1/event/finish", address))
+            .header(TIMESTAMP_HEADER, timestamp)
+            .header(SIGNATURE_HEADER, signature)
+            .body(body.to_vec())
             .send()
-            .await?;
+            .await
+            .unwrap()
+            .status()
+    }
+
+    #[tokio::test]
+    async fn signed_requests_verify() {
+        let (address, received) = fake_insights();
+        let event_id = Uuid::new_v4();
+
+        client(address.clone(), SECRET)
+            .finish_event(event_id, Uuid::new_v4())
+            .await
+            .unwrap();
+
+        let signed = received.lock().unwrap()[0].clone();
+        assert!(signed.signature.starts_with("sha256="));
+
+        let tampered = String::from_utf8(signed.body.clone())
+            .unwrap()
+            .replace(&event_id.to_string(), &Uuid::new_v4().to_string());
+        assert_eq!(
+            replay(
+                &address,
+                &signed.timestamp,
+                &signed.signature,
+                tampered.as_bytes()
+            )
+            .await,
+            StatusCode::UNAUTHORIZED
+        );
+
+        let stale = (Utc::now().timestamp() - MAX_SIGNATURE_AGE_SECONDS - 1).to_string();
+        assert_eq!(
+            replay(
+                &address,
+                &stale,
+                &sign(SECRET, &stale, &signed.body),
+                &signed.body
+            )
+            .await,
+            StatusCode::UNAUTHORIZED
+        );
+
+        let now = Utc::now().timestamp().to_string();
+        assert_eq!(
+            replay(
+                &address,
+                &now,
+                &sign(SECRET, &now, &signed.body),
+                &signed.body
+            )
+            .await,
+            StatusCode::OK
+        );
+        assert_eq!(
+            replay(
+                &address,
+                &now,
+                &sign("another-secret", &now, &signed.body),
+                &signed.body
+            )
+            .await,
+            StatusCode::UNAUTHORIZED
+        );
+    }
+
+    #[tokio::test]
+    async fn wrong_secret_is_refused() {
+        let (address, received) = fake_insights();
+
+        let result = client(address, "another-secret")
+            .finish_event(Uuid::new_v4(), Uuid::new_v4())
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(InsightsError::Status {
+                status: StatusCode::UNAUTHORIZED,
+                ..
+            })
+        ));
+        assert_eq!(received.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn backoff_stays_under_the_cap() {
+        for attempt in [1, 2, 10, 32, 33, u32::MAX] {
+            assert!(backoff(attempt) <= RETRY_MAX_DELAY);
+        }
+
+        assert!(backoff(1) <= RETRY_BASE_DELAY);
+    }
 
-        match response.status() {
-            reqwest::StatusCode::OK => Ok(()),
-            _ => Err(Box::<dyn Error>::from("smth went wrong")),
+    fn open_breaker() -> CircuitBreaker {
+        let breaker = CircuitBreaker::default();
+        for _ in 0..BREAKER_THRESHOLD {
+            breaker.record(false);
         }
+        breaker
+    }
+
+    fn cool_down(breaker: &CircuitBreaker) {
+        breaker.state.lock().unwrap().opened_at = Some(Instant::now() - BREAKER_COOLDOWN);
+    }
+
+    #[test]
+    fn breaker_lets_one_probe_through() {
+        let breaker = open_breaker();
+        assert!(!breaker.allow());
+
+        cool_down(&breaker);
+        assert!(breaker.allow());
+        assert!(!breaker.allow());
+
+        // a failed probe opens the circuit for another cooldown
+        breaker.record(false);
+        assert!(!breaker.allow());
+
+        cool_down(&breaker);
+        assert!(breaker.allow());
+        breaker.record(true);
+        assert!(breaker.allow());
+        assert!(breaker.allow());
+    }
+
+    #[tokio::test]
+    async fn failed_discovery_opens_the_circuit() {
+        // nothing listens on a port that was just released
+        let address = {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            format!("http://{}", listener.local_addr().unwrap())
+        };
+        let client = client(address, SECRET);
+
+        for _ in 0..BREAKER_THRESHOLD {
+            assert!(client.breaker.allow());
+            client.version().await;
+        }
+
+        assert!(!client.breaker.allow());
+        assert!(client.version.lock().unwrap().is_none());
     }
 }
@@ -1,11 +1,65 @@
 mod bot;
+mod calendar;
+mod catalog;
+mod config;
+#[cfg(test)]
+mod conformance;
 mod err;
+mod i18n;
+mod ical;
+mod identifier;
 mod insights;
+mod markdown;
+mod migrations;
 mod models;
 mod repository;
 mod service;
+mod sqlite;
+mod webhook;
+
+use config::{ConfigError, DatabaseConfig};
+use dotenv::dotenv;
+use std::env;
+use std::process;
 
 #[tokio::main]
 async fn main() {
-    bot::run().await;
+    dotenv().ok();
+    pretty_env_logger::init();
+
+    match env::args().nth(1).as_deref() {
+        Some("migrate") => migrate(config::load_database()).await,
+        _ => {
+            let config = match config::load() {
+                Ok(config) => config,
+                Err(err) => {
+                    eprintln!("{}", err);
+                    process::exit(1);
+                }
+            };
+
+            if let Err(err) = bot::run(config).await {
+                eprintln!("unable to start the service: {}", err);
+                process::exit(1);
+            }
+        }
+    }
+}
+
+// migrations also run on startup, the subcommand applies them without starting the bot
+async fn migrate(database: Result<DatabaseConfig, ConfigError>) {
+    let database = match database {
+        Ok(database) => database,
+        Err(err) => {
+            eprintln!("{}", err);
+            process::exit(1);
+        }
+    };
+
+    if let Err(err) = repository::new_repository(&database.dsn, &database.postgres).await {
+        eprintln!("unable to migrate database: {}", err);
+        process::exit(1);
+    }
+
+    log::info!("database is up to date");
 }
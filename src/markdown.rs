@@ -0,0 +1,99 @@
+// characters telegram reserves in MarkdownV2 text, see https://core.telegram.org/bots/api#markdownv2-style
+const RESERVED: [char; 19] = [
+    '\\', '_', '*', '[', ']', '(', ')', '~', '`', '>', '#', '+', '-', '=', '|', '{', '}', '.', '!',
+];
+
+// makes user-provided text safe to put into a MarkdownV2 message
+pub fn escape(text: &str) -> String {
+    escape_chars(text, &RESERVED)
+}
+
+// inside (...) of inline links only ')' and '\' have to be escaped
+pub fn escape_url(url: &str) -> String {
+    escape_chars(url, &['\\', ')'])
+}
+
+// a link that pings the member, it works for members without a username too
+pub fn user_mention(user_id: i64, name: &str) -> String {
+    format!("[{}](tg://user?id={})", escape(name), user_id)
+}
+
+fn escape_chars(text: &str, reserved: &[char]) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        if reserved.contains(&c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    // arbitrary unicode with the reserved characters showing up often enough to matter
+    fn text() -> impl Strategy<Value = String> {
+        prop::collection::vec(
+            prop_oneof![any::<char>(), prop::sample::select(RESERVED.to_vec())],
+            0..64,
+        )
+        .prop_map(|chars| chars.into_iter().collect())
+    }
+
+    fn unescape(text: &str) -> String {
+        let mut chars = text.chars();
+        let mut unescaped = String::with_capacity(text.len());
+
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => unescaped.extend(chars.next()),
+                c => unescaped.push(c),
+            }
+        }
+
+        unescaped
+    }
+
+    proptest! {
+        #[test]
+        fn escape_leaves_no_reserved_char_bare(text in text()) {
+            let escaped = escape(&text);
+            let mut chars = escaped.chars();
+
+            while let Some(c) = chars.next() {
+                match c {
+                    '\\' => prop_assert!(chars.next().is_some_and(|next| RESERVED.contains(&next))),
+                    c => prop_assert!(!RESERVED.contains(&c)),
+                }
+            }
+        }
+
+        #[test]
+        fn escape_round_trips(text in text()) {
+            prop_assert_eq!(unescape(&escape(&text)), text);
+        }
+
+        #[test]
+        fn escape_url_only_touches_backslash_and_paren(text in text()) {
+            let escaped = escape_url(&text);
+            let touched = text.chars().filter(|c| matches!(c, '\\' | ')')).count();
+
+            let mut chars = escaped.chars();
+
+            while let Some(c) = chars.next() {
+                match c {
+                    '\\' => prop_assert!(matches!(chars.next(), Some('\\' | ')'))),
+                    c => prop_assert_ne!(c, ')'),
+                }
+            }
+
+            prop_assert_eq!(escaped.chars().count(), text.chars().count() + touched);
+            prop_assert_eq!(unescape(&escaped), text);
+        }
+    }
+}
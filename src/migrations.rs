@@ -0,0 +1,194 @@
+use tokio_postgres::{Client, Error};
+
+struct Migration {
+    version: i32,
+    name: &'static str,
+    sql: &'static str,
+}
+
+// migrations are embedded and applied in order, a new one only gets appended here
+const MIGRATIONS: [Migration; 18] = [
+    Migration {
+        version: 1,
+        name: "baseline",
+        sql: include_str!("../migrations/0001_baseline.sql"),
+    },
+    Migration {
+        version: 2,
+        name: "club_audit",
+        sql: include_str!("../migrations/0002_club_audit.sql"),
+    },
+    Migration {
+        version: 3,
+        name: "event_tracks",
+        sql: include_str!("../migrations/0003_event_tracks.sql"),
+    },
+    Migration {
+        version: 4,
+        name: "club_settings",
+        sql: include_str!("../migrations/0004_club_settings.sql"),
+    },
+    Migration {
+        version: 5,
+        name: "unescape_markdown",
+        sql: include_str!("../migrations/0005_unescape_markdown.sql"),
+    },
+    Migration {
+        version: 6,
+        name: "active_event_invariants",
+        sql: include_str!("../migrations/0006_active_event_invariants.sql"),
+    },
+    Migration {
+        version: 7,
+        name: "insights_outbox",
+        sql: include_str!("../migrations/0007_insights_outbox.sql"),
+    },
+    Migration {
+        version: 8,
+        name: "suggestion_works",
+        sql: include_str!("../migrations/0008_suggestion_works.sql"),
+    },
+    Migration {
+        version: 9,
+        name: "suggestion_identifiers",
+        sql: include_str!("../migrations/0009_suggestion_identifiers.sql"),
+    },
+    Migration {
+        version: 10,
+        name: "club_kind",
+        sql: include_str!("../migrations/0010_club_kind.sql"),
+    },
+    Migration {
+        version: 11,
+        name: "reading_progress",
+        sql: include_str!("../migrations/0011_reading_progress.sql"),
+    },
+    Migration {
+        version: 12,
+        name: "ratings",
+        sql: include_str!("../migrations/0012_ratings.sql"),
+    },
+    Migration {
+        version: 13,
+        name: "discussion_threads",
+        sql: include_str!("../migrations/0013_discussion_threads.sql"),
+    },
+    Migration {
+        version: 14,
+        name: "questions",
+        sql: include_str!("../migrations/0014_questions.sql"),
+    },
+    Migration {
+        version: 15,
+        name: "hosts",
+        sql: include_str!("../migrations/0015_hosts.sql"),
+    },
+    Migration {
+        version: 16,
+        name: "event_location",
+        sql: include_str!("../migrations/0016_event_location.sql"),
+    },
+    Migration {
+        version: 17,
+        name: "calendar_feeds",
+        sql: include_str!("../migrations/0017_calendar_feeds.sql"),
+    },
+    Migration {
+        version: 18,
+        name: "event_revisions",
+        sql: include_str!("../migrations/0018_event_revisions.sql"),
+    },
+];
+
+// any constant works, it only keeps two instances from migrating at once
+const MIGRATIONS_LOCK: i64 = 0x636c_7562_7665_6e74;
+
+pub async fn run(client: &mut Client) -> Result<(), Error> {
+    client
+        .execute("SELECT pg_advisory_lock($1);", &[&MIGRATIONS_LOCK])
+        .await?;
+
+    let result = apply(client).await;
+
+    client
+        .execute("SELECT pg_advisory_unlock($1);", &[&MIGRATIONS_LOCK])
+        .await?;
+
+    result
+}
+
+async fn apply(client: &mut Client) -> Result<(), Error> {
+    // created under the lock, two instances creating it at once can fail on pg_type
+    client
+        .batch_execute(
+            "CREATE TABLE IF NOT EXISTS schema_history (
+                version int4 PRIMARY KEY NOT NULL,
+                name text NOT NULL,
+                applied_at timestamptz NOT NULL DEFAULT NOW()
+            );",
+        )
+        .await?;
+
+    detect_baseline(client).await?;
+
+    let applied: Vec<i32> = client
+        .query("SELECT version FROM schema_history;", &[])
+        .await?
+        .iter()
+        .map(|row| row.get(0))
+        .collect();
+
+    for migration in MIGRATIONS.iter() {
+        if applied.contains(&migration.version) {
+            continue;
+        }
+
+        log::info!(
+            "applying migration {} {}",
+            migration.version,
+            migration.name
+        );
+
+        let tx = client.transaction().await?;
+        tx.batch_execute(migration.sql).await?;
+        tx.execute(
+            "INSERT INTO schema_history (version, name) VALUES ($1, $2);",
+            &[&migration.version, &migration.name],
+        )
+        .await?;
+        tx.commit().await?;
+    }
+
+    Ok(())
+}
+
+// databases created from the old scheme.sql have tables but no history,
+// so the baseline is recorded as applied and later migrations are written to tolerate
+// objects scheme.sql may already have created
+async fn detect_baseline(client: &Client) -> Result<(), Error> {
+    let row = client
+        .query_one(
+            "SELECT
+                (SELECT count(*) FROM schema_history),
+                to_regclass('public.club') IS NOT NULL;",
+            &[],
+        )
+        .await?;
+
+    let history: i64 = row.get(0);
+    let has_tables: bool = row.get(1);
+
+    if history == 0 && has_tables {
+        log::info!("existing schema found, recording it as baseline");
+
+        let baseline = &MIGRATIONS[0];
+        client
+            .execute(
+                "INSERT INTO schema_history (version, name) VALUES ($1, $2);",
+                &[&baseline.version, &baseline.name],
+            )
+            .await?;
+    }
+
+    Ok(())
+}
@@ -1,15 +1,24 @@
-use chrono::NaiveDateTime;
+use crate::identifier::Identifier;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use chrono_tz::Tz;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 pub struct NewClubRequest {
     pub chat_id: i64,
+    pub actor_id: i64,
 }
 
 pub struct NewEventRequest {
     pub chat_id: i64,
+    pub actor_id: i64,
     pub event_id: Uuid,
     pub event_date: NaiveDateTime,
+    pub track: String,
+    pub with_insights: bool,
+    // the next one in the club's rotation, none when nobody is in it
+    pub host: Option<Member>,
+    pub location: Option<Location>,
 }
 
 pub struct LastEventRequest {
@@ -19,21 +28,43 @@ pub struct LastEventRequest {
 pub struct AchieveEventRequest {
     pub event_id: Uuid,
     pub chat_id: i64,
+    pub actor_id: i64,
+    pub outbox: Option<OutboxMessage>,
 }
 
 pub struct LastEventResponse {
     pub event_id: Uuid,
+    pub track: String,
     pub event_date: NaiveDateTime,
     pub subject: String,
     pub with_insights: bool,
     pub insights_link: Option<String>,
+    pub poll_message_id: Option<i32>,
+    // what the picked subject resolved to in the catalog
+    pub work: Option<Work>,
+    pub discussion: Option<DiscussionThread>,
+    // who ran /startclub, none until the event starts
+    pub started_by: Option<i64>,
+    pub host: Option<Member>,
+    pub location: Option<Location>,
+    // grows with every change a calendar shows, updated_at is the time of the latest one
+    pub revision: i32,
+    pub updated_at: NaiveDateTime,
+}
+
+pub struct ActiveEventsResponse {
+    pub events: Vec<LastEventResponse>,
 }
 
 pub struct NewMemberSuggestion {
     pub event_id: Uuid,
     pub chat_id: i64,
-    pub user_id: u32,
+    pub user_id: i64,
+    // the suggester's name as telegram showed it
+    pub name: String,
     pub suggestion: String,
+    pub work: Option<Work>,
+    pub identifier: Option<Identifier>,
 }
 
 pub struct EventSuggestionsRequest {
@@ -42,29 +73,270 @@ pub struct EventSuggestionsRequest {
 
 pub struct EventToggleWithInsightsRequest {
     pub event_id: Uuid,
+    pub chat_id: i64,
+    pub actor_id: i64,
     pub with_insights: bool,
 }
 
+pub struct EventSuggestion {
+    pub user_id: i64,
+    // none for suggestions made before names were kept
+    pub name: Option<String>,
+    pub suggestion: String,
+    pub work: Option<Work>,
+    pub identifier: Option<Identifier>,
+}
+
+// a catalog entry a suggestion was resolved to
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct Work {
+    // the catalog's own id, stable between searches
+    pub key: String,
+    pub title: String,
+    pub author: Option<String>,
+    pub year: Option<i32>,
+    pub pages: Option<i32>,
+    pub cover_url: Option<String>,
+    pub director: Option<String>,
+    // minutes a film runs or a game takes to play
+    pub runtime: Option<i32>,
+}
+
+pub struct SuggestionRequest {
+    pub id: i64,
+}
+
+pub struct SuggestionResponse {
+    pub chat_id: i64,
+    pub user_id: i64,
+    pub suggestion: String,
+}
+
+pub struct SuggestionWorkRequest {
+    pub id: i64,
+    pub chat_id: i64,
+    pub actor_id: i64,
+    pub work: Work,
+}
+
 pub struct EventSuggestionsResponse {
-    pub suggestions: Vec<String>,
+    pub suggestions: Vec<EventSuggestion>,
+}
+
+pub struct PickPollRequest {
+    pub event_id: Uuid,
+    pub chat_id: i64,
+    pub actor_id: i64,
+    pub message_id: i32,
 }
 
 pub struct PickedSubjectRequest {
     pub event_id: Uuid,
+    pub chat_id: i64,
+    pub actor_id: i64,
     pub subject: String,
-    pub insights_link: Option<String>,
+    pub work: Option<Work>,
+    pub suggested_by: Vec<Member>,
+    pub outbox: Option<OutboxMessage>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct Member {
+    pub user_id: i64,
+    pub name: Option<String>,
+}
+
+impl Member {
+    // an event's host is stored as two nullable columns
+    pub fn parse(user_id: Option<i64>, name: Option<String>) -> Option<Member> {
+        user_id.map(|user_id| Member { user_id, name })
+    }
+}
+
+// where an event takes place, the address is an address, a venue name or a video call link
+#[derive(Clone, Debug)]
+pub struct Location {
+    pub address: String,
+    // a map location was shared or written along with the address
+    pub coordinates: Option<Coordinates>,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Coordinates {
+    pub latitude: f64,
+    pub longitude: f64,
 }
 
+impl Location {
+    // an event's location is stored as three nullable columns
+    pub fn parse(
+        address: Option<String>,
+        latitude: Option<f64>,
+        longitude: Option<f64>,
+    ) -> Option<Location> {
+        let coordinates = match (latitude, longitude) {
+            (Some(latitude), Some(longitude)) => Some(Coordinates {
+                latitude,
+                longitude,
+            }),
+            _ => None,
+        };
+
+        address.map(|address| Location {
+            address,
+            coordinates,
+        })
+    }
+
+    pub fn latitude(&self) -> Option<f64> {
+        self.coordinates.map(|coordinates| coordinates.latitude)
+    }
+
+    pub fn longitude(&self) -> Option<f64> {
+        self.coordinates.map(|coordinates| coordinates.longitude)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum AuditAction {
+    ClubRegistered,
+    EventCreated,
+    SuggestionAdded,
+    InsightsToggled,
+    SubjectPicked,
+    EventStarted,
+    EventAchieved,
+    SettingsChanged,
+    HostAdded,
+    HostRemoved,
+    HostSwapped,
+    LocationChanged,
+    RatingGiven,
+    ReviewAdded,
+    QuestionAsked,
+    QuestionCovered,
+    CalendarReset,
+    ProgressMarked,
+    PollStarted,
+    WorkChosen,
+    DiscussionOpened,
+}
+
+impl AuditAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::ClubRegistered => "club_registered",
+            Self::EventCreated => "event_created",
+            Self::SuggestionAdded => "suggestion_added",
+            Self::InsightsToggled => "insights_toggled",
+            Self::SubjectPicked => "subject_picked",
+            Self::EventStarted => "event_started",
+            Self::EventAchieved => "event_achieved",
+            Self::SettingsChanged => "settings_changed",
+            Self::HostAdded => "host_added",
+            Self::HostRemoved => "host_removed",
+            Self::HostSwapped => "host_swapped",
+            Self::LocationChanged => "location_changed",
+            Self::RatingGiven => "rating_given",
+            Self::ReviewAdded => "review_added",
+            Self::QuestionAsked => "question_asked",
+            Self::QuestionCovered => "question_covered",
+            Self::CalendarReset => "calendar_reset",
+            Self::ProgressMarked => "progress_marked",
+            Self::PollStarted => "poll_started",
+            Self::WorkChosen => "work_chosen",
+            Self::DiscussionOpened => "discussion_opened",
+        }
+    }
+
+    pub fn parse(action: &str) -> Option<Self> {
+        match action {
+            "club_registered" => Some(Self::ClubRegistered),
+            "event_created" => Some(Self::EventCreated),
+            "suggestion_added" => Some(Self::SuggestionAdded),
+            "insights_toggled" => Some(Self::InsightsToggled),
+            "subject_picked" => Some(Self::SubjectPicked),
+            "event_started" => Some(Self::EventStarted),
+            "event_achieved" => Some(Self::EventAchieved),
+            "settings_changed" => Some(Self::SettingsChanged),
+            "host_added" => Some(Self::HostAdded),
+            "host_removed" => Some(Self::HostRemoved),
+            "host_swapped" => Some(Self::HostSwapped),
+            "location_changed" => Some(Self::LocationChanged),
+            "rating_given" => Some(Self::RatingGiven),
+            "review_added" => Some(Self::ReviewAdded),
+            "question_asked" => Some(Self::QuestionAsked),
+            "question_covered" => Some(Self::QuestionCovered),
+            "calendar_reset" => Some(Self::CalendarReset),
+            "progress_marked" => Some(Self::ProgressMarked),
+            "poll_started" => Some(Self::PollStarted),
+            "work_chosen" => Some(Self::WorkChosen),
+            "discussion_opened" => Some(Self::DiscussionOpened),
+            _ => None,
+        }
+    }
+}
+
+pub struct AuditRecord {
+    pub chat_id: i64,
+    pub actor_id: i64,
+    pub action: AuditAction,
+    pub payload: serde_json::Value,
+}
+
+pub struct AuditLogRequest {
+    pub chat_id: i64,
+    pub limit: i64,
+}
+
+pub struct AuditLogEntry {
+    pub actor_id: i64,
+    pub action: String,
+    pub payload: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+}
+
+pub struct AuditLogResponse {
+    pub entries: Vec<AuditLogEntry>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct ApiVersionResponse {
+    pub versions: Vec<String>,
+}
+
+// the v2 contract, v1 only gets the fields of RegisterEventRequestV1
 #[derive(Deserialize, Serialize, Clone)]
 pub struct RegisterEventRequest {
     pub event_id: Uuid,
     pub event_subject: String,
     pub club_id: i64,
+    pub club_name: String,
+    pub event_date: Option<DateTime<Utc>>,
+    pub participants: Vec<i64>,
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct RegisterEventRequestV1 {
+    pub event_id: Uuid,
+    pub event_subject: String,
+    pub club_id: i64,
+}
+
+impl From<&RegisterEventRequest> for RegisterEventRequestV1 {
+    fn from(req: &RegisterEventRequest) -> Self {
+        RegisterEventRequestV1 {
+            event_id: req.event_id,
+            event_subject: req.event_subject.clone(),
+            club_id: req.club_id,
+        }
+    }
 }
 
 #[derive(Deserialize, Serialize)]
 pub struct RegisterEventResponse {
     pub insights_link: String,
+    pub error: Option<String>,
 }
 
 #[derive(Deserialize, Serialize, Clone)]
@@ -75,5 +347,541 @@ pub struct ManageEventRequest {
 #[derive(Deserialize, Serialize)]
 pub struct StartEventResponse {
     pub summary_link: String,
+    // only v2 says when the summary stops being available
+    #[serde(default)]
+    pub summary_expires_at: Option<DateTime<Utc>>,
     pub error: Option<String>,
 }
+
+// what the outbox keeps for a registration, messages written before v2 only have the subject
+#[derive(Deserialize, Serialize, Default)]
+#[serde(default)]
+pub struct RegisterPayload {
+    pub subject: String,
+    pub club_name: String,
+    pub event_date: Option<DateTime<Utc>>,
+    pub participants: Vec<i64>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum PickMode {
+    Random,
+    Poll,
+    Ranked,
+}
+
+impl PickMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Random => "random",
+            Self::Poll => "poll",
+            Self::Ranked => "ranked",
+        }
+    }
+
+    pub fn parse(mode: &str) -> Option<Self> {
+        match mode {
+            "random" => Some(Self::Random),
+            "poll" => Some(Self::Poll),
+            "ranked" => Some(Self::Ranked),
+            _ => None,
+        }
+    }
+}
+
+// what the club picks, books unless changed
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ClubKind {
+    Books,
+    Films,
+    BoardGames,
+    Generic,
+}
+
+impl ClubKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Books => "books",
+            Self::Films => "films",
+            Self::BoardGames => "games",
+            Self::Generic => "generic",
+        }
+    }
+
+    pub fn parse(kind: &str) -> Option<Self> {
+        match kind {
+            "books" => Some(Self::Books),
+            "films" => Some(Self::Films),
+            "games" => Some(Self::BoardGames),
+            "generic" => Some(Self::Generic),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum DiscussionKind {
+    // a forum topic, the id is its message thread id
+    Topic,
+    // replies to a message of the bot, the id is that message
+    Reply,
+}
+
+impl DiscussionKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Topic => "topic",
+            Self::Reply => "reply",
+        }
+    }
+
+    pub fn parse(kind: &str) -> Option<Self> {
+        match kind {
+            "topic" => Some(Self::Topic),
+            "reply" => Some(Self::Reply),
+            _ => None,
+        }
+    }
+}
+
+// where spoilers of an event are posted
+#[derive(Clone, Copy)]
+pub struct DiscussionThread {
+    pub kind: DiscussionKind,
+    pub id: i32,
+}
+
+impl DiscussionThread {
+    pub fn parse(kind: Option<String>, id: Option<i32>) -> Option<DiscussionThread> {
+        let kind = DiscussionKind::parse(kind?.as_str())?;
+        Some(DiscussionThread { kind, id: id? })
+    }
+}
+
+pub struct DiscussionThreadRequest {
+    pub event_id: Uuid,
+    pub chat_id: i64,
+    pub actor_id: i64,
+    pub thread: DiscussionThread,
+}
+
+pub struct NewQuestionRequest {
+    pub id: Uuid,
+    pub event_id: Uuid,
+    pub chat_id: i64,
+    pub user_id: i64,
+    // none keeps the question anonymous
+    pub name: Option<String>,
+    pub question: String,
+}
+
+pub struct QuestionsRequest {
+    pub event_id: Uuid,
+}
+
+pub struct Question {
+    pub id: Uuid,
+    pub name: Option<String>,
+    pub question: String,
+    // given in shuffled order when the event starts
+    pub number: Option<i32>,
+    pub covered: bool,
+}
+
+pub struct QuestionsResponse {
+    pub questions: Vec<Question>,
+}
+
+pub struct QuestionNumber {
+    pub question_id: Uuid,
+    pub number: i32,
+}
+
+pub struct QuestionCoveredRequest {
+    pub question_id: Uuid,
+    pub event_id: Uuid,
+    pub chat_id: i64,
+    pub actor_id: i64,
+    // the number the question was posted under
+    pub number: i32,
+    pub covered: bool,
+}
+
+pub struct Host {
+    pub user_id: i64,
+    pub name: String,
+    pub position: i32,
+}
+
+pub struct HostsRequest {
+    pub chat_id: i64,
+}
+
+// the rotation in order
+pub struct HostsResponse {
+    pub hosts: Vec<Host>,
+    // who of the rotation got the latest event, the next event goes to the one after
+    pub last_host: Option<i64>,
+}
+
+pub struct NewHostRequest {
+    pub chat_id: i64,
+    pub actor_id: i64,
+    pub user_id: i64,
+    pub name: String,
+}
+
+pub struct RemoveHostRequest {
+    pub chat_id: i64,
+    pub actor_id: i64,
+    pub user_id: i64,
+}
+
+pub struct HostPosition {
+    pub user_id: i64,
+    pub position: i32,
+}
+
+pub struct EventLocationRequest {
+    pub event_id: Uuid,
+    pub chat_id: i64,
+    pub actor_id: i64,
+    pub location: Location,
+}
+
+pub struct CalendarTokenRequest {
+    pub chat_id: i64,
+}
+
+pub struct CalendarTokenResponse {
+    // none until the club asks for its feed link
+    pub token: Option<String>,
+}
+
+pub struct NewCalendarTokenRequest {
+    pub chat_id: i64,
+    pub actor_id: i64,
+    pub token: String,
+    // false when the club gets its first token
+    pub reset: bool,
+}
+
+pub struct CalendarRequest {
+    pub token: String,
+    // the latest events, upcoming ones included
+    pub limit: i64,
+}
+
+pub struct CalendarResponse {
+    pub chat_id: i64,
+    pub events: Vec<CalendarEvent>,
+}
+
+pub struct CalendarEvent {
+    pub event_id: Uuid,
+    pub track: String,
+    pub event_date: NaiveDateTime,
+    pub subject: String,
+    pub insights_link: Option<String>,
+    pub host: Option<Member>,
+    pub location: Option<Location>,
+    pub revision: i32,
+    pub updated_at: NaiveDateTime,
+}
+
+pub struct SwapHostRequest {
+    pub event_id: Uuid,
+    pub chat_id: i64,
+    pub actor_id: i64,
+    pub host: Member,
+    // the two hosts trade places in the rotation, empty when either isn't in it
+    pub positions: Vec<HostPosition>,
+}
+
+#[derive(Clone)]
+pub struct ClubSettings {
+    pub chat_id: i64,
+    pub default_insights: bool,
+    pub pick_mode: PickMode,
+    // max suggestions per member for one event, 0 means no limit
+    pub suggestion_limit: i32,
+    pub time_zone: String,
+    // none means following each member's telegram language
+    pub language: Option<String>,
+    // minutes before the event
+    pub reminder_offsets: Vec<i32>,
+    pub kind: ClubKind,
+    // only the event's host and chat admins may pick or achieve it
+    pub host_only: bool,
+}
+
+impl ClubSettings {
+    pub fn new(chat_id: i64) -> ClubSettings {
+        ClubSettings {
+            chat_id,
+            default_insights: false,
+            pick_mode: PickMode::Random,
+            suggestion_limit: 0,
+            time_zone: "UTC".to_string(),
+            language: None,
+            reminder_offsets: vec![],
+            kind: ClubKind::Books,
+            host_only: false,
+        }
+    }
+
+    pub fn tz(&self) -> Tz {
+        self.time_zone.parse().unwrap_or(Tz::UTC)
+    }
+}
+
+pub struct ClubSettingsRequest {
+    pub chat_id: i64,
+}
+
+pub struct UpdateClubSettingsRequest {
+    pub actor_id: i64,
+    pub settings: ClubSettings,
+}
+
+pub struct DueRemindersRequest {
+    pub now: DateTime<Utc>,
+}
+
+pub struct DueReminder {
+    pub event_id: Uuid,
+    pub chat_id: i64,
+    pub track: String,
+    pub event_date: NaiveDateTime,
+    pub subject: String,
+    pub picked_at: Option<NaiveDateTime>,
+    pub offset_minutes: i32,
+    pub host: Option<Member>,
+    pub location: Option<Location>,
+}
+
+pub struct DueRemindersResponse {
+    pub reminders: Vec<DueReminder>,
+}
+
+pub struct SentRemindersRequest {
+    pub event_id: Uuid,
+    pub offsets: Vec<i32>,
+}
+
+pub struct DueCheckinsRequest {
+    pub now: DateTime<Utc>,
+    // events last checked in on before this are due again
+    pub due_before: DateTime<Utc>,
+}
+
+pub struct DueCheckin {
+    pub event_id: Uuid,
+    pub chat_id: i64,
+    pub track: String,
+    pub subject: String,
+}
+
+pub struct DueCheckinsResponse {
+    pub checkins: Vec<DueCheckin>,
+}
+
+pub struct SentCheckinRequest {
+    pub event_id: Uuid,
+    pub sent_at: DateTime<Utc>,
+}
+
+pub struct NewProgressRequest {
+    pub event_id: Uuid,
+    pub chat_id: i64,
+    pub user_id: i64,
+    pub name: String,
+    pub percent: i32,
+    pub page: Option<i32>,
+}
+
+pub struct ProgressRequest {
+    pub event_id: Uuid,
+}
+
+pub struct MemberProgress {
+    pub name: String,
+    pub percent: i32,
+}
+
+// furthest along first
+pub struct ProgressResponse {
+    pub members: Vec<MemberProgress>,
+}
+
+pub struct NewRatingRequest {
+    pub event_id: Uuid,
+    pub chat_id: i64,
+    pub user_id: i64,
+    pub name: String,
+    // the rated subject, as the audit log shows it
+    pub subject: String,
+    pub stars: i32,
+}
+
+pub struct NewReviewRequest {
+    pub event_id: Uuid,
+    pub chat_id: i64,
+    pub user_id: i64,
+    pub subject: String,
+    pub review: String,
+}
+
+// picked events already achieved, the latest first
+pub struct AchievedEventsRequest {
+    pub chat_id: i64,
+    pub limit: i64,
+}
+
+pub struct Rating {
+    pub user_id: i64,
+    pub name: String,
+    pub stars: i32,
+    pub review: Option<String>,
+}
+
+pub struct AchievedEvent {
+    pub event_id: Uuid,
+    pub event_date: NaiveDateTime,
+    pub subject: String,
+    pub suggested_by: Vec<Member>,
+    pub ratings: Vec<Rating>,
+}
+
+pub struct AchievedEventsResponse {
+    pub events: Vec<AchievedEvent>,
+}
+
+pub struct EventChatRequest {
+    pub event_id: Uuid,
+}
+
+pub struct EventChatResponse {
+    pub chat_id: i64,
+    pub subject: String,
+    pub started_by: Option<i64>,
+    pub host_id: Option<i64>,
+}
+
+// callbacks insights posts to the bot's webhook
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum InsightsCallback {
+    SummaryReady {
+        event_id: Uuid,
+        summary_link: String,
+    },
+    InsightsSubmitted {
+        event_id: Uuid,
+        count: i32,
+    },
+}
+
+impl InsightsCallback {
+    pub fn event_id(&self) -> Uuid {
+        match self {
+            Self::SummaryReady { event_id, .. } | Self::InsightsSubmitted { event_id, .. } => {
+                *event_id
+            }
+        }
+    }
+}
+
+pub struct EventStartedRequest {
+    pub event_id: Uuid,
+    pub chat_id: i64,
+    pub actor_id: i64,
+    // none for events without insights
+    pub outbox: Option<OutboxMessage>,
+    // numbers for the questions that don't have one yet
+    pub numbers: Vec<QuestionNumber>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum OutboxKind {
+    Register,
+    Start,
+    Finish,
+}
+
+impl OutboxKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Register => "register",
+            Self::Start => "start",
+            Self::Finish => "finish",
+        }
+    }
+
+    pub fn parse(kind: &str) -> Option<Self> {
+        match kind {
+            "register" => Some(Self::Register),
+            "start" => Some(Self::Start),
+            "finish" => Some(Self::Finish),
+            _ => None,
+        }
+    }
+}
+
+// an insights call written in the same transaction as the change it belongs to,
+// the id is sent as the idempotency key so redelivery is harmless
+#[derive(Clone)]
+pub struct OutboxMessage {
+    pub id: Uuid,
+    pub event_id: Uuid,
+    pub chat_id: i64,
+    pub kind: OutboxKind,
+    pub payload: serde_json::Value,
+}
+
+impl OutboxMessage {
+    pub fn new(
+        kind: OutboxKind,
+        event_id: Uuid,
+        chat_id: i64,
+        payload: serde_json::Value,
+    ) -> OutboxMessage {
+        OutboxMessage {
+            id: Uuid::new_v4(),
+            event_id,
+            chat_id,
+            kind,
+            payload,
+        }
+    }
+}
+
+pub struct DueOutboxRequest {
+    pub now: DateTime<Utc>,
+    // only messages of this event, all events when none
+    pub event_id: Option<Uuid>,
+    pub limit: i64,
+}
+
+pub struct OutboxEntry {
+    pub message: OutboxMessage,
+    pub attempts: i32,
+}
+
+pub struct DueOutboxResponse {
+    pub entries: Vec<OutboxEntry>,
+}
+
+pub struct OutboxDeliveredRequest {
+    pub id: Uuid,
+    pub event_id: Uuid,
+    // set when a registration returned the event's insights link
+    pub insights_link: Option<String>,
+}
+
+pub struct OutboxFailedRequest {
+    pub id: Uuid,
+    pub error: String,
+    // none gives the message up
+    pub next_attempt_at: Option<DateTime<Utc>>,
+}
@@ -1,18 +1,98 @@
+use crate::identifier::Identifier;
+use crate::migrations;
 use crate::models::*;
+use crate::sqlite::new_sqlite_repository;
 use async_trait::async_trait;
-use bb8_postgres::bb8::Pool;
+use bb8_postgres::bb8::{Pool, RunError};
 use bb8_postgres::{tokio_postgres::NoTls, PostgresConnectionManager};
-use chrono::{DateTime, NaiveDateTime, Utc};
-use tokio_postgres::Error;
+use chrono::{DateTime, Utc};
+use openssl::ssl::{SslConnector, SslFiletype, SslMethod};
+use postgres_openssl::MakeTlsConnector;
+use serde_json::json;
+use std::fmt;
+use std::time::Duration;
+use tokio_postgres::config::SslMode;
+use tokio_postgres::error::SqlState;
+use tokio_postgres::tls::{MakeTlsConnect, TlsConnect};
+use tokio_postgres::types::Json;
+use tokio_postgres::{GenericClient, Socket};
 use uuid::Uuid;
 
-// todo use this trait in service, study box
+// storage errors are backend independent, so callers only care about conflicts
+#[derive(Debug)]
+pub enum Error {
+    // a unique constraint rejected the write
+    Conflict,
+    // no pooled connection became available within the connection timeout
+    TimedOut,
+    Postgres(tokio_postgres::Error),
+    Sqlite(rusqlite::Error),
+    Tls(openssl::error::ErrorStack),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Conflict => write!(f, "record already exists"),
+            Self::TimedOut => write!(f, "timed out waiting for a database connection"),
+            Self::Postgres(err) => write!(f, "postgres: {}", err),
+            Self::Sqlite(err) => write!(f, "sqlite: {}", err),
+            Self::Tls(err) => write!(f, "tls: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<tokio_postgres::Error> for Error {
+    fn from(err: tokio_postgres::Error) -> Self {
+        match err.code() {
+            Some(&SqlState::UNIQUE_VIOLATION) => Self::Conflict,
+            _ => Self::Postgres(err),
+        }
+    }
+}
+
+impl From<RunError<tokio_postgres::Error>> for Error {
+    fn from(err: RunError<tokio_postgres::Error>) -> Self {
+        match err {
+            RunError::User(err) => Self::from(err),
+            RunError::TimedOut => Self::TimedOut,
+        }
+    }
+}
+
+impl From<openssl::error::ErrorStack> for Error {
+    fn from(err: openssl::error::ErrorStack) -> Self {
+        Self::Tls(err)
+    }
+}
+
+impl From<rusqlite::Error> for Error {
+    fn from(err: rusqlite::Error) -> Self {
+        match err.sqlite_error_code() {
+            Some(rusqlite::ErrorCode::ConstraintViolation)
+                if matches!(
+                    err.sqlite_error().map(|e| e.extended_code),
+                    Some(rusqlite::ffi::SQLITE_CONSTRAINT_UNIQUE)
+                        | Some(rusqlite::ffi::SQLITE_CONSTRAINT_PRIMARYKEY)
+                ) =>
+            {
+                Self::Conflict
+            }
+            _ => Self::Sqlite(err),
+        }
+    }
+}
+
 #[async_trait]
-pub trait Repository {
+pub trait Repository: Send + Sync {
     async fn register_new_club(&self, req: NewClubRequest) -> Result<(), Error>;
     async fn write_new_event(&self, req: NewEventRequest) -> Result<(), Error>;
-    async fn get_latest_event(&self, req: LastEventRequest) -> Result<LastEventResponse, Error>;
-    async fn write_new_member_suggestion(&self, req: NewMemberSuggestion) -> Result<(), Error>;
+    async fn get_active_events(&self, req: LastEventRequest)
+        -> Result<ActiveEventsResponse, Error>;
+    // returns the id the suggestion can be referred to by later
+    async fn write_new_member_suggestion(&self, req: NewMemberSuggestion) -> Result<i64, Error>;
     async fn achieve_event(&self, req: AchieveEventRequest) -> Result<(), Error>;
     async fn get_all_suggestions_for_event(
         &self,
@@ -20,99 +100,374 @@ pub trait Repository {
     ) -> Result<EventSuggestionsResponse, Error>;
     async fn write_picked_subject(&self, req: PickedSubjectRequest) -> Result<(), Error>;
     async fn toggle_with_insights(&self, req: EventToggleWithInsightsRequest) -> Result<(), Error>;
+    async fn get_audit_log(&self, req: AuditLogRequest) -> Result<AuditLogResponse, Error>;
+    async fn write_pick_poll(&self, req: PickPollRequest) -> Result<(), Error>;
+    async fn get_club_settings(&self, req: ClubSettingsRequest) -> Result<ClubSettings, Error>;
+    async fn write_club_settings(&self, req: UpdateClubSettingsRequest) -> Result<(), Error>;
+    async fn get_due_reminders(
+        &self,
+        req: DueRemindersRequest,
+    ) -> Result<DueRemindersResponse, Error>;
+    async fn mark_reminders_sent(&self, req: SentRemindersRequest) -> Result<(), Error>;
+    async fn write_event_started(&self, req: EventStartedRequest) -> Result<(), Error>;
+    async fn get_due_outbox(&self, req: DueOutboxRequest) -> Result<DueOutboxResponse, Error>;
+    async fn mark_outbox_delivered(&self, req: OutboxDeliveredRequest) -> Result<(), Error>;
+    async fn mark_outbox_failed(&self, req: OutboxFailedRequest) -> Result<(), Error>;
+    async fn get_event_chat(
+        &self,
+        req: EventChatRequest,
+    ) -> Result<Option<EventChatResponse>, Error>;
+    async fn get_suggestion(
+        &self,
+        req: SuggestionRequest,
+    ) -> Result<Option<SuggestionResponse>, Error>;
+    async fn write_suggestion_work(&self, req: SuggestionWorkRequest) -> Result<(), Error>;
+    async fn get_due_checkins(&self, req: DueCheckinsRequest)
+        -> Result<DueCheckinsResponse, Error>;
+    async fn mark_checkin_sent(&self, req: SentCheckinRequest) -> Result<(), Error>;
+    async fn write_progress(&self, req: NewProgressRequest) -> Result<(), Error>;
+    async fn get_progress(&self, req: ProgressRequest) -> Result<ProgressResponse, Error>;
+    async fn write_rating(&self, req: NewRatingRequest) -> Result<(), Error>;
+    async fn write_review(&self, req: NewReviewRequest) -> Result<(), Error>;
+    async fn get_achieved_events(
+        &self,
+        req: AchievedEventsRequest,
+    ) -> Result<AchievedEventsResponse, Error>;
+    async fn write_discussion_thread(&self, req: DiscussionThreadRequest) -> Result<(), Error>;
+    async fn write_question(&self, req: NewQuestionRequest) -> Result<(), Error>;
+    async fn get_questions(&self, req: QuestionsRequest) -> Result<QuestionsResponse, Error>;
+    async fn write_question_covered(&self, req: QuestionCoveredRequest) -> Result<(), Error>;
+    async fn get_hosts(&self, req: HostsRequest) -> Result<HostsResponse, Error>;
+    // a conflict when the member is already in the rotation
+    async fn write_host(&self, req: NewHostRequest) -> Result<(), Error>;
+    async fn delete_host(&self, req: RemoveHostRequest) -> Result<(), Error>;
+    async fn write_event_host(&self, req: SwapHostRequest) -> Result<(), Error>;
+    async fn write_event_location(&self, req: EventLocationRequest) -> Result<(), Error>;
+    // none when the club isn't registered
+    async fn get_calendar_token(
+        &self,
+        req: CalendarTokenRequest,
+    ) -> Result<Option<CalendarTokenResponse>, Error>;
+    async fn write_calendar_token(&self, req: NewCalendarTokenRequest) -> Result<(), Error>;
+    // none when no club has the token
+    async fn get_calendar(&self, req: CalendarRequest) -> Result<Option<CalendarResponse>, Error>;
 }
 
-pub struct Postgres {
-    pool: Pool<PostgresConnectionManager<NoTls>>,
+// what bb8-postgres needs from a tls connector, NoTls and openssl both fit
+pub trait PostgresTls:
+    MakeTlsConnect<Socket, Stream: Send + Sync, TlsConnect: Send + TlsConnect<Socket, Future: Send>>
+    + Clone
+    + Send
+    + Sync
+    + 'static
+{
 }
 
-pub async fn new_postgres_repository(dsn: &str) -> Result<Postgres, Error> {
-    let manager = PostgresConnectionManager::new(dsn.parse()?, NoTls);
-    let pool = Pool::builder().build(manager).await.unwrap();
+impl<T> PostgresTls for T where
+    T: MakeTlsConnect<
+            Socket,
+            Stream: Send + Sync,
+            TlsConnect: Send + TlsConnect<Socket, Future: Send>,
+        > + Clone
+        + Send
+        + Sync
+        + 'static
+{
+}
+
+pub struct Postgres<T: PostgresTls = NoTls> {
+    pool: Pool<PostgresConnectionManager<T>>,
+}
+
+// connection settings on top of the dsn
+pub struct PostgresOptions {
+    pub ssl_root_cert: Option<String>,
+    pub ssl_cert: Option<String>,
+    pub ssl_key: Option<String>,
+    pub max_size: u32,
+    pub min_idle: Option<u32>,
+    pub connection_timeout: Duration,
+    pub idle_timeout: Option<Duration>,
+    pub connect_retries: u32,
+}
+
+const INITIAL_RETRY_DELAY: Duration = Duration::from_secs(1);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+// DB_DSN picks the backend, sqlite://path/to/club.db for sqlite and postgres otherwise
+pub async fn new_repository(
+    dsn: &str,
+    opts: &PostgresOptions,
+) -> Result<Box<dyn Repository>, Error> {
+    match dsn.strip_prefix("sqlite://") {
+        Some(path) => Ok(Box::new(new_sqlite_repository(path).await?)),
+        None => new_postgres_repository(dsn, opts).await,
+    }
+}
+
+// tls is used when the dsn requires it or certificates are given, the pool type depends on it
+pub async fn new_postgres_repository(
+    dsn: &str,
+    opts: &PostgresOptions,
+) -> Result<Box<dyn Repository>, Error> {
+    let config: tokio_postgres::Config = dsn.parse()?;
+
+    let with_tls = config.get_ssl_mode() == SslMode::Require
+        || opts.ssl_root_cert.is_some()
+        || opts.ssl_cert.is_some();
+
+    if with_tls {
+        Ok(Box::new(connect(config, tls_connector(opts)?, opts).await?))
+    } else {
+        Ok(Box::new(connect(config, NoTls, opts).await?))
+    }
+}
+
+fn tls_connector(opts: &PostgresOptions) -> Result<MakeTlsConnector, Error> {
+    let mut builder = SslConnector::builder(SslMethod::tls())?;
+
+    if let Some(ca) = &opts.ssl_root_cert {
+        builder.set_ca_file(ca)?;
+    }
+
+    if let (Some(cert), Some(key)) = (&opts.ssl_cert, &opts.ssl_key) {
+        builder.set_certificate_chain_file(cert)?;
+        builder.set_private_key_file(key, SslFiletype::PEM)?;
+    }
+
+    Ok(MakeTlsConnector::new(builder.build()))
+}
+
+async fn connect<T: PostgresTls>(
+    config: tokio_postgres::Config,
+    tls: T,
+    opts: &PostgresOptions,
+) -> Result<Postgres<T>, Error> {
+    let manager = PostgresConnectionManager::new(config, tls);
+    let pool = Pool::builder()
+        .max_size(opts.max_size)
+        .min_idle(opts.min_idle)
+        .connection_timeout(opts.connection_timeout)
+        .idle_timeout(opts.idle_timeout)
+        .build_unchecked(manager);
+
+    // the database may still be starting, e.g. under docker-compose
+    let mut delay = INITIAL_RETRY_DELAY;
+    let mut attempt = 0;
+    let mut conn = loop {
+        match pool.get().await {
+            Ok(conn) => break conn,
+            Err(err) if attempt < opts.connect_retries => {
+                attempt += 1;
+                log::warn!(
+                    "database is unavailable ({}), retrying in {:?}",
+                    Error::from(err),
+                    delay
+                );
+                tokio::time::sleep(delay).await;
+                delay = (delay * 2).min(MAX_RETRY_DELAY);
+            }
+            Err(err) => return Err(err.into()),
+        }
+    };
+
+    migrations::run(&mut conn).await?;
+    drop(conn);
 
     Ok(Postgres { pool })
 }
 
+// club keeps a pointer to its soonest active event, there may be several with tracks
+const REFRESH_CLUB_NEXT_EVENT: &str = "UPDATE club SET
+    active_event = (SELECT id FROM events WHERE chat_id = $1 AND active = true ORDER BY event_date LIMIT 1),
+    next_event = (SELECT min(event_date) AT TIME ZONE 'UTC' FROM events WHERE chat_id = $1 AND active = true)
+WHERE chat_id = $1;";
+
+// audit records are written through the same client as the change they describe,
+// so passing a transaction keeps both in one commit
+async fn insert_audit_record<C: GenericClient>(
+    client: &C,
+    rec: &AuditRecord,
+) -> Result<(), tokio_postgres::Error> {
+    client
+        .execute(
+            "INSERT INTO club_audit (chat_id, actor_id, action, payload) VALUES ($1, $2, $3, $4);",
+            &[
+                &rec.chat_id,
+                &rec.actor_id,
+                &rec.action.as_str(),
+                &rec.payload,
+            ],
+        )
+        .await
+        .map(|_| ())
+}
+
+async fn insert_outbox_message<C: GenericClient>(
+    client: &C,
+    msg: &OutboxMessage,
+) -> Result<(), tokio_postgres::Error> {
+    client
+        .execute(
+            "INSERT INTO insights_outbox (id, event_id, chat_id, kind, payload) VALUES ($1, $2, $3, $4, $5);",
+            &[
+                &msg.id,
+                &msg.event_id,
+                &msg.chat_id,
+                &msg.kind.as_str(),
+                &msg.payload,
+            ],
+        )
+        .await
+        .map(|_| ())
+}
+
 #[async_trait]
-impl Repository for Postgres {
+impl<T: PostgresTls> Repository for Postgres<T> {
     async fn register_new_club(&self, req: NewClubRequest) -> Result<(), Error> {
-        let conn = self.pool.get().await.unwrap();
-        let result = conn
-            .execute("INSERT INTO club (chat_id) VALUES ($1);", &[&req.chat_id])
-            .await;
+        let mut conn = self.pool.get().await?;
+        let tx = conn.transaction().await?;
 
-        result.map(|_| ())
+        tx.execute("INSERT INTO club (chat_id) VALUES ($1);", &[&req.chat_id])
+            .await?;
+
+        insert_audit_record(
+            &tx,
+            &AuditRecord {
+                chat_id: req.chat_id,
+                actor_id: req.actor_id,
+                action: AuditAction::ClubRegistered,
+                payload: json!({}),
+            },
+        )
+        .await?;
+
+        Ok(tx.commit().await?)
     }
 
     async fn write_new_event(&self, req: NewEventRequest) -> Result<(), Error> {
-        let mut conn = self.pool.get().await.unwrap();
-        let tx = conn.transaction().await.unwrap();
+        let mut conn = self.pool.get().await?;
+        let tx = conn.transaction().await?;
 
         tx.execute(
-            "INSERT INTO events (id, chat_id, event_date, active) VALUES ($1, $2, $3, true);",
-            &[&req.event_id, &req.chat_id, &req.event_date.and_utc()],
+            "INSERT INTO events (id, chat_id, event_date, active, track, insights, host_id, host_name, location, latitude, longitude)
+            VALUES ($1, $2, $3, true, $4, $5, $6, $7, $8, $9, $10);",
+            &[
+                &req.event_id,
+                &req.chat_id,
+                &req.event_date.and_utc(),
+                &req.track,
+                &req.with_insights,
+                &req.host.as_ref().map(|host| host.user_id),
+                &req.host.as_ref().and_then(|host| host.name.clone()),
+                &req.location.as_ref().map(|location| location.address.clone()),
+                &req.location.as_ref().and_then(Location::latitude),
+                &req.location.as_ref().and_then(Location::longitude),
+            ],
         )
         .await?;
 
-        tx.execute(
-            "UPDATE club SET active_event = $1, next_event = $3 WHERE chat_id = $2;",
-            &[&req.event_id, &req.chat_id, &req.event_date],
+        tx.execute(REFRESH_CLUB_NEXT_EVENT, &[&req.chat_id]).await?;
+
+        insert_audit_record(
+            &tx,
+            &AuditRecord {
+                chat_id: req.chat_id,
+                actor_id: req.actor_id,
+                action: AuditAction::EventCreated,
+                payload: json!({
+                    "event_id": req.event_id,
+                    "event_date": req.event_date.format("%Y.%m.%d %H:%M").to_string(),
+                    "track": req.track,
+                }),
+            },
         )
         .await?;
 
-        tx.commit().await
+        Ok(tx.commit().await?)
     }
 
-    async fn get_latest_event(&self, req: LastEventRequest) -> Result<LastEventResponse, Error> {
-        let conn = self.pool.get().await.unwrap();
+    async fn get_active_events(
+        &self,
+        req: LastEventRequest,
+    ) -> Result<ActiveEventsResponse, Error> {
+        let conn = self.pool.get().await?;
         let result = conn
             .query(
-                "SELECT id, event_date, subject, insights, insights_link FROM events WHERE chat_id = $1 AND active = true;",
+                "SELECT id, event_date, subject, insights, insights_link, track, poll_message_id, work, discussion_kind, discussion_id, started_by, host_id, host_name, location, latitude, longitude, revision, updated_at FROM events WHERE chat_id = $1 AND active = true ORDER BY event_date;",
                 &[&req.chat_id],
             )
-            .await
-            .unwrap();
-
-        if result.is_empty() {
-            return Ok(LastEventResponse {
-                event_id: Uuid::default(),
-                event_date: NaiveDateTime::default(),
-                subject: String::new(),
-                with_insights: false,
-                insights_link: None,
-            });
-        }
-
-        let event_id = result[0].get(0);
-        let event_date: DateTime<Utc> = result[0].get(1);
-        let subject: Option<String> = result[0].get(2);
-        let with_insights: bool = result[0].get(3);
-        let insights_link: Option<String> = result[0].get(4);
-
-        Ok(LastEventResponse {
-            event_id,
-            event_date: event_date.naive_utc(),
-            subject: subject.unwrap_or_default(),
-            with_insights,
-            insights_link,
-        })
+            .await?;
+
+        let mut ans = ActiveEventsResponse { events: vec![] };
+
+        for row in result {
+            let event_date: DateTime<Utc> = row.get(1);
+            let subject: Option<String> = row.get(2);
+            let work: Option<Json<Work>> = row.get(7);
+            let updated_at: DateTime<Utc> = row.get(17);
+
+            ans.events.push(LastEventResponse {
+                event_id: row.get(0),
+                track: row.get(5),
+                event_date: event_date.naive_utc(),
+                subject: subject.unwrap_or_default(),
+                with_insights: row.get(3),
+                insights_link: row.get(4),
+                poll_message_id: row.get(6),
+                work: work.map(|work| work.0),
+                discussion: DiscussionThread::parse(row.get(8), row.get(9)),
+                started_by: row.get(10),
+                host: Member::parse(row.get(11), row.get(12)),
+                location: Location::parse(row.get(13), row.get(14), row.get(15)),
+                revision: row.get(16),
+                updated_at: updated_at.naive_utc(),
+            })
+        }
+
+        Ok(ans)
     }
 
-    async fn write_new_member_suggestion(&self, req: NewMemberSuggestion) -> Result<(), Error> {
-        let conn = self.pool.get().await.unwrap();
-        let result = conn
-            .execute(
-            "INSERT INTO suggestions (event_id, chat_id, user_id, suggestion) VALUES ($1, $2, $3, $4);",
-            &[&req.event_id, &req.chat_id, &(req.user_id as i64), &req.suggestion]
+    async fn write_new_member_suggestion(&self, req: NewMemberSuggestion) -> Result<i64, Error> {
+        let mut conn = self.pool.get().await?;
+        let tx = conn.transaction().await?;
+
+        let row = tx
+            .query_one(
+                "INSERT INTO suggestions (event_id, chat_id, user_id, suggestion, work, identifier_kind, identifier, name)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8) RETURNING id;",
+                &[
+                    &req.event_id,
+                    &req.chat_id,
+                    &req.user_id,
+                    &req.suggestion,
+                    &req.work.as_ref().map(Json),
+                    &req.identifier.as_ref().map(Identifier::kind),
+                    &req.identifier.as_ref().map(Identifier::value),
+                    &req.name,
+                ],
             )
-            .await;
+            .await?;
+
+        insert_audit_record(
+            &tx,
+            &AuditRecord {
+                chat_id: req.chat_id,
+                actor_id: req.user_id,
+                action: AuditAction::SuggestionAdded,
+                payload: json!({ "event_id": req.event_id, "suggestion": req.suggestion }),
+            },
+        )
+        .await?;
 
-        result.map(|_| ())
+        tx.commit().await?;
+
+        Ok(row.get(0))
     }
 
     async fn achieve_event(&self, req: AchieveEventRequest) -> Result<(), Error> {
-        let mut conn = self.pool.get().await.unwrap();
-        let tx = conn.transaction().await.unwrap();
+        let mut conn = self.pool.get().await?;
+        let tx = conn.transaction().await?;
 
         tx.execute(
             "UPDATE events SET active = false, achieved_on = now() WHERE id = $1;",
@@ -121,71 +476,1049 @@ impl Repository for Postgres {
         .await?;
 
         tx.execute(
-            "UPDATE club SET active_event = null, last_event = now(), next_event = null WHERE chat_id = $1;",
+            "UPDATE club SET last_event = now() WHERE chat_id = $1;",
             &[&req.chat_id],
         )
         .await?;
 
-        tx.commit().await
+        tx.execute(REFRESH_CLUB_NEXT_EVENT, &[&req.chat_id]).await?;
+
+        insert_audit_record(
+            &tx,
+            &AuditRecord {
+                chat_id: req.chat_id,
+                actor_id: req.actor_id,
+                action: AuditAction::EventAchieved,
+                payload: json!({ "event_id": req.event_id }),
+            },
+        )
+        .await?;
+
+        if let Some(msg) = &req.outbox {
+            insert_outbox_message(&tx, msg).await?;
+        }
+
+        Ok(tx.commit().await?)
     }
 
     async fn get_all_suggestions_for_event(
         &self,
         req: EventSuggestionsRequest,
     ) -> Result<EventSuggestionsResponse, Error> {
-        let conn = self.pool.get().await.unwrap();
+        let conn = self.pool.get().await?;
         let result = conn
             .query(
-                "SELECT suggestion FROM suggestions WHERE event_id = $1;",
+                "SELECT user_id, suggestion, work, identifier_kind, identifier, name FROM suggestions WHERE event_id = $1;",
                 &[&req.event_id],
             )
-            .await
-            .unwrap();
+            .await?;
 
         let mut ans = EventSuggestionsResponse {
             suggestions: vec![],
         };
 
         for row in result {
-            ans.suggestions.push(row.get(0))
+            let work: Option<Json<Work>> = row.get(2);
+            let identifier_kind: Option<String> = row.get(3);
+            let identifier: Option<String> = row.get(4);
+
+            ans.suggestions.push(EventSuggestion {
+                user_id: row.get(0),
+                name: row.get(5),
+                suggestion: row.get(1),
+                work: work.map(|work| work.0),
+                identifier: identifier_kind
+                    .zip(identifier)
+                    .and_then(|(kind, value)| Identifier::parse(&kind, value)),
+            })
         }
 
         Ok(ans)
     }
 
     async fn write_picked_subject(&self, req: PickedSubjectRequest) -> Result<(), Error> {
-        let conn = self.pool.get().await.unwrap();
+        let mut conn = self.pool.get().await?;
+        let tx = conn.transaction().await?;
+
+        tx.execute(
+            "UPDATE events SET subject = $1, work = $3, suggested_by = $4, picked_at = NOW(), revision = revision + 1, updated_at = NOW() WHERE id = $2;",
+            &[
+                &req.subject,
+                &req.event_id,
+                &req.work.as_ref().map(Json),
+                &Json(&req.suggested_by),
+            ],
+        )
+        .await?;
+
+        insert_audit_record(
+            &tx,
+            &AuditRecord {
+                chat_id: req.chat_id,
+                actor_id: req.actor_id,
+                action: AuditAction::SubjectPicked,
+                payload: json!({
+                    "event_id": req.event_id,
+                    "subject": req.subject,
+                }),
+            },
+        )
+        .await?;
+
+        if let Some(msg) = &req.outbox {
+            insert_outbox_message(&tx, msg).await?;
+        }
+
+        Ok(tx.commit().await?)
+    }
+
+    async fn toggle_with_insights(&self, req: EventToggleWithInsightsRequest) -> Result<(), Error> {
+        let mut conn = self.pool.get().await?;
+        let tx = conn.transaction().await?;
+
+        tx.execute(
+            "UPDATE events SET insights = $1, revision = revision + 1, updated_at = NOW() WHERE id = $2",
+            &[&!req.with_insights, &req.event_id],
+        )
+        .await?;
+
+        insert_audit_record(
+            &tx,
+            &AuditRecord {
+                chat_id: req.chat_id,
+                actor_id: req.actor_id,
+                action: AuditAction::InsightsToggled,
+                payload: json!({ "event_id": req.event_id, "insights": !req.with_insights }),
+            },
+        )
+        .await?;
+
+        Ok(tx.commit().await?)
+    }
+
+    async fn get_audit_log(&self, req: AuditLogRequest) -> Result<AuditLogResponse, Error> {
+        let conn = self.pool.get().await?;
+        let result = conn
+            .query(
+                "SELECT actor_id, action, payload, created_at FROM club_audit WHERE chat_id = $1 ORDER BY created_at DESC, id DESC LIMIT $2;",
+                &[&req.chat_id, &req.limit],
+            )
+            .await?;
+
+        let mut ans = AuditLogResponse { entries: vec![] };
+
+        for row in result {
+            ans.entries.push(AuditLogEntry {
+                actor_id: row.get(0),
+                action: row.get(1),
+                payload: row.get(2),
+                created_at: row.get(3),
+            })
+        }
+
+        Ok(ans)
+    }
+
+    async fn write_pick_poll(&self, req: PickPollRequest) -> Result<(), Error> {
+        let mut conn = self.pool.get().await?;
+        let tx = conn.transaction().await?;
+
+        tx.execute(
+            "UPDATE events SET poll_message_id = $1 WHERE id = $2;",
+            &[&req.message_id, &req.event_id],
+        )
+        .await?;
+
+        insert_audit_record(
+            &tx,
+            &AuditRecord {
+                chat_id: req.chat_id,
+                actor_id: req.actor_id,
+                action: AuditAction::PollStarted,
+                payload: json!({
+                    "event_id": req.event_id,
+                    "message_id": req.message_id,
+                }),
+            },
+        )
+        .await?;
+
+        Ok(tx.commit().await?)
+    }
+
+    async fn get_club_settings(&self, req: ClubSettingsRequest) -> Result<ClubSettings, Error> {
+        let conn = self.pool.get().await?;
+        let result = conn
+            .query(
+                "SELECT default_insights, pick_mode, suggestion_limit, time_zone, language, reminder_offsets, kind, host_only FROM club_settings WHERE chat_id = $1;",
+                &[&req.chat_id],
+            )
+            .await?;
+
+        let settings = match result.first() {
+            Some(row) => {
+                let pick_mode: String = row.get(1);
+                let kind: String = row.get(6);
+
+                ClubSettings {
+                    chat_id: req.chat_id,
+                    default_insights: row.get(0),
+                    pick_mode: PickMode::parse(pick_mode.as_str()).unwrap_or(PickMode::Random),
+                    suggestion_limit: row.get(2),
+                    time_zone: row.get(3),
+                    language: row.get(4),
+                    reminder_offsets: row.get(5),
+                    kind: ClubKind::parse(kind.as_str()).unwrap_or(ClubKind::Books),
+                    host_only: row.get(7),
+                }
+            }
+            None => ClubSettings::new(req.chat_id),
+        };
+
+        Ok(settings)
+    }
+
+    async fn write_club_settings(&self, req: UpdateClubSettingsRequest) -> Result<(), Error> {
+        let mut conn = self.pool.get().await?;
+        let tx = conn.transaction().await?;
+        let settings = &req.settings;
+
+        tx.execute(
+            "INSERT INTO club_settings (chat_id, default_insights, pick_mode, suggestion_limit, time_zone, language, reminder_offsets, kind, host_only)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            ON CONFLICT (chat_id) DO UPDATE SET default_insights = $2, pick_mode = $3, suggestion_limit = $4,
+                time_zone = $5, language = $6, reminder_offsets = $7, kind = $8, host_only = $9, updated_at = now();",
+            &[
+                &settings.chat_id,
+                &settings.default_insights,
+                &settings.pick_mode.as_str(),
+                &settings.suggestion_limit,
+                &settings.time_zone,
+                &settings.language,
+                &settings.reminder_offsets,
+                &settings.kind.as_str(),
+                &settings.host_only,
+            ],
+        )
+        .await?;
+
+        insert_audit_record(
+            &tx,
+            &AuditRecord {
+                chat_id: settings.chat_id,
+                actor_id: req.actor_id,
+                action: AuditAction::SettingsChanged,
+                payload: json!({
+                    "default_insights": settings.default_insights,
+                    "pick_mode": settings.pick_mode.as_str(),
+                    "suggestion_limit": settings.suggestion_limit,
+                    "time_zone": settings.time_zone,
+                    "language": settings.language,
+                    "reminder_offsets": settings.reminder_offsets,
+                    "kind": settings.kind.as_str(),
+                    "host_only": settings.host_only,
+                }),
+            },
+        )
+        .await?;
+
+        Ok(tx.commit().await?)
+    }
+
+    async fn get_due_reminders(
+        &self,
+        req: DueRemindersRequest,
+    ) -> Result<DueRemindersResponse, Error> {
+        let conn = self.pool.get().await?;
+        let result = conn
+            .query(
+                "SELECT e.id, e.chat_id, e.track, e.event_date, e.subject, o.offset_minutes, e.picked_at, e.host_id, e.host_name,
+                    e.location, e.latitude, e.longitude
+                FROM events e
+                JOIN club_settings s ON s.chat_id = e.chat_id
+                CROSS JOIN LATERAL unnest(s.reminder_offsets) AS o(offset_minutes)
+                WHERE e.active = true
+                    AND e.event_date > $1
+                    AND e.event_date - make_interval(mins => o.offset_minutes) <= $1
+                    AND NOT EXISTS (
+                        SELECT 1 FROM event_reminders r WHERE r.event_id = e.id AND r.offset_minutes = o.offset_minutes
+                    )
+                ORDER BY e.event_date, o.offset_minutes;",
+                &[&req.now],
+            )
+            .await?;
+
+        let mut ans = DueRemindersResponse { reminders: vec![] };
+
+        for row in result {
+            let event_date: DateTime<Utc> = row.get(3);
+            let subject: Option<String> = row.get(4);
+            let picked_at: Option<DateTime<Utc>> = row.get(6);
+
+            ans.reminders.push(DueReminder {
+                event_id: row.get(0),
+                chat_id: row.get(1),
+                track: row.get(2),
+                event_date: event_date.naive_utc(),
+                subject: subject.unwrap_or_default(),
+                picked_at: picked_at.map(|date| date.naive_utc()),
+                offset_minutes: row.get(5),
+                host: Member::parse(row.get(7), row.get(8)),
+                location: Location::parse(row.get(9), row.get(10), row.get(11)),
+            })
+        }
+
+        Ok(ans)
+    }
+
+    async fn mark_reminders_sent(&self, req: SentRemindersRequest) -> Result<(), Error> {
+        let conn = self.pool.get().await?;
         let result = conn
             .execute(
-                "UPDATE events SET subject = $1, insights_link = $2 WHERE id = $3;",
-                &[&req.subject, &req.insights_link, &req.event_id],
+                "INSERT INTO event_reminders (event_id, offset_minutes) SELECT $1, unnest($2::int4[]) ON CONFLICT DO NOTHING;",
+                &[&req.event_id, &req.offsets],
             )
             .await;
 
-        result.map(|_| ())
+        Ok(result.map(|_| ())?)
     }
 
-    async fn toggle_with_insights(&self, req: EventToggleWithInsightsRequest) -> Result<(), Error> {
-        let conn = self.pool.get().await.unwrap();
-
-        if req.with_insights {
-            let result = conn
-                .execute(
-                    "UPDATE events SET insights = false WHERE id = $1",
-                    &[&req.event_id],
-                )
-                .await;
-
-            result.map(|_| ())
-        } else {
-            let result = conn
-                .execute(
-                    "UPDATE events SET insights = true WHERE id = $1",
-                    &[&req.event_id],
-                )
-                .await;
-
-            result.map(|_| ())
+    async fn write_event_started(&self, req: EventStartedRequest) -> Result<(), Error> {
+        let mut conn = self.pool.get().await?;
+        let tx = conn.transaction().await?;
+
+        insert_audit_record(
+            &tx,
+            &AuditRecord {
+                chat_id: req.chat_id,
+                actor_id: req.actor_id,
+                action: AuditAction::EventStarted,
+                payload: json!({ "event_id": req.event_id }),
+            },
+        )
+        .await?;
+
+        tx.execute(
+            "UPDATE events SET started_by = COALESCE(started_by, $2) WHERE id = $1;",
+            &[&req.event_id, &req.actor_id],
+        )
+        .await?;
+
+        for number in &req.numbers {
+            tx.execute(
+                "UPDATE questions SET number = $2 WHERE id = $1;",
+                &[&number.question_id, &number.number],
+            )
+            .await?;
+        }
+
+        if let Some(outbox) = &req.outbox {
+            insert_outbox_message(&tx, outbox).await?;
+        }
+
+        Ok(tx.commit().await?)
+    }
+
+    // calls of one event go out in order, a message waits while an earlier one is pending
+    async fn get_due_outbox(&self, req: DueOutboxRequest) -> Result<DueOutboxResponse, Error> {
+        let conn = self.pool.get().await?;
+        let result = conn
+            .query(
+                "SELECT o.id, o.event_id, o.chat_id, o.kind, o.payload, o.attempts
+                FROM insights_outbox o
+                WHERE o.status = 'pending'
+                    AND o.next_attempt_at <= $1
+                    AND ($2::uuid IS NULL OR o.event_id = $2)
+                    AND NOT EXISTS (
+                        SELECT 1 FROM insights_outbox p WHERE p.event_id = o.event_id AND p.status = 'pending' AND p.seq < o.seq
+                    )
+                ORDER BY o.seq
+                LIMIT $3;",
+                &[&req.now, &req.event_id, &req.limit],
+            )
+            .await?;
+
+        let mut ans = DueOutboxResponse { entries: vec![] };
+
+        for row in result {
+            let kind: String = row.get(3);
+            let Some(kind) = OutboxKind::parse(&kind) else {
+                continue;
+            };
+
+            ans.entries.push(OutboxEntry {
+                message: OutboxMessage {
+                    id: row.get(0),
+                    event_id: row.get(1),
+                    chat_id: row.get(2),
+                    kind,
+                    payload: row.get(4),
+                },
+                attempts: row.get(5),
+            })
+        }
+
+        Ok(ans)
+    }
+
+    async fn mark_outbox_delivered(&self, req: OutboxDeliveredRequest) -> Result<(), Error> {
+        let mut conn = self.pool.get().await?;
+        let tx = conn.transaction().await?;
+
+        tx.execute(
+            "UPDATE insights_outbox SET status = 'delivered', attempts = attempts + 1, delivered_at = now(), last_error = NULL
+            WHERE id = $1 AND status = 'pending';",
+            &[&req.id],
+        )
+        .await?;
+
+        if let Some(link) = &req.insights_link {
+            tx.execute(
+                "UPDATE events SET insights_link = $1, revision = revision + 1, updated_at = NOW() WHERE id = $2;",
+                &[link, &req.event_id],
+            )
+            .await?;
+        }
+
+        Ok(tx.commit().await?)
+    }
+
+    async fn mark_outbox_failed(&self, req: OutboxFailedRequest) -> Result<(), Error> {
+        let conn = self.pool.get().await?;
+        let status = match req.next_attempt_at {
+            Some(_) => "pending",
+            None => "failed",
+        };
+
+        let result = conn
+            .execute(
+                "UPDATE insights_outbox SET status = $2, attempts = attempts + 1, last_error = $3, next_attempt_at = coalesce($4, next_attempt_at)
+                WHERE id = $1 AND status = 'pending';",
+                &[&req.id, &status, &req.error, &req.next_attempt_at],
+            )
+            .await;
+
+        Ok(result.map(|_| ())?)
+    }
+
+    async fn get_event_chat(
+        &self,
+        req: EventChatRequest,
+    ) -> Result<Option<EventChatResponse>, Error> {
+        let conn = self.pool.get().await?;
+        let row = conn
+            .query_opt(
+                "SELECT chat_id, subject, started_by, host_id FROM events WHERE id = $1;",
+                &[&req.event_id],
+            )
+            .await?;
+
+        Ok(row.map(|row| {
+            let subject: Option<String> = row.get(1);
+
+            EventChatResponse {
+                chat_id: row.get(0),
+                subject: subject.unwrap_or_default(),
+                started_by: row.get(2),
+                host_id: row.get(3),
+            }
+        }))
+    }
+
+    async fn get_suggestion(
+        &self,
+        req: SuggestionRequest,
+    ) -> Result<Option<SuggestionResponse>, Error> {
+        let conn = self.pool.get().await?;
+        let row = conn
+            .query_opt(
+                "SELECT chat_id, user_id, suggestion FROM suggestions WHERE id = $1;",
+                &[&req.id],
+            )
+            .await?;
+
+        Ok(row.map(|row| SuggestionResponse {
+            chat_id: row.get(0),
+            user_id: row.get(1),
+            suggestion: row.get(2),
+        }))
+    }
+
+    async fn write_suggestion_work(&self, req: SuggestionWorkRequest) -> Result<(), Error> {
+        let mut conn = self.pool.get().await?;
+        let tx = conn.transaction().await?;
+
+        tx.execute(
+            "UPDATE suggestions SET work = $2 WHERE id = $1;",
+            &[&req.id, &Json(&req.work)],
+        )
+        .await?;
+
+        insert_audit_record(
+            &tx,
+            &AuditRecord {
+                chat_id: req.chat_id,
+                actor_id: req.actor_id,
+                action: AuditAction::WorkChosen,
+                payload: json!({
+                    "suggestion_id": req.id,
+                    "key": req.work.key,
+                    "title": req.work.title,
+                }),
+            },
+        )
+        .await?;
+
+        Ok(tx.commit().await?)
+    }
+
+    async fn get_due_checkins(
+        &self,
+        req: DueCheckinsRequest,
+    ) -> Result<DueCheckinsResponse, Error> {
+        let conn = self.pool.get().await?;
+        let result = conn
+            .query(
+                "SELECT id, chat_id, track, subject FROM events
+                WHERE active = true
+                    AND subject <> ''
+                    AND event_date > $1
+                    AND coalesce(checkin_at, picked_at) <= $2
+                ORDER BY event_date;",
+                &[&req.now, &req.due_before],
+            )
+            .await?;
+
+        let mut ans = DueCheckinsResponse { checkins: vec![] };
+
+        for row in result {
+            ans.checkins.push(DueCheckin {
+                event_id: row.get(0),
+                chat_id: row.get(1),
+                track: row.get(2),
+                subject: row.get(3),
+            })
+        }
+
+        Ok(ans)
+    }
+
+    async fn mark_checkin_sent(&self, req: SentCheckinRequest) -> Result<(), Error> {
+        let conn = self.pool.get().await?;
+
+        conn.execute(
+            "UPDATE events SET checkin_at = $2 WHERE id = $1;",
+            &[&req.event_id, &req.sent_at],
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn write_progress(&self, req: NewProgressRequest) -> Result<(), Error> {
+        let mut conn = self.pool.get().await?;
+        let tx = conn.transaction().await?;
+
+        tx.execute(
+            "INSERT INTO progress (event_id, user_id, name, percent, page) VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (event_id, user_id) DO UPDATE SET name = $3, percent = $4, page = $5, updated_at = NOW();",
+            &[&req.event_id, &req.user_id, &req.name, &req.percent, &req.page],
+        )
+        .await?;
+
+        insert_audit_record(
+            &tx,
+            &AuditRecord {
+                chat_id: req.chat_id,
+                actor_id: req.user_id,
+                action: AuditAction::ProgressMarked,
+                payload: json!({
+                    "event_id": req.event_id,
+                    "percent": req.percent,
+                    "page": req.page,
+                }),
+            },
+        )
+        .await?;
+
+        Ok(tx.commit().await?)
+    }
+
+    async fn get_progress(&self, req: ProgressRequest) -> Result<ProgressResponse, Error> {
+        let conn = self.pool.get().await?;
+        let result = conn
+            .query(
+                "SELECT name, percent FROM progress WHERE event_id = $1 ORDER BY percent DESC, updated_at;",
+                &[&req.event_id],
+            )
+            .await?;
+
+        let mut ans = ProgressResponse { members: vec![] };
+
+        for row in result {
+            ans.members.push(MemberProgress {
+                name: row.get(0),
+                percent: row.get(1),
+            })
+        }
+
+        Ok(ans)
+    }
+
+    async fn write_rating(&self, req: NewRatingRequest) -> Result<(), Error> {
+        let mut conn = self.pool.get().await?;
+        let tx = conn.transaction().await?;
+
+        tx.execute(
+            "INSERT INTO ratings (event_id, user_id, name, stars) VALUES ($1, $2, $3, $4)
+            ON CONFLICT (event_id, user_id) DO UPDATE SET name = $3, stars = $4;",
+            &[&req.event_id, &req.user_id, &req.name, &req.stars],
+        )
+        .await?;
+
+        insert_audit_record(
+            &tx,
+            &AuditRecord {
+                chat_id: req.chat_id,
+                actor_id: req.user_id,
+                action: AuditAction::RatingGiven,
+                payload: json!({
+                    "event_id": req.event_id,
+                    "subject": req.subject,
+                    "stars": req.stars,
+                }),
+            },
+        )
+        .await?;
+
+        Ok(tx.commit().await?)
+    }
+
+    async fn write_review(&self, req: NewReviewRequest) -> Result<(), Error> {
+        let mut conn = self.pool.get().await?;
+        let tx = conn.transaction().await?;
+
+        tx.execute(
+            "UPDATE ratings SET review = $3 WHERE event_id = $1 AND user_id = $2;",
+            &[&req.event_id, &req.user_id, &req.review],
+        )
+        .await?;
+
+        insert_audit_record(
+            &tx,
+            &AuditRecord {
+                chat_id: req.chat_id,
+                actor_id: req.user_id,
+                action: AuditAction::ReviewAdded,
+                payload: json!({
+                    "event_id": req.event_id,
+                    "subject": req.subject,
+                }),
+            },
+        )
+        .await?;
+
+        Ok(tx.commit().await?)
+    }
+
+    async fn get_achieved_events(
+        &self,
+        req: AchievedEventsRequest,
+    ) -> Result<AchievedEventsResponse, Error> {
+        let conn = self.pool.get().await?;
+        let result = conn
+            .query(
+                "SELECT e.id, e.event_date, e.subject, e.suggested_by, r.user_id, r.name, r.stars, r.review
+                FROM (
+                    SELECT * FROM events
+                    WHERE chat_id = $1 AND active = false AND achieved_on IS NOT NULL AND subject <> ''
+                    ORDER BY achieved_on DESC
+                    LIMIT $2
+                ) e
+                LEFT JOIN ratings r ON r.event_id = e.id
+                ORDER BY e.achieved_on DESC, e.id, r.created_at;",
+                &[&req.chat_id, &req.limit],
+            )
+            .await?;
+
+        let mut ans = AchievedEventsResponse { events: vec![] };
+
+        for row in result {
+            let event_id: Uuid = row.get(0);
+
+            if ans.events.last().map(|event| event.event_id) != Some(event_id) {
+                let event_date: DateTime<Utc> = row.get(1);
+                let suggested_by: Json<Vec<Member>> = row.get(3);
+
+                ans.events.push(AchievedEvent {
+                    event_id,
+                    event_date: event_date.naive_utc(),
+                    subject: row.get(2),
+                    suggested_by: suggested_by.0,
+                    ratings: vec![],
+                })
+            }
+
+            let user_id: Option<i64> = row.get(4);
+            if let (Some(user_id), Some(event)) = (user_id, ans.events.last_mut()) {
+                event.ratings.push(Rating {
+                    user_id,
+                    name: row.get(5),
+                    stars: row.get(6),
+                    review: row.get(7),
+                })
+            }
         }
+
+        Ok(ans)
+    }
+
+    async fn write_discussion_thread(&self, req: DiscussionThreadRequest) -> Result<(), Error> {
+        let mut conn = self.pool.get().await?;
+        let tx = conn.transaction().await?;
+
+        tx.execute(
+            "UPDATE events SET discussion_kind = $2, discussion_id = $3 WHERE id = $1;",
+            &[&req.event_id, &req.thread.kind.as_str(), &req.thread.id],
+        )
+        .await?;
+
+        insert_audit_record(
+            &tx,
+            &AuditRecord {
+                chat_id: req.chat_id,
+                actor_id: req.actor_id,
+                action: AuditAction::DiscussionOpened,
+                payload: json!({
+                    "event_id": req.event_id,
+                    "kind": req.thread.kind.as_str(),
+                    "thread_id": req.thread.id,
+                }),
+            },
+        )
+        .await?;
+
+        Ok(tx.commit().await?)
+    }
+
+    async fn write_question(&self, req: NewQuestionRequest) -> Result<(), Error> {
+        let mut conn = self.pool.get().await?;
+        let tx = conn.transaction().await?;
+
+        tx.execute(
+            "INSERT INTO questions (id, event_id, user_id, name, question) VALUES ($1, $2, $3, $4, $5);",
+            &[&req.id, &req.event_id, &req.user_id, &req.name, &req.question],
+        )
+        .await?;
+
+        // the log is open to the chat, so the text stays out of it and anonymous questions stay anonymous
+        insert_audit_record(
+            &tx,
+            &AuditRecord {
+                chat_id: req.chat_id,
+                actor_id: req.user_id,
+                action: AuditAction::QuestionAsked,
+                payload: json!({
+                    "event_id": req.event_id,
+                    "question_id": req.id,
+                }),
+            },
+        )
+        .await?;
+
+        Ok(tx.commit().await?)
+    }
+
+    async fn get_questions(&self, req: QuestionsRequest) -> Result<QuestionsResponse, Error> {
+        let conn = self.pool.get().await?;
+        let result = conn
+            .query(
+                "SELECT id, name, question, number, covered FROM questions
+                WHERE event_id = $1
+                ORDER BY number NULLS LAST, created_at;",
+                &[&req.event_id],
+            )
+            .await?;
+
+        Ok(QuestionsResponse {
+            questions: result
+                .iter()
+                .map(|row| Question {
+                    id: row.get(0),
+                    name: row.get(1),
+                    question: row.get(2),
+                    number: row.get(3),
+                    covered: row.get(4),
+                })
+                .collect(),
+        })
+    }
+
+    async fn write_question_covered(&self, req: QuestionCoveredRequest) -> Result<(), Error> {
+        let mut conn = self.pool.get().await?;
+        let tx = conn.transaction().await?;
+
+        tx.execute(
+            "UPDATE questions SET covered = $2 WHERE id = $1;",
+            &[&req.question_id, &req.covered],
+        )
+        .await?;
+
+        insert_audit_record(
+            &tx,
+            &AuditRecord {
+                chat_id: req.chat_id,
+                actor_id: req.actor_id,
+                action: AuditAction::QuestionCovered,
+                payload: json!({
+                    "event_id": req.event_id,
+                    "question_id": req.question_id,
+                    "number": req.number,
+                    "covered": req.covered,
+                }),
+            },
+        )
+        .await?;
+
+        Ok(tx.commit().await?)
+    }
+
+    async fn get_hosts(&self, req: HostsRequest) -> Result<HostsResponse, Error> {
+        let conn = self.pool.get().await?;
+        let result = conn
+            .query(
+                "SELECT user_id, name, position FROM hosts WHERE chat_id = $1 ORDER BY position, added_at;",
+                &[&req.chat_id],
+            )
+            .await?;
+        let last_host = conn
+            .query_opt(
+                "SELECT e.host_id FROM events e
+                JOIN hosts h ON h.chat_id = e.chat_id AND h.user_id = e.host_id
+                WHERE e.chat_id = $1
+                ORDER BY e.created_at DESC
+                LIMIT 1;",
+                &[&req.chat_id],
+            )
+            .await?;
+
+        Ok(HostsResponse {
+            hosts: result
+                .iter()
+                .map(|row| Host {
+                    user_id: row.get(0),
+                    name: row.get(1),
+                    position: row.get(2),
+                })
+                .collect(),
+            last_host: last_host.map(|row| row.get(0)),
+        })
+    }
+
+    async fn write_host(&self, req: NewHostRequest) -> Result<(), Error> {
+        let mut conn = self.pool.get().await?;
+        let tx = conn.transaction().await?;
+
+        tx.execute(
+            "INSERT INTO hosts (chat_id, user_id, name, position)
+            SELECT $1, $2, $3, COALESCE(max(position), 0) + 1 FROM hosts WHERE chat_id = $1;",
+            &[&req.chat_id, &req.user_id, &req.name],
+        )
+        .await?;
+
+        insert_audit_record(
+            &tx,
+            &AuditRecord {
+                chat_id: req.chat_id,
+                actor_id: req.actor_id,
+                action: AuditAction::HostAdded,
+                payload: json!({ "user_id": req.user_id, "name": req.name }),
+            },
+        )
+        .await?;
+
+        Ok(tx.commit().await?)
+    }
+
+    async fn delete_host(&self, req: RemoveHostRequest) -> Result<(), Error> {
+        let mut conn = self.pool.get().await?;
+        let tx = conn.transaction().await?;
+
+        let name: Option<String> = tx
+            .query_opt(
+                "DELETE FROM hosts WHERE chat_id = $1 AND user_id = $2 RETURNING name;",
+                &[&req.chat_id, &req.user_id],
+            )
+            .await?
+            .map(|row| row.get(0));
+
+        insert_audit_record(
+            &tx,
+            &AuditRecord {
+                chat_id: req.chat_id,
+                actor_id: req.actor_id,
+                action: AuditAction::HostRemoved,
+                payload: json!({ "user_id": req.user_id, "name": name }),
+            },
+        )
+        .await?;
+
+        Ok(tx.commit().await?)
+    }
+
+    async fn write_event_host(&self, req: SwapHostRequest) -> Result<(), Error> {
+        let mut conn = self.pool.get().await?;
+        let tx = conn.transaction().await?;
+
+        tx.execute(
+            "UPDATE events SET host_id = $2, host_name = $3, revision = revision + 1, updated_at = NOW() WHERE id = $1;",
+            &[&req.event_id, &req.host.user_id, &req.host.name],
+        )
+        .await?;
+
+        for host in &req.positions {
+            tx.execute(
+                "UPDATE hosts SET position = $3 WHERE chat_id = $1 AND user_id = $2;",
+                &[&req.chat_id, &host.user_id, &host.position],
+            )
+            .await?;
+        }
+
+        insert_audit_record(
+            &tx,
+            &AuditRecord {
+                chat_id: req.chat_id,
+                actor_id: req.actor_id,
+                action: AuditAction::HostSwapped,
+                payload: json!({
+                    "event_id": req.event_id,
+                    "user_id": req.host.user_id,
+                    "name": req.host.name,
+                }),
+            },
+        )
+        .await?;
+
+        Ok(tx.commit().await?)
+    }
+
+    async fn write_event_location(&self, req: EventLocationRequest) -> Result<(), Error> {
+        let mut conn = self.pool.get().await?;
+        let tx = conn.transaction().await?;
+
+        tx.execute(
+            "UPDATE events SET location = $2, latitude = $3, longitude = $4, revision = revision + 1, updated_at = NOW() WHERE id = $1;",
+            &[
+                &req.event_id,
+                &req.location.address,
+                &req.location.latitude(),
+                &req.location.longitude(),
+            ],
+        )
+        .await?;
+
+        insert_audit_record(
+            &tx,
+            &AuditRecord {
+                chat_id: req.chat_id,
+                actor_id: req.actor_id,
+                action: AuditAction::LocationChanged,
+                payload: json!({
+                    "event_id": req.event_id,
+                    "location": req.location.address,
+                }),
+            },
+        )
+        .await?;
+
+        Ok(tx.commit().await?)
+    }
+
+    async fn get_calendar_token(
+        &self,
+        req: CalendarTokenRequest,
+    ) -> Result<Option<CalendarTokenResponse>, Error> {
+        let conn = self.pool.get().await?;
+        let row = conn
+            .query_opt(
+                "SELECT calendar_token FROM club WHERE chat_id = $1;",
+                &[&req.chat_id],
+            )
+            .await?;
+
+        Ok(row.map(|row| CalendarTokenResponse { token: row.get(0) }))
+    }
+
+    async fn write_calendar_token(&self, req: NewCalendarTokenRequest) -> Result<(), Error> {
+        let mut conn = self.pool.get().await?;
+        let tx = conn.transaction().await?;
+
+        tx.execute(
+            "UPDATE club SET calendar_token = $2 WHERE chat_id = $1;",
+            &[&req.chat_id, &req.token],
+        )
+        .await?;
+
+        // the token opens the feed, so the open log only says it changed
+        insert_audit_record(
+            &tx,
+            &AuditRecord {
+                chat_id: req.chat_id,
+                actor_id: req.actor_id,
+                action: AuditAction::CalendarReset,
+                payload: json!({ "reset": req.reset }),
+            },
+        )
+        .await?;
+
+        Ok(tx.commit().await?)
+    }
+
+    async fn get_calendar(&self, req: CalendarRequest) -> Result<Option<CalendarResponse>, Error> {
+        let conn = self.pool.get().await?;
+        let club = conn
+            .query_opt(
+                "SELECT chat_id FROM club WHERE calendar_token = $1;",
+                &[&req.token],
+            )
+            .await?;
+
+        let Some(club) = club else {
+            return Ok(None);
+        };
+
+        let chat_id: i64 = club.get(0);
+        let result = conn
+            .query(
+                "SELECT id, track, event_date, subject, CASE WHEN insights THEN insights_link END, host_id, host_name, location, latitude, longitude, revision, updated_at
+                FROM events WHERE chat_id = $1 ORDER BY event_date DESC LIMIT $2;",
+                &[&chat_id, &req.limit],
+            )
+            .await?;
+
+        let mut ans = CalendarResponse {
+            chat_id,
+            events: vec![],
+        };
+
+        for row in result {
+            let event_date: DateTime<Utc> = row.get(2);
+            let subject: Option<String> = row.get(3);
+            let updated_at: DateTime<Utc> = row.get(11);
+
+            ans.events.push(CalendarEvent {
+                event_id: row.get(0),
+                track: row.get(1),
+                event_date: event_date.naive_utc(),
+                subject: subject.unwrap_or_default(),
+                insights_link: row.get(4),
+                host: Member::parse(row.get(5), row.get(6)),
+                location: Location::parse(row.get(7), row.get(8), row.get(9)),
+                revision: row.get(10),
+                updated_at: updated_at.naive_utc(),
+            })
+        }
+
+        Ok(Some(ans))
     }
 }
@@ -1,329 +1,3945 @@
+use crate::catalog;
+use crate::catalog::CatalogProvider;
+use crate::config::Config;
 use crate::err::CustomError as Err;
+use crate::i18n;
+use crate::i18n::{tr, LANGUAGES};
+use crate::ical;
+use crate::identifier;
+use crate::identifier::Identifier;
 use crate::insights;
-use crate::insights::InsightsClient;
+use crate::insights::{InsightsClient, InsightsError};
+use crate::markdown;
 use crate::models::*;
-use crate::repository::{new_postgres_repository, Postgres, Repository};
+use crate::repository;
+use crate::repository::{new_repository, Repository};
 use chrono::prelude::*;
+use chrono_tz::Tz;
 use rand::seq::SliceRandom;
-use std::env;
+use serde_json::json;
+use std::collections::HashMap;
 use std::error::Error;
+use uuid::Uuid;
+
+pub const DEFAULT_TRACK: &str = "main";
+pub const SUGGESTION_LIMITS: [i32; 5] = [0, 1, 2, 3, 5];
+pub const REMINDER_OFFSETS: [i32; 4] = [10080, 1440, 180, 60];
+// percentages offered as buttons on the weekly check-in
+pub const PROGRESS_STEPS: [i32; 5] = [0, 25, 50, 75, 100];
+
+// members are asked how far along they are once a week between the pick and the event
+const CHECKIN_INTERVAL_DAYS: i64 = 7;
+const PROGRESS_BAR_LENGTH: i32 = 10;
+
+pub const MAX_STARS: i32 = 5;
+const MAX_REVIEW_LENGTH: usize = 280;
+// best rated picks shown in /stats
+const STATS_TOP_PICKS: usize = 3;
+
+// telegram allows topic names of up to 128 chars
+const MAX_TOPIC_NAME_LENGTH: usize = 128;
+
+// the numbered list of questions has to fit one message
+const MAX_QUESTIONS: usize = 15;
+const MAX_QUESTION_LENGTH: usize = 200;
+// a first word that keeps the question anonymous
+const ANONYMOUS_MARKS: [&str; 4] = ["anon", "anonymous", "анон", "анонимно"];
+
+// events in a club's calendar feed, the latest ones with the upcoming first
+const CALENDAR_EVENTS: i64 = 100;
+const CALENDAR_TOKEN_LENGTH: usize = 32;
+
+// telegram allows up to 10 poll options of 100 chars each
+const MAX_POLL_OPTIONS: usize = 10;
+const MAX_POLL_OPTION_LENGTH: usize = 100;
+
+// insights calls delivered per worker run, and how a failing one is retried
+const OUTBOX_BATCH: i64 = 50;
+const OUTBOX_MAX_ATTEMPTS: i32 = 20;
+const OUTBOX_RETRY_DELAY_SECONDS: i64 = 30;
+const OUTBOX_MAX_RETRY_DELAY_SECONDS: i64 = 3600;
+
+pub enum Pick {
+    Picked(String),
+    StartPoll {
+        event_id: Uuid,
+        question: String,
+        options: Vec<String>,
+        // how many suggestions the filter left out, shown with the poll
+        skipped: usize,
+    },
+    ClosePoll {
+        message_id: i32,
+    },
+}
+
+pub enum SettingChange {
+    ToggleDefaultInsights,
+    NextPickMode,
+    NextSuggestionLimit,
+    SuggestionLimit(i32),
+    NextLanguage,
+    TimeZone(String),
+    ToggleReminder(i32),
+    NextKind,
+    Kind(String),
+    ToggleHostOnly,
+}
+
+// a member of the host rotation, by telegram id or by their number in /hosts list
+pub enum HostRef {
+    User(i64),
+    Number(usize),
+}
+
+pub enum Suggestion {
+    // with the suggestion as it's shown back to the club
+    Added(String),
+    // several catalog works match, the suggester picks one of them
+    Choose {
+        suggestion_id: i64,
+        text: String,
+        works: Vec<Work>,
+        kind: ClubKind,
+    },
+}
+
+pub enum ProgressMark {
+    Percent(i32),
+    // counted against the page count of the picked work
+    Page(i32),
+}
+
+impl ProgressMark {
+    // "45%", "p.120", "p. 120" or "стр. 120"
+    pub fn parse(text: &str) -> Option<ProgressMark> {
+        let text = text.split_whitespace().collect::<String>().to_lowercase();
+
+        if let Some(percent) = text.strip_suffix('%') {
+            return match percent.parse() {
+                Ok(percent) if (0..=100).contains(&percent) => Some(Self::Percent(percent)),
+                _ => None,
+            };
+        }
+
+        let page = ["page", "p.", "p", "стр.", "стр"]
+            .iter()
+            .find_map(|prefix| text.strip_prefix(prefix))?;
+
+        match page.parse() {
+            Ok(page) if page > 0 => Some(Self::Page(page)),
+            _ => None,
+        }
+    }
+}
+
+// how far into the subject a spoiler goes
+pub enum SpoilerMark {
+    Chapter(String),
+    Progress(ProgressMark),
+}
+
+impl SpoilerMark {
+    // a progress mark, or a chapter like "12", "ch.12" or "IV"
+    pub fn parse(text: &str) -> Option<SpoilerMark> {
+        if let Some(mark) = ProgressMark::parse(text) {
+            return Some(Self::Progress(mark));
+        }
+
+        let lower = text.to_lowercase();
+        let prefix = ["chapter", "ch.", "ch", "гл.", "гл"]
+            .iter()
+            .find(|prefix| lower.starts_with(*prefix))
+            .map_or(0, |prefix| prefix.len());
+        let chapter = text.get(prefix..).unwrap_or_default().trim_start();
+
+        let numbered = chapter.starts_with(|c: char| c.is_ascii_digit())
+            && chapter.chars().all(|c| c.is_ascii_digit() || c == '.');
+        // only capitals, so words like "did" or "mix" aren't taken for numerals
+        let roman = !chapter.is_empty() && chapter.chars().all(|c| "IVXLCDM".contains(c));
+
+        (numbered || roman).then(|| Self::Chapter(chapter.to_string()))
+    }
+
+    // the mark and the spoiler after it, the mark is one word or two like "ch. 12" or "стр. 120"
+    pub fn split(text: &str) -> Option<(SpoilerMark, &str)> {
+        let words: Vec<&str> = text.split_whitespace().collect();
+
+        (1..=2)
+            .filter(|count| words.len() > *count)
+            .find_map(|count| {
+                let mark = Self::parse(&words[..count].join(" "))?;
+                let rest = text.trim_start();
+                let rest = words[..count]
+                    .iter()
+                    .fold(rest, |rest, word| rest[word.len()..].trim_start());
+
+                Some((mark, rest.trim_end()))
+            })
+    }
+}
+
+// the event spoilers go to and the thread opened for them, if any
+pub struct Discussion {
+    pub event_id: Uuid,
+    pub topic: String,
+    // posted to start the thread
+    pub announcement: String,
+    pub thread: Option<DiscussionThread>,
+}
+
+pub struct Spoiler {
+    pub discussion: Discussion,
+    // MarkdownV2 with the spoiler text hidden
+    pub text: String,
+}
+
+pub struct Asked {
+    pub text: String,
+    // the command message gives the author away, so it's better removed
+    pub anonymous: bool,
+}
+
+pub struct Started {
+    // MarkdownV2, none when the questions are all there is to post
+    pub text: Option<String>,
+    pub questions: Option<QuestionList>,
+}
+
+// MarkdownV2 list of the questions in their shuffled order, covered ones struck through
+pub struct QuestionList {
+    pub event_id: Uuid,
+    pub text: String,
+    // every number and whether it's covered, for the buttons
+    pub numbers: Vec<(i32, bool)>,
+}
+
+// reply about an event's date or place, with a map pin when its coordinates are known
+pub struct Scheduled {
+    pub text: String,
+    pub venue: Option<Venue>,
+}
+
+pub struct Venue {
+    pub coordinates: Coordinates,
+    pub title: String,
+    pub address: String,
+}
+
+pub struct CalendarFile {
+    pub name: String,
+    pub content: String,
+}
+
+// message the bot sends to a chat on its own
+pub struct Notice {
+    pub chat_id: i64,
+    pub text: String,
+}
+
+pub struct Achieved {
+    pub date: String,
+    // none when the event ended without a pick, there is nothing to rate then
+    pub rating: Option<RatingRound>,
+}
+
+// question sent with star buttons for the subject of an achieved event
+pub struct RatingRound {
+    pub event_id: Uuid,
+    pub text: String,
+}
+
+// weekly question about progress, answered with the buttons of its event
+pub struct CheckIn {
+    pub chat_id: i64,
+    pub event_id: Uuid,
+    pub text: String,
+}
+
+// the pick and what insights is told about the club along with it
+struct PickedSubject<'a> {
+    subject: String,
+    work: Option<Work>,
+    suggested_by: Vec<Member>,
+    kind: ClubKind,
+    club_name: &'a str,
+    participants: Vec<i64>,
+}
+
+enum Delivery {
+    // with the link insights returned, if any, and when it stops working
+    Delivered {
+        link: Option<String>,
+        expires_at: Option<DateTime<Utc>>,
+    },
+    // insights refused the call and said why
+    Rejected(String),
+    // left in the outbox for the worker
+    Deferred,
+}
 
 pub struct Service {
-    repository: Postgres,
+    repository: Box<dyn Repository>,
     insights: InsightsClient,
+    // none keeps suggestions as typed
+    catalog: Option<Box<dyn CatalogProvider>>,
+    // where calendar feeds are served, none when they are off
+    calendar_url: Option<String>,
 }
 
 impl Service {
-    pub async fn register_new_club(&self, chat_id: i64) -> Result<(), Box<dyn Error>> {
+    pub async fn new(config: &Config) -> Result<Service, Box<dyn Error>> {
+        let repository = new_repository(&config.database.dsn, &config.database.postgres).await?;
+        let insights = insights::new(&config.insights);
+        let catalog = catalog::new_catalog(&[&config.catalog, &config.film_catalog])?;
+
+        let calendar_url = config
+            .calendar
+            .as_ref()
+            .map(|calendar| calendar.url.trim_end_matches('/').to_string());
+
+        Ok(Service {
+            repository,
+            insights,
+            catalog,
+            calendar_url,
+        })
+    }
+
+    // insights is unreachable and calendar feeds are off, the rest runs for real
+    #[cfg(test)]
+    pub fn with_repository(
+        repository: Box<dyn Repository>,
+        catalog: Option<Box<dyn CatalogProvider>>,
+    ) -> Service {
+        Service {
+            repository,
+            insights: insights::new(&insights::InsightsOptions {
+                address: "http://127.0.0.1:9".to_string(),
+                timeout: std::time::Duration::from_secs(1),
+                retries: 0,
+                auth: insights::InsightsAuth::None,
+            }),
+            catalog,
+            calendar_url: None,
+        }
+    }
+
+    pub async fn register_new_club(
+        &self,
+        chat_id: i64,
+        user_id: i64,
+    ) -> Result<(), Box<dyn Error>> {
         self.repository
-            .register_new_club(NewClubRequest { chat_id })
+            .register_new_club(NewClubRequest {
+                chat_id,
+                actor_id: user_id,
+            })
             .await
             .map_err(|err| Box::new(err) as Box<dyn Error>)
     }
 
-    pub async fn new_club_event(&self, chat_id: i64, date: &str) -> Result<String, Box<dyn Error>> {
-        let dt = Utc.datetime_from_str(date, "%Y.%m.%d %H:%M");
-        match dt {
-            Ok(_) => {}
-            Err(_) => return Err(Box::new(Err::WrongDateFormat)),
+    pub async fn new_club_event(
+        &self,
+        user_id: i64,
+        args: &str,
+        settings: &ClubSettings,
+        lang: &str,
+    ) -> Result<Scheduled, Box<dyn Error>> {
+        let chat_id = settings.chat_id;
+        let words: Vec<&str> = args.split_whitespace().collect();
+        // the track is optional, the date is the first two words without it
+        let start = match words.first() {
+            Some(word) if NaiveDate::parse_from_str(word, "%Y.%m.%d").is_err() => 1,
+            _ => 0,
+        };
+
+        if words.len() < start + 2 {
+            return Err(Box::new(Err::WrongDateFormat));
         }
 
-        if dt.unwrap().le(&Utc::now()) {
-            return Err(Box::new(Err::EventInPast));
+        let track = match start {
+            0 => DEFAULT_TRACK.to_string(),
+            _ => words[0].to_lowercase(),
+        };
+        let date = words[start..start + 2].join(" ");
+        let location = parse_location(skip_words(args, start + 2));
+
+        if !is_valid_track_name(&track) {
+            return Err(Box::new(Err::WrongTrackName));
         }
 
-        let event_date = NaiveDateTime::from_timestamp_opt(dt.unwrap().timestamp(), 0).unwrap();
+        let tz = settings.tz();
 
-        let latest_event = self
-            .repository
-            .get_latest_event(LastEventRequest { chat_id })
-            .await
-            .unwrap();
+        // dates are written in club's time zone and stored in utc
+        let dt = match NaiveDateTime::parse_from_str(date.as_str(), "%Y.%m.%d %H:%M") {
+            Ok(local) => tz.from_local_datetime(&local).single(),
+            Err(_) => None,
+        };
 
-        if !latest_event.event_id.is_nil() {
-            return Err(Box::new(Err::ActiveEventFound(
-                latest_event.event_date.to_string(),
-            )));
+        let dt = match dt {
+            Some(dt) => dt.with_timezone(&Utc),
+            None => return Err(Box::new(Err::WrongDateFormat)),
+        };
+
+        if dt.le(&Utc::now()) {
+            return Err(Box::new(Err::EventInPast));
         }
 
+        let event_date = dt.naive_utc();
         let event_id = uuid::Uuid::new_v4();
+        let host = next_host(self.repository.get_hosts(HostsRequest { chat_id }).await?);
 
+        // one active event per track is enforced by the schema, a conflict means the track is busy
         let resp = self
             .repository
             .write_new_event(NewEventRequest {
                 chat_id,
+                actor_id: user_id,
                 event_id,
                 event_date,
+                track: track.clone(),
+                with_insights: settings.default_insights,
+                host: host.clone(),
+                location: location.clone(),
             })
             .await;
 
-        resp.unwrap();
-        Ok(beautify_date(event_date))
+        if let Err(repository::Error::Conflict) = resp {
+            let event = self.active_event(chat_id, &track).await?;
+
+            return Err(Box::new(Err::ActiveEventFound(beautify_date(
+                local_date(event.event_date, tz),
+                lang,
+            ))));
+        }
+
+        resp?;
+        let noun = event_noun(&track, lang);
+        let date = beautify_date(local_date(event_date, tz), lang);
+        let text = tr!(
+            lang,
+            "event-created",
+            noun = noun.as_str(),
+            date = date.as_str()
+        );
+
+        let text = match host {
+            Some(host) => tr!(
+                lang,
+                "event-created-host",
+                created = text,
+                host = member_name(&host, lang)
+            ),
+            None => text,
+        };
+
+        let text = match &location {
+            Some(location) => tr!(
+                lang,
+                "event-created-location",
+                created = text,
+                location = location.address.as_str()
+            ),
+            None => text,
+        };
+
+        Ok(Scheduled {
+            text,
+            venue: location.and_then(|location| venue(location, &noun, &date, lang)),
+        })
     }
 
-    pub async fn new_member_suggestion(
+    // the active event as an .ics file to add to a calendar
+    pub async fn event_calendar(
+        &self,
+        track: &str,
+        settings: &ClubSettings,
+        lang: &str,
+    ) -> Result<CalendarFile, Box<dyn Error>> {
+        let chat_id = settings.chat_id;
+        let event = self.active_event(chat_id, track).await?;
+        let tz = settings.tz();
+        let name = format!(
+            "{}-{}.ics",
+            event.track,
+            local_date(event.event_date, tz).format("%Y-%m-%d")
+        );
+
+        let entry = calendar_entry(
+            CalendarEvent {
+                event_id: event.event_id,
+                track: event.track,
+                event_date: event.event_date,
+                subject: event.subject,
+                insights_link: event.insights_link.filter(|_| event.with_insights),
+                host: event.host,
+                location: event.location,
+                revision: event.revision,
+                updated_at: event.updated_at,
+            },
+            lang,
+        );
+
+        Ok(CalendarFile {
+            name,
+            content: ical::calendar(&tr!(lang, "calendar-name"), &[entry], Utc::now()),
+        })
+    }
+
+    // the club's feed link, made on the first call, a reset makes the old one stop working
+    pub async fn calendar_link(
         &self,
         chat_id: i64,
-        user_id: u32,
-        suggestion: &str,
-    ) -> Result<(), Box<dyn Error>> {
-        let latest_event = self
+        user_id: i64,
+        reset: bool,
+        lang: &str,
+    ) -> Result<String, Box<dyn Error>> {
+        let Some(url) = &self.calendar_url else {
+            return Err(Box::new(Err::CalendarDisabled));
+        };
+
+        let Some(current) = self
             .repository
-            .get_latest_event(LastEventRequest { chat_id })
-            .await
-            .unwrap();
+            .get_calendar_token(CalendarTokenRequest { chat_id })
+            .await?
+        else {
+            return Err(Box::new(Err::ClubNotFound));
+        };
 
-        if latest_event.event_id.is_nil() {
-            return Err(Box::new(Err::NoActiveEventFound));
-        }
+        let token = match current.token {
+            Some(token) if !reset => token,
+            _ => {
+                let token = nanoid::nanoid!(CALENDAR_TOKEN_LENGTH);
 
-        if !latest_event.subject.is_empty() {
-            return Err(Box::new(Err::AlreadyPickedSubject(latest_event.subject)));
-        }
+                self.repository
+                    .write_calendar_token(NewCalendarTokenRequest {
+                        chat_id,
+                        actor_id: user_id,
+                        token: token.clone(),
+                        reset: current.token.is_some(),
+                    })
+                    .await?;
+
+                token
+            }
+        };
+
+        let link = format!("{}/calendar/{}.ics", url, token);
+
+        Ok(match reset {
+            true => tr!(lang, "calendar-reset", link = link),
+            false => tr!(lang, "calendar-link", link = link),
+        })
+    }
+
+    // none for a token no club has, the feed is rebuilt from the events on every request
+    pub async fn calendar_feed(&self, token: &str) -> Result<Option<String>, Box<dyn Error>> {
+        let Some(calendar) = self
+            .repository
+            .get_calendar(CalendarRequest {
+                token: token.to_string(),
+                limit: CALENDAR_EVENTS,
+            })
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        let settings = self.settings(calendar.chat_id).await?;
+        let lang = i18n::resolve_language(settings.language.as_deref(), None);
+        let entries: Vec<ical::Entry> = calendar
+            .events
+            .into_iter()
+            .rev()
+            .map(|event| calendar_entry(event, lang))
+            .collect();
+
+        Ok(Some(ical::calendar(
+            &tr!(lang, "calendar-name"),
+            &entries,
+            Utc::now(),
+        )))
+    }
+
+    // "/where" tells where the event is, "/where Cafe Central" or a reply to a shared
+    // location moves it there
+    pub async fn event_location(
+        &self,
+        user_id: i64,
+        args: &str,
+        shared: Option<Location>,
+        settings: &ClubSettings,
+        lang: &str,
+    ) -> Result<Scheduled, Box<dyn Error>> {
+        let chat_id = settings.chat_id;
+        let events = self
+            .repository
+            .get_active_events(LastEventRequest { chat_id })
+            .await?
+            .events;
+
+        let (track, text) = match events
+            .iter()
+            .any(|event| event.track == args.trim().to_lowercase())
+        {
+            true => (args.trim().to_lowercase(), String::new()),
+            false => split_track(&events, args),
+        };
+        let event = resolve_event(events, &track)?;
+        let tz = settings.tz();
+        let noun = event_noun(&event.track, lang);
+        let date = beautify_date(local_date(event.event_date, tz), lang);
+
+        let Some(location) = shared.or_else(|| parse_location(&text)) else {
+            let text = match &event.location {
+                Some(location) => tr!(
+                    lang,
+                    "location-current",
+                    noun = noun.as_str(),
+                    date = date.as_str(),
+                    location = location.address.as_str()
+                ),
+                None => tr!(
+                    lang,
+                    "location-none",
+                    noun = noun.as_str(),
+                    date = date.as_str()
+                ),
+            };
+
+            return Ok(Scheduled {
+                text,
+                venue: event
+                    .location
+                    .and_then(|location| venue(location, &noun, &date, lang)),
+            });
+        };
 
         self.repository
-            .write_new_member_suggestion(NewMemberSuggestion {
-                event_id: latest_event.event_id,
+            .write_event_location(EventLocationRequest {
+                event_id: event.event_id,
                 chat_id,
-                user_id,
-                suggestion: escape_hyphen(suggestion).to_string(),
+                actor_id: user_id,
+                location: location.clone(),
             })
-            .await
-            .unwrap();
+            .await?;
 
-        Ok(())
+        Ok(Scheduled {
+            text: tr!(
+                lang,
+                "location-set",
+                noun = noun.as_str(),
+                date = date.as_str(),
+                location = location.address.as_str()
+            ),
+            venue: venue(location, &noun, &date, lang),
+        })
     }
 
-    pub async fn toggle_with_insights(&self, chat_id: i64) -> Result<String, Box<dyn Error>> {
-        let latest_event = self
+    pub async fn new_member_suggestion(
+        &self,
+        user_id: i64,
+        name: &str,
+        text: &str,
+        settings: &ClubSettings,
+        lang: &str,
+    ) -> Result<Suggestion, Box<dyn Error>> {
+        let chat_id = settings.chat_id;
+        let events = self
             .repository
-            .get_latest_event(LastEventRequest { chat_id })
-            .await
-            .unwrap();
+            .get_active_events(LastEventRequest { chat_id })
+            .await?
+            .events;
 
-        if latest_event.event_id.is_nil() {
-            return Err(Box::new(Err::NoActiveEventFound));
-        }
+        let (track, suggestion) = split_track(&events, text);
+        let latest_event = resolve_event(events, track.as_str())?;
 
         if !latest_event.subject.is_empty() {
-            return Ok("Unable to toggle insights because subject is already picked".to_string());
+            return Err(Box::new(Err::AlreadyPickedSubject(latest_event.subject)));
         }
 
-        self.repository
-            .toggle_with_insights(EventToggleWithInsightsRequest {
+        let identifier = identifier::detect(&suggestion);
+
+        let suggested: Vec<EventSuggestion> = self
+            .repository
+            .get_all_suggestions_for_event(EventSuggestionsRequest {
                 event_id: latest_event.event_id,
-                with_insights: latest_event.with_insights,
             })
-            .await?;
+            .await?
+            .suggestions
+            .into_iter()
+            .filter(|suggestion| suggestion.user_id == user_id)
+            .collect();
 
-        if latest_event.with_insights {
-            return Ok("Turned off insights for current event".to_string());
-        } else if !latest_event.with_insights {
-            return Ok("Turned on insights for current event".to_string());
+        if settings.suggestion_limit > 0 && suggested.len() >= settings.suggestion_limit as usize {
+            return Err(Box::new(Err::SuggestionLimitReached(
+                settings.suggestion_limit,
+            )));
         }
 
-        Ok("Unable to toggle insights".to_string())
-    }
+        // an isbn or a link names one work, there is nothing to choose from
+        let mut works = match &identifier {
+            Some(identifier) => self.lookup_catalog(identifier).await.into_iter().collect(),
+            None => self.search_catalog(&suggestion, settings.kind).await,
+        };
+        let work = match works.len() {
+            1 => works.pop(),
+            _ => None,
+        };
 
-    // start_active_event needed only to stop accepting new insights and get summary link
-    pub async fn start_active_event(&self, chat_id: i64) -> Result<String, Box<dyn Error>> {
-        let latest_event = self
-            .repository
-            .get_latest_event(LastEventRequest { chat_id })
-            .await
-            .unwrap();
+        // another link to a work the member already suggested isn't a new suggestion
+        let duplicate = suggested.iter().any(|s| match (&work, &s.work) {
+            (Some(work), Some(other)) => work.key == other.key,
+            _ => identifier.is_some() && s.identifier == identifier,
+        });
 
-        if latest_event.event_id.is_nil() {
-            return Err(Box::new(Err::NoActiveEventFound));
+        if duplicate {
+            return Err(Box::new(Err::AlreadySuggested));
         }
 
-        if !latest_event.with_insights {
-            return Err(Box::new(Err::EventWithoutInsights));
-        }
+        let description = match &work {
+            Some(work) => describe_work(work, settings.kind, lang),
+            None => raw_title(&suggestion, identifier.as_ref(), lang),
+        };
 
-        let summary_link = self
-            .insights
-            .start_event(latest_event.event_id)
-            .await
-            .unwrap();
+        let suggestion_id = self
+            .repository
+            .write_new_member_suggestion(NewMemberSuggestion {
+                event_id: latest_event.event_id,
+                chat_id,
+                user_id,
+                name: name.to_string(),
+                suggestion: suggestion.clone(),
+                work,
+                identifier,
+            })
+            .await?;
 
-        Ok(format!(
-            "Here is your [insights summary]({})\\.\nHave a great club\\!",
-            summary_link,
-        ))
+        if works.len() > 1 {
+            return Ok(Suggestion::Choose {
+                suggestion_id,
+                text: suggestion,
+                works,
+                kind: settings.kind,
+            });
+        }
+
+        Ok(Suggestion::Added(description))
     }
 
-    pub async fn achieve_active_event(&self, chat_id: i64) -> Result<String, Box<dyn Error>> {
-        let latest_event = self
+    // key is none when none of the offered works is the one suggested
+    pub async fn choose_work(
+        &self,
+        user_id: i64,
+        suggestion_id: i64,
+        key: Option<&str>,
+        settings: &ClubSettings,
+        lang: &str,
+    ) -> Result<String, Box<dyn Error>> {
+        let chat_id = settings.chat_id;
+        let suggestion = self
             .repository
-            .get_latest_event(LastEventRequest { chat_id })
-            .await
-            .unwrap();
+            .get_suggestion(SuggestionRequest { id: suggestion_id })
+            .await?
+            .filter(|suggestion| suggestion.chat_id == chat_id);
 
-        if latest_event.event_id.is_nil() {
-            return Err(Box::new(Err::NoActiveEventFound));
+        let Some(suggestion) = suggestion else {
+            return Err(Box::new(Err::SuggestionNotFound));
+        };
+
+        if suggestion.user_id != user_id {
+            return Err(Box::new(Err::NotYourSuggestion));
         }
 
+        let kind = settings.kind;
+
+        // works aren't kept between the question and the answer, the catalog is asked again
+        let work = match key {
+            Some(key) => self
+                .search_catalog(&suggestion.suggestion, kind)
+                .await
+                .into_iter()
+                .find(|work| work.key == key),
+            None => None,
+        };
+
+        let Some(work) = work else {
+            return Ok(suggestion.suggestion);
+        };
+
+        let description = describe_work(&work, kind, lang);
+
         self.repository
-            .achieve_event(AchieveEventRequest {
+            .write_suggestion_work(SuggestionWorkRequest {
+                id: suggestion_id,
                 chat_id,
-                event_id: latest_event.event_id,
+                actor_id: user_id,
+                work,
             })
-            .await
-            .unwrap();
+            .await?;
 
-        if latest_event.with_insights && !latest_event.subject.is_empty() {
-            self.insights
-                .finish_event(latest_event.event_id)
-                .await
-                .unwrap();
-        }
+        Ok(description)
+    }
 
-        let formatted_date = beautify_date(latest_event.event_date);
+    async fn lookup_catalog(&self, identifier: &Identifier) -> Option<Work> {
+        let catalog = self.catalog.as_ref()?;
 
-        Ok(formatted_date)
+        match catalog.lookup(identifier).await {
+            Ok(work) => work,
+            Err(err) => {
+                log::warn!(
+                    "unable to look up {} {}: {}",
+                    identifier.kind(),
+                    identifier.value(),
+                    err
+                );
+                None
+            }
+        }
     }
 
-    pub async fn pick_from_suggestions(&self, chat_id: i64) -> Result<String, Box<dyn Error>> {
-        let latest_event = self
-            .repository
-            .get_latest_event(LastEventRequest { chat_id })
-            .await
-            .unwrap();
+    // a catalog that can't be reached leaves the suggestion unresolved
+    async fn search_catalog(&self, text: &str, kind: ClubKind) -> Vec<Work> {
+        let Some(catalog) = &self.catalog else {
+            return vec![];
+        };
 
-        if latest_event.event_id.is_nil() {
-            return Err(Box::new(Err::NoActiveEventFound));
+        match catalog.search(text, kind).await {
+            Ok(works) => catalog::candidates(text, works),
+            Err(err) => {
+                log::warn!("unable to resolve suggestion {:?}: {}", text, err);
+                vec![]
+            }
         }
+    }
+
+    pub async fn toggle_with_insights(
+        &self,
+        chat_id: i64,
+        user_id: i64,
+        track: &str,
+        lang: &str,
+    ) -> Result<String, Box<dyn Error>> {
+        let latest_event = self.active_event(chat_id, track).await?;
 
         if !latest_event.subject.is_empty() {
-            return Err(Box::new(Err::AlreadyPickedSubject(latest_event.subject)));
+            return Ok(tr!(lang, "insights-subject-picked"));
         }
 
-        let suggestions = self
-            .repository
-            .get_all_suggestions_for_event(EventSuggestionsRequest {
+        self.repository
+            .toggle_with_insights(EventToggleWithInsightsRequest {
                 event_id: latest_event.event_id,
+                chat_id,
+                actor_id: user_id,
+                with_insights: latest_event.with_insights,
             })
-            .await
-            .unwrap()
-            .suggestions;
+            .await?;
 
-        if suggestions.is_empty() {
-            return Err(Box::new(Err::NoSuggestionsFound));
+        if latest_event.with_insights {
+            return Ok(tr!(lang, "insights-off"));
         }
 
-        let result = suggestions.choose(&mut rand::thread_rng());
-
-        if !latest_event.with_insights {
-            self.repository
-                .write_picked_subject(PickedSubjectRequest {
-                    event_id: latest_event.event_id,
-                    subject: result.unwrap().to_string(),
-                    insights_link: None,
-                })
-                .await
-                .unwrap();
-
-            return Ok(format!("Randomly picked\n{}", result.unwrap()));
-        }
+        Ok(tr!(lang, "insights-on"))
+    }
 
-        let insights_link = self
-            .insights
-            .register_event(RegisterEventRequest {
+    // closes the questions and numbers them in shuffled order, with insights it also
+    // stops accepting new insights and gets the summary link
+    pub async fn start_active_event(
+        &self,
+        user_id: i64,
+        track: &str,
+        settings: &ClubSettings,
+        lang: &str,
+    ) -> Result<Started, Box<dyn Error>> {
+        let chat_id = settings.chat_id;
+        let latest_event = self.active_event(chat_id, track).await?;
+        let questions = self
+            .repository
+            .get_questions(QuestionsRequest {
                 event_id: latest_event.event_id,
-                event_subject: unescape_hyphen(result.unwrap()),
-                club_id: chat_id,
             })
-            .await
-            .unwrap();
+            .await?
+            .questions;
+
+        // numbers are given once, a repeated start only numbers questions that had none
+        let mut unnumbered: Vec<Uuid> = questions
+            .iter()
+            .filter(|question| question.number.is_none())
+            .map(|question| question.id)
+            .collect();
+        unnumbered.shuffle(&mut rand::thread_rng());
+
+        let first = questions
+            .iter()
+            .filter_map(|question| question.number)
+            .max()
+            .unwrap_or_default()
+            + 1;
+
+        let outbox = latest_event.with_insights.then(|| {
+            OutboxMessage::new(OutboxKind::Start, latest_event.event_id, chat_id, json!({}))
+        });
 
         self.repository
-            .write_picked_subject(PickedSubjectRequest {
+            .write_event_started(EventStartedRequest {
                 event_id: latest_event.event_id,
-                subject: result.unwrap().to_string(),
-                insights_link: Some(insights_link.clone()),
+                chat_id,
+                actor_id: user_id,
+                outbox: outbox.clone(),
+                numbers: unnumbered
+                    .into_iter()
+                    .zip(first..)
+                    .map(|(question_id, number)| QuestionNumber {
+                        question_id,
+                        number,
+                    })
+                    .collect(),
             })
-            .await
-            .unwrap();
+            .await?;
 
-        Ok(format!(
-            "Randomly picked\n{}\n\nAnd here is your [insights link]({})",
-            result.unwrap(),
-            insights_link,
-        ))
-    }
+        let questions = match questions.is_empty() {
+            true => None,
+            false => Some(self.question_list(latest_event.event_id, lang).await?),
+        };
 
-    pub async fn get_current_event_info(&self, chat_id: i64) -> Result<String, Box<dyn Error>> {
-        let latest_event = self
-            .repository
-            .get_latest_event(LastEventRequest { chat_id })
-            .await
-            .unwrap();
+        let text = match outbox {
+            Some(outbox) => Some(self.insights_started(&outbox, settings, lang).await?),
+            None if questions.is_none() => {
+                Some(markdown::escape(&tr!(lang, "start-club-no-questions")))
+            }
+            None => None,
+        };
 
-        if latest_event.event_id.is_nil() {
-            return Err(Box::new(Err::NoActiveEventFound));
-        }
+        Ok(Started { text, questions })
+    }
 
-        let formatted_date = beautify_date(latest_event.event_date);
+    async fn insights_started(
+        &self,
+        outbox: &OutboxMessage,
+        settings: &ClubSettings,
+        lang: &str,
+    ) -> Result<String, Box<dyn Error>> {
+        // the event has started already, the outbox worker retries a failed call
+        let delivery = self.deliver_now(outbox).await.unwrap_or_else(|err| {
+            log::error!(
+                "unable to start insights event {}: {}",
+                outbox.event_id,
+                err
+            );
+            Delivery::Deferred
+        });
 
-        if latest_event.subject.is_empty() {
-            return Ok(format!(
-                "The next event is on {}.\nThe subject hasn't been picked yet",
-                formatted_date,
-            ));
-        }
+        let (link, expires_at) = match delivery {
+            Delivery::Delivered {
+                link: Some(link),
+                expires_at,
+            } => (link, expires_at),
+            Delivery::Rejected(reason) => {
+                return Ok(markdown::escape(&tr!(
+                    lang,
+                    "start-club-rejected",
+                    reason = reason
+                )))
+            }
+            _ => return Ok(markdown::escape(&tr!(lang, "start-club-pending"))),
+        };
 
-        let mut message = format!(
-            "The next event is on {}\\.\nThe subject is \\- {}",
-            formatted_date, latest_event.subject
-        );
+        match expires_at {
+            Some(expires_at) => {
+                let tz = settings.tz();
+                let date = beautify_date(local_date(expires_at.naive_utc(), tz), lang);
 
-        if latest_event.with_insights {
-            message = format!(
-                "{}\nHere is the [insights link]({})",
-                message,
-                latest_event.insights_link.unwrap()
-            )
+                Ok(tr!(
+                    lang,
+                    "start-club-summary-expiring",
+                    link = markdown::escape_url(&link),
+                    date = markdown::escape(&date)
+                ))
+            }
+            None => Ok(tr!(
+                lang,
+                "start-club-summary",
+                link = markdown::escape_url(&link)
+            )),
         }
-
-        Ok(message)
     }
-}
-
-pub async fn default_service() -> Service {
-    let dsn = env::var("DB_DSN").unwrap();
-    let repo = new_postgres_repository(dsn.as_str()).await;
 
-    let address = env::var("INSIGHTS_ADDRESS").unwrap();
-    let insights = insights::new(address);
+    pub async fn achieve_active_event(
+        &self,
+        user_id: i64,
+        track: &str,
+        settings: &ClubSettings,
+        lang: &str,
+    ) -> Result<Achieved, Box<dyn Error>> {
+        let chat_id = settings.chat_id;
+        let latest_event = self.active_event(chat_id, track).await?;
 
-    Service {
-        repository: repo.unwrap(),
-        insights,
-    }
-}
+        let outbox = (latest_event.with_insights && !latest_event.subject.is_empty()).then(|| {
+            OutboxMessage::new(
+                OutboxKind::Finish,
+                latest_event.event_id,
+                chat_id,
+                json!({}),
+            )
+        });
 
-fn escape_hyphen(text: &str) -> String {
-    text.replace("-", "\\-")
-}
+        self.repository
+            .achieve_event(AchieveEventRequest {
+                chat_id,
+                actor_id: user_id,
+                event_id: latest_event.event_id,
+                outbox: outbox.clone(),
+            })
+            .await?;
+
+        // the event is achieved either way, the worker finishes it on insights later
+        if let Some(outbox) = &outbox {
+            match self.deliver_now(outbox).await {
+                Ok(Delivery::Rejected(reason)) => log::warn!(
+                    "insights refused to finish event {}: {}",
+                    latest_event.event_id,
+                    reason
+                ),
+                Err(err) => log::error!(
+                    "unable to finish insights event {}: {}",
+                    latest_event.event_id,
+                    err
+                ),
+                _ => {}
+            }
+        }
+
+        let tz = settings.tz();
+        let formatted_date = beautify_date(local_date(latest_event.event_date, tz), lang);
+
+        let rating = (!latest_event.subject.is_empty()).then(|| RatingRound {
+            event_id: latest_event.event_id,
+            text: tr!(
+                lang,
+                "rating-round",
+                subject = latest_event.subject.as_str()
+            ),
+        });
+
+        Ok(Achieved {
+            date: formatted_date,
+            rating,
+        })
+    }
+
+    // the star buttons stay on old messages, so any achieved event of the chat can be rated
+    pub async fn rate_event(
+        &self,
+        chat_id: i64,
+        user_id: i64,
+        name: &str,
+        event_id: Uuid,
+        stars: i32,
+        lang: &str,
+    ) -> Result<String, Box<dyn Error>> {
+        let event = self
+            .repository
+            .get_event_chat(EventChatRequest { event_id })
+            .await?
+            .filter(|event| event.chat_id == chat_id && !event.subject.is_empty());
+
+        let Some(event) = event else {
+            return Err(Box::new(Err::NothingToRate));
+        };
+
+        let stars = stars.clamp(1, MAX_STARS);
+
+        self.repository
+            .write_rating(NewRatingRequest {
+                event_id,
+                chat_id,
+                user_id,
+                name: name.to_string(),
+                subject: event.subject.clone(),
+                stars,
+            })
+            .await?;
+
+        Ok(tr!(
+            lang,
+            "rating-saved",
+            subject = event.subject.as_str(),
+            stars = stars
+        ))
+    }
+
+    // a review goes along with the member's rating of the latest achieved event
+    pub async fn review_event(
+        &self,
+        chat_id: i64,
+        user_id: i64,
+        text: &str,
+        lang: &str,
+    ) -> Result<String, Box<dyn Error>> {
+        let review = text.trim();
+
+        if review.chars().count() > MAX_REVIEW_LENGTH {
+            return Err(Box::new(Err::ReviewTooLong(MAX_REVIEW_LENGTH as i32)));
+        }
+
+        let latest = self
+            .repository
+            .get_achieved_events(AchievedEventsRequest { chat_id, limit: 1 })
+            .await?
+            .events
+            .pop();
+
+        let Some(latest) = latest else {
+            return Err(Box::new(Err::NothingToRate));
+        };
+
+        if !latest
+            .ratings
+            .iter()
+            .any(|rating| rating.user_id == user_id)
+        {
+            return Err(Box::new(Err::RatingRequired(latest.subject)));
+        }
+
+        self.repository
+            .write_review(NewReviewRequest {
+                event_id: latest.event_id,
+                chat_id,
+                user_id,
+                subject: latest.subject.clone(),
+                review: review.to_string(),
+            })
+            .await?;
+
+        Ok(tr!(lang, "review-saved", subject = latest.subject.as_str()))
+    }
+
+    pub async fn get_history(
+        &self,
+        limit: i64,
+        settings: &ClubSettings,
+        lang: &str,
+    ) -> Result<String, Box<dyn Error>> {
+        let chat_id = settings.chat_id;
+        let events = self
+            .repository
+            .get_achieved_events(AchievedEventsRequest { chat_id, limit })
+            .await?
+            .events;
+
+        if events.is_empty() {
+            return Ok(tr!(lang, "history-empty"));
+        }
+
+        let tz = settings.tz();
+        let lines: Vec<String> = events
+            .iter()
+            .map(|event| describe_achieved_event(event, tz, lang))
+            .collect();
+
+        Ok(tr!(
+            lang,
+            "history-title",
+            count = lines.len(),
+            lines = lines.join("\n")
+        ))
+    }
+
+    pub async fn get_stats(&self, chat_id: i64, lang: &str) -> Result<String, Box<dyn Error>> {
+        let events = self
+            .repository
+            .get_achieved_events(AchievedEventsRequest {
+                chat_id,
+                limit: i64::MAX,
+            })
+            .await?
+            .events;
+
+        Ok(describe_stats(&events, lang))
+    }
+
+    pub async fn pick_from_suggestions(
+        &self,
+        user_id: i64,
+        track: &str,
+        under: Option<i32>,
+        club_name: &str,
+        settings: &ClubSettings,
+        lang: &str,
+    ) -> Result<Pick, Box<dyn Error>> {
+        let chat_id = settings.chat_id;
+        let latest_event = self.active_event(chat_id, track).await?;
+
+        if !latest_event.subject.is_empty() {
+            return Err(Box::new(Err::AlreadyPickedSubject(latest_event.subject)));
+        }
+
+        // the second /pick in poll mode closes the poll started by the first one
+        if let Some(message_id) = latest_event.poll_message_id {
+            return Ok(Pick::ClosePoll { message_id });
+        }
+
+        let mut suggestions = self
+            .repository
+            .get_all_suggestions_for_event(EventSuggestionsRequest {
+                event_id: latest_event.event_id,
+            })
+            .await?
+            .suggestions;
+
+        if suggestions.is_empty() {
+            return Err(Box::new(Err::NoSuggestionsFound));
+        }
+
+        // everyone who suggested takes part, whether their suggestion passed the filter or not
+        let participants = participants(&suggestions);
+
+        // suggestions the catalog knows nothing about can't be shown to fit, so they are
+        // left out and the club is told how many
+        let mut skipped = 0;
+
+        if let Some(limit) = under {
+            if settings.kind == ClubKind::Generic {
+                return Err(Box::new(Err::PickFilterWithoutKind));
+            }
+
+            suggestions.retain(|suggestion| {
+                let size = suggestion
+                    .work
+                    .as_ref()
+                    .and_then(|work| work_size(work, settings.kind));
+
+                if size.is_none() {
+                    skipped += 1;
+                }
+
+                size.is_some_and(|size| size <= limit)
+            });
+
+            if suggestions.is_empty() {
+                return Err(Box::new(Err::NoSuggestionsUnder(
+                    limit,
+                    settings.kind.as_str().to_string(),
+                    skipped,
+                )));
+            }
+        }
+
+        let picked = match settings.pick_mode {
+            // a work suggested twice is one option, not twice the chance
+            PickMode::Random => distinct(&suggestions)
+                .choose(&mut rand::thread_rng())
+                .unwrap(),
+            PickMode::Ranked => most_suggested(&suggestions),
+            PickMode::Poll => {
+                let options = poll_options(&suggestions, lang);
+                if options.len() > 1 {
+                    return Ok(Pick::StartPoll {
+                        event_id: latest_event.event_id,
+                        question: tr!(
+                            lang,
+                            "pick-poll-question",
+                            kind = settings.kind.as_str(),
+                            noun = event_noun(&latest_event.track, lang)
+                        ),
+                        options,
+                        skipped,
+                    });
+                }
+
+                &suggestions[0]
+            }
+        };
+
+        let message = self
+            .write_pick(
+                chat_id,
+                user_id,
+                latest_event,
+                PickedSubject {
+                    subject: subject_of(picked, lang),
+                    work: picked.work.clone(),
+                    suggested_by: suggesters(&suggestions, picked),
+                    kind: settings.kind,
+                    club_name,
+                    participants,
+                },
+                settings.pick_mode,
+                lang,
+            )
+            .await?;
+
+        match skipped {
+            0 => Ok(Pick::Picked(message)),
+            _ => Ok(Pick::Picked(format!(
+                "{}\n\n{}",
+                message,
+                markdown::escape(&skipped_note(skipped, settings.kind, lang))
+            ))),
+        }
+    }
+
+    pub async fn save_pick_poll(
+        &self,
+        chat_id: i64,
+        user_id: i64,
+        event_id: Uuid,
+        message_id: i32,
+    ) -> Result<(), Box<dyn Error>> {
+        self.repository
+            .write_pick_poll(PickPollRequest {
+                event_id,
+                chat_id,
+                actor_id: user_id,
+                message_id,
+            })
+            .await?;
+
+        Ok(())
+    }
+
+    // votes are poll options with their voter counts, empty when the poll couldn't be closed
+    pub async fn pick_from_poll(
+        &self,
+        user_id: i64,
+        track: &str,
+        club_name: &str,
+        votes: Vec<(String, i32)>,
+        settings: &ClubSettings,
+        lang: &str,
+    ) -> Result<String, Box<dyn Error>> {
+        let chat_id = settings.chat_id;
+        let latest_event = self.active_event(chat_id, track).await?;
+
+        if !latest_event.subject.is_empty() {
+            return Err(Box::new(Err::AlreadyPickedSubject(latest_event.subject)));
+        }
+
+        let suggestions = self
+            .repository
+            .get_all_suggestions_for_event(EventSuggestionsRequest {
+                event_id: latest_event.event_id,
+            })
+            .await?
+            .suggestions;
+
+        if suggestions.is_empty() {
+            return Err(Box::new(Err::NoSuggestionsFound));
+        }
+
+        let most_votes = votes.iter().map(|(_, count)| *count).max().unwrap_or(0);
+        let leaders: Vec<&String> = votes
+            .iter()
+            .filter(|(_, count)| *count == most_votes)
+            .map(|(option, _)| option)
+            .collect();
+
+        // poll options are trimmed to telegram limits, so map the winner back to the full suggestion
+        let winner = leaders.choose(&mut rand::thread_rng());
+        let picked = match winner {
+            Some(winner) => suggestions
+                .iter()
+                .find(|suggestion| poll_option(&subject_of(suggestion, lang)) == **winner),
+            None => distinct(&suggestions)
+                .choose(&mut rand::thread_rng())
+                .copied(),
+        };
+
+        let (subject, work, suggested_by) = match (picked, winner) {
+            (Some(picked), _) => (
+                subject_of(picked, lang),
+                picked.work.clone(),
+                suggesters(&suggestions, picked),
+            ),
+            (None, Some(winner)) => (winner.to_string(), None, vec![]),
+            (None, None) => return Err(Box::new(Err::NoSuggestionsFound)),
+        };
+
+        let kind = settings.kind;
+
+        self.write_pick(
+            chat_id,
+            user_id,
+            latest_event,
+            PickedSubject {
+                subject,
+                work,
+                suggested_by,
+                kind,
+                club_name,
+                participants: participants(&suggestions),
+            },
+            PickMode::Poll,
+            lang,
+        )
+        .await
+    }
+
+    async fn write_pick(
+        &self,
+        chat_id: i64,
+        user_id: i64,
+        latest_event: LastEventResponse,
+        picked: PickedSubject<'_>,
+        mode: PickMode,
+        lang: &str,
+    ) -> Result<String, Box<dyn Error>> {
+        let PickedSubject {
+            subject,
+            work,
+            suggested_by,
+            kind,
+            club_name,
+            participants,
+        } = picked;
+        let headline = markdown::escape(&tr!(lang, "pick-headline", mode = mode.as_str()));
+        // the message shows what's known about the work, the event keeps the short subject
+        let details = markdown::escape(&match &work {
+            Some(work) => describe_work(work, kind, lang),
+            None => subject.clone(),
+        });
+
+        let outbox = latest_event.with_insights.then(|| {
+            let payload = RegisterPayload {
+                subject: subject.clone(),
+                club_name: club_name.to_string(),
+                event_date: Some(Utc.from_utc_datetime(&latest_event.event_date)),
+                participants,
+            };
+
+            OutboxMessage::new(
+                OutboxKind::Register,
+                latest_event.event_id,
+                chat_id,
+                serde_json::to_value(payload).unwrap(),
+            )
+        });
+
+        self.repository
+            .write_picked_subject(PickedSubjectRequest {
+                event_id: latest_event.event_id,
+                chat_id,
+                actor_id: user_id,
+                subject: subject.clone(),
+                work,
+                suggested_by,
+                outbox: outbox.clone(),
+            })
+            .await?;
+
+        let Some(outbox) = outbox else {
+            return Ok(tr!(
+                lang,
+                "pick-result",
+                headline = headline,
+                subject = details
+            ));
+        };
+
+        // the pick doesn't wait for insights, a missing link is attached by the outbox worker
+        let delivery = self.deliver_now(&outbox).await.unwrap_or_else(|err| {
+            log::error!(
+                "unable to register insights event {}: {}",
+                outbox.event_id,
+                err
+            );
+            Delivery::Deferred
+        });
+
+        match delivery {
+            Delivery::Delivered {
+                link: Some(link), ..
+            } => Ok(tr!(
+                lang,
+                "pick-result-insights",
+                headline = headline,
+                subject = details,
+                link = markdown::escape_url(&link),
+            )),
+            Delivery::Rejected(reason) => Ok(tr!(
+                lang,
+                "pick-result-insights-rejected",
+                headline = headline,
+                subject = details,
+                reason = markdown::escape(&reason),
+            )),
+            _ => Ok(tr!(
+                lang,
+                "pick-result-insights-pending",
+                headline = headline,
+                subject = details,
+            )),
+        }
+    }
+
+    // delivers insights calls that couldn't go out right after the change that wrote them
+    pub async fn deliver_insights_outbox(&self) -> Result<Vec<Notice>, Box<dyn Error>> {
+        let due = self
+            .repository
+            .get_due_outbox(DueOutboxRequest {
+                now: Utc::now(),
+                event_id: None,
+                limit: OUTBOX_BATCH,
+            })
+            .await?
+            .entries;
+
+        let mut notices = vec![];
+
+        for entry in due {
+            let delivery = self.deliver(&entry).await?;
+
+            let message = entry.message;
+            let lang = self.language(message.chat_id, None).await;
+            let subject = message.payload["subject"].as_str().unwrap_or_default();
+
+            let text = match (delivery, message.kind) {
+                (
+                    Delivery::Delivered {
+                        link: Some(link), ..
+                    },
+                    OutboxKind::Register,
+                ) => tr!(
+                    lang,
+                    "insights-link-attached",
+                    subject = subject,
+                    link = link
+                ),
+                (
+                    Delivery::Delivered {
+                        link: Some(link), ..
+                    },
+                    OutboxKind::Start,
+                ) => {
+                    tr!(lang, "insights-summary-attached", link = link)
+                }
+                (Delivery::Rejected(reason), kind) => tr!(
+                    lang,
+                    "insights-rejected",
+                    call = kind.as_str(),
+                    reason = reason
+                ),
+                _ => continue,
+            };
+
+            notices.push(Notice {
+                chat_id: message.chat_id,
+                text,
+            })
+        }
+
+        Ok(notices)
+    }
+
+    // callbacks are posted to the club the event belongs to, none when the event is unknown
+    pub async fn insights_callback(
+        &self,
+        callback: InsightsCallback,
+    ) -> Result<Option<Notice>, Box<dyn Error>> {
+        let Some(event) = self
+            .repository
+            .get_event_chat(EventChatRequest {
+                event_id: callback.event_id(),
+            })
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        let lang = self.language(event.chat_id, None).await;
+
+        let text = match callback {
+            InsightsCallback::SummaryReady { summary_link, .. } => tr!(
+                lang,
+                "insights-summary-ready",
+                subject = event.subject,
+                link = summary_link
+            ),
+            InsightsCallback::InsightsSubmitted { count, .. } => tr!(
+                lang,
+                "insights-submitted",
+                count = count,
+                subject = event.subject
+            ),
+        };
+
+        Ok(Some(Notice {
+            chat_id: event.chat_id,
+            text,
+        }))
+    }
+
+    // a message just written is sent right away, unless an earlier call of its event still waits
+    async fn deliver_now(&self, message: &OutboxMessage) -> Result<Delivery, Box<dyn Error>> {
+        let due = self
+            .repository
+            .get_due_outbox(DueOutboxRequest {
+                now: Utc::now(),
+                event_id: Some(message.event_id),
+                limit: 1,
+            })
+            .await?
+            .entries;
+
+        match due.first() {
+            Some(entry) if entry.message.id == message.id => self.deliver(entry).await,
+            _ => Ok(Delivery::Deferred),
+        }
+    }
+
+    async fn deliver(&self, entry: &OutboxEntry) -> Result<Delivery, Box<dyn Error>> {
+        let message = &entry.message;
+
+        let result = match message.kind {
+            OutboxKind::Register => {
+                let payload: RegisterPayload =
+                    serde_json::from_value(message.payload.clone()).unwrap_or_default();
+
+                self.insights
+                    .register_event(
+                        RegisterEventRequest {
+                            event_id: message.event_id,
+                            event_subject: payload.subject,
+                            club_id: message.chat_id,
+                            club_name: payload.club_name,
+                            event_date: payload.event_date,
+                            participants: payload.participants,
+                        },
+                        message.id,
+                    )
+                    .await
+                    .map(|link| (Some(link), None))
+            }
+            OutboxKind::Start => self
+                .insights
+                .start_event(message.event_id, message.id)
+                .await
+                .map(|started| (Some(started.summary_link), started.summary_expires_at)),
+            OutboxKind::Finish => self
+                .insights
+                .finish_event(message.event_id, message.id)
+                .await
+                .map(|_| (None, None)),
+        };
+
+        let err = match result {
+            Ok((link, expires_at)) => {
+                self.repository
+                    .mark_outbox_delivered(OutboxDeliveredRequest {
+                        id: message.id,
+                        event_id: message.event_id,
+                        insights_link: link
+                            .clone()
+                            .filter(|_| message.kind == OutboxKind::Register),
+                    })
+                    .await?;
+
+                return Ok(Delivery::Delivered { link, expires_at });
+            }
+            Err(err) => err,
+        };
+
+        // an open circuit or a struggling insights may recover, a rejected call won't
+        let attempts = entry.attempts + 1;
+        let retry = matches!(err, InsightsError::Unavailable) || err.is_transient();
+        let next_attempt_at = match retry && attempts < OUTBOX_MAX_ATTEMPTS {
+            true => Some(Utc::now() + outbox_backoff(attempts)),
+            false => None,
+        };
+
+        match next_attempt_at {
+            Some(at) => log::warn!(
+                "insights {} for event {} failed, retrying at {}: {}",
+                message.kind.as_str(),
+                message.event_id,
+                at,
+                err
+            ),
+            None => log::error!(
+                "insights {} for event {} failed after {} attempts, giving up: {}",
+                message.kind.as_str(),
+                message.event_id,
+                attempts,
+                err
+            ),
+        }
+
+        self.repository
+            .mark_outbox_failed(OutboxFailedRequest {
+                id: message.id,
+                error: err.to_string(),
+                next_attempt_at,
+            })
+            .await?;
+
+        match err {
+            InsightsError::Rejected(reason) => Ok(Delivery::Rejected(reason)),
+            _ => Ok(Delivery::Deferred),
+        }
+    }
+
+    pub async fn get_current_event_info(
+        &self,
+        track: &str,
+        settings: &ClubSettings,
+        lang: &str,
+    ) -> Result<String, Box<dyn Error>> {
+        let chat_id = settings.chat_id;
+        let events = self
+            .repository
+            .get_active_events(LastEventRequest { chat_id })
+            .await?
+            .events;
+
+        let tz = settings.tz();
+
+        // with several tracks running and none named, show all of them
+        let events = if track.trim().is_empty() && events.len() > 1 {
+            events
+        } else {
+            vec![resolve_event(events, track)?]
+        };
+
+        let mut messages = vec![];
+
+        for event in events {
+            let members = self
+                .repository
+                .get_progress(ProgressRequest {
+                    event_id: event.event_id,
+                })
+                .await?
+                .members;
+
+            messages.push(describe_event(event, &members, tz, lang));
+        }
+
+        Ok(messages.join("\n\n"))
+    }
+
+    pub async fn record_progress(
+        &self,
+        chat_id: i64,
+        user_id: i64,
+        name: &str,
+        text: &str,
+        lang: &str,
+    ) -> Result<String, Box<dyn Error>> {
+        let events = self
+            .repository
+            .get_active_events(LastEventRequest { chat_id })
+            .await?
+            .events;
+
+        let (track, mark) = split_track(&events, text);
+        let event = resolve_event(events, &track)?;
+
+        let Some(mark) = ProgressMark::parse(&mark) else {
+            return Err(Box::new(Err::WrongProgress));
+        };
+
+        self.write_progress(event, chat_id, user_id, name, mark, lang)
+            .await
+    }
+
+    // a tap on a check-in only counts while its event is still ahead
+    pub async fn checkin_progress(
+        &self,
+        chat_id: i64,
+        user_id: i64,
+        name: &str,
+        event_id: Uuid,
+        percent: i32,
+        lang: &str,
+    ) -> Result<String, Box<dyn Error>> {
+        let event = self
+            .repository
+            .get_active_events(LastEventRequest { chat_id })
+            .await?
+            .events
+            .into_iter()
+            .find(|event| event.event_id == event_id);
+
+        let Some(event) = event else {
+            return Err(Box::new(Err::NoActiveEventFound));
+        };
+
+        let mark = ProgressMark::Percent(percent.clamp(0, 100));
+
+        self.write_progress(event, chat_id, user_id, name, mark, lang)
+            .await
+    }
+
+    async fn write_progress(
+        &self,
+        event: LastEventResponse,
+        chat_id: i64,
+        user_id: i64,
+        name: &str,
+        mark: ProgressMark,
+        lang: &str,
+    ) -> Result<String, Box<dyn Error>> {
+        if event.subject.is_empty() {
+            return Err(Box::new(Err::SubjectNotPicked));
+        }
+
+        let (percent, page) = match mark {
+            ProgressMark::Percent(percent) => (percent, None),
+            ProgressMark::Page(page) => {
+                let pages = event.work.as_ref().and_then(|work| work.pages);
+                let Some(pages) = pages.filter(|pages| *pages > 0) else {
+                    return Err(Box::new(Err::ProgressPagesUnknown));
+                };
+
+                // a page past the end means the book is finished
+                let page = page.min(pages);
+                (page * 100 / pages, Some((page, pages)))
+            }
+        };
+
+        self.repository
+            .write_progress(NewProgressRequest {
+                event_id: event.event_id,
+                chat_id,
+                user_id,
+                name: name.to_string(),
+                percent,
+                page: page.map(|(page, _)| page),
+            })
+            .await?;
+
+        Ok(match page {
+            Some((page, pages)) => tr!(
+                lang,
+                "progress-saved-page",
+                name = name,
+                page = page,
+                pages = pages,
+                percent = percent,
+                subject = event.subject.as_str()
+            ),
+            None => tr!(
+                lang,
+                "progress-saved",
+                name = name,
+                percent = percent,
+                subject = event.subject.as_str()
+            ),
+        })
+    }
+
+    pub async fn discussion(
+        &self,
+        chat_id: i64,
+        track: &str,
+        lang: &str,
+    ) -> Result<Discussion, Box<dyn Error>> {
+        let event = self.active_event(chat_id, track).await?;
+
+        discussion_of(event, lang)
+    }
+
+    pub async fn save_discussion(
+        &self,
+        chat_id: i64,
+        user_id: i64,
+        event_id: Uuid,
+        thread: DiscussionThread,
+    ) -> Result<(), Box<dyn Error>> {
+        self.repository
+            .write_discussion_thread(DiscussionThreadRequest {
+                event_id,
+                chat_id,
+                actor_id: user_id,
+                thread,
+            })
+            .await?;
+
+        Ok(())
+    }
+
+    // members whose progress is short of the spoiler are named in its warning
+    pub async fn spoiler(
+        &self,
+        chat_id: i64,
+        name: &str,
+        text: &str,
+        lang: &str,
+    ) -> Result<Spoiler, Box<dyn Error>> {
+        let events = self
+            .repository
+            .get_active_events(LastEventRequest { chat_id })
+            .await?
+            .events;
+
+        let (track, text) = split_track(&events, text);
+        let event = resolve_event(events, &track)?;
+
+        let Some((mark, text)) = SpoilerMark::split(&text) else {
+            return Err(Box::new(Err::WrongSpoiler));
+        };
+
+        let pages = event.work.as_ref().and_then(|work| work.pages);
+        let (label, percent) = match mark {
+            SpoilerMark::Chapter(chapter) => (
+                tr!(lang, "spoiler-label-chapter", chapter = chapter.as_str()),
+                None,
+            ),
+            SpoilerMark::Progress(ProgressMark::Percent(percent)) => (
+                tr!(lang, "spoiler-label-percent", percent = percent),
+                Some(percent),
+            ),
+            SpoilerMark::Progress(ProgressMark::Page(page)) => (
+                tr!(lang, "spoiler-label-page", page = page),
+                pages
+                    .filter(|pages| *pages > 0)
+                    .map(|pages| page.min(pages) * 100 / pages),
+            ),
+        };
+
+        let behind: Vec<String> = match percent {
+            Some(percent) => self
+                .repository
+                .get_progress(ProgressRequest {
+                    event_id: event.event_id,
+                })
+                .await?
+                .members
+                .into_iter()
+                .filter(|member| member.percent < percent)
+                .map(|member| format!("{} ({}%)", member.name, member.percent))
+                .collect(),
+            None => vec![],
+        };
+
+        let warning = match behind.is_empty() {
+            true => tr!(lang, "spoiler-warning", label = label.as_str()),
+            false => tr!(
+                lang,
+                "spoiler-behind",
+                members = behind.join(", "),
+                label = label.as_str()
+            ),
+        };
+
+        let text = tr!(
+            lang,
+            "spoiler-post",
+            name = markdown::escape(name),
+            label = markdown::escape(&label),
+            warning = markdown::escape(&warning),
+            text = markdown::escape(text)
+        );
+
+        Ok(Spoiler {
+            discussion: discussion_of(event, lang)?,
+            text,
+        })
+    }
+
+    // questions are taken until the event starts
+    pub async fn new_question(
+        &self,
+        chat_id: i64,
+        user_id: i64,
+        name: &str,
+        text: &str,
+        lang: &str,
+    ) -> Result<Asked, Box<dyn Error>> {
+        let events = self
+            .repository
+            .get_active_events(LastEventRequest { chat_id })
+            .await?
+            .events;
+
+        let (track, text) = split_track(&events, text);
+        let event = resolve_event(events, &track)?;
+
+        let (anonymous, question) = match text.split_once(char::is_whitespace) {
+            Some((first, rest)) if ANONYMOUS_MARKS.contains(&first.to_lowercase().as_str()) => {
+                (true, rest.trim())
+            }
+            _ => (false, text.as_str()),
+        };
+
+        if event.started_by.is_some() {
+            return Err(Box::new(Err::QuestionsClosed));
+        }
+
+        if question.chars().count() > MAX_QUESTION_LENGTH {
+            return Err(Box::new(Err::QuestionTooLong(MAX_QUESTION_LENGTH as i32)));
+        }
+
+        let count = self
+            .repository
+            .get_questions(QuestionsRequest {
+                event_id: event.event_id,
+            })
+            .await?
+            .questions
+            .len();
+
+        if count >= MAX_QUESTIONS {
+            return Err(Box::new(Err::QuestionLimitReached(MAX_QUESTIONS as i32)));
+        }
+
+        self.repository
+            .write_question(NewQuestionRequest {
+                id: Uuid::new_v4(),
+                event_id: event.event_id,
+                chat_id,
+                user_id,
+                name: (!anonymous).then(|| name.to_string()),
+                question: question.to_string(),
+            })
+            .await?;
+
+        let key = match anonymous {
+            true => "question-saved-anonymous",
+            false => "question-saved",
+        };
+
+        Ok(Asked {
+            text: tr!(lang, key, count = count + 1),
+            anonymous,
+        })
+    }
+
+    // the event's host or whoever ran /startclub, chat admins may mark questions too
+    pub async fn cover_question(
+        &self,
+        chat_id: i64,
+        user_id: i64,
+        privileged: bool,
+        event_id: Uuid,
+        number: i32,
+        lang: &str,
+    ) -> Result<QuestionList, Box<dyn Error>> {
+        let event = self
+            .repository
+            .get_event_chat(EventChatRequest { event_id })
+            .await?
+            .filter(|event| event.chat_id == chat_id);
+
+        let Some(event) = event else {
+            return Err(Box::new(Err::QuestionNotFound));
+        };
+
+        if !privileged && event.started_by != Some(user_id) && event.host_id != Some(user_id) {
+            return Err(Box::new(Err::HostOnly));
+        }
+
+        let question = self
+            .repository
+            .get_questions(QuestionsRequest { event_id })
+            .await?
+            .questions
+            .into_iter()
+            .find(|question| question.number == Some(number));
+
+        let Some(question) = question else {
+            return Err(Box::new(Err::QuestionNotFound));
+        };
+
+        self.repository
+            .write_question_covered(QuestionCoveredRequest {
+                question_id: question.id,
+                event_id,
+                chat_id,
+                actor_id: user_id,
+                number,
+                covered: !question.covered,
+            })
+            .await?;
+
+        self.question_list(event_id, lang).await
+    }
+
+    async fn question_list(
+        &self,
+        event_id: Uuid,
+        lang: &str,
+    ) -> Result<QuestionList, Box<dyn Error>> {
+        let questions = self
+            .repository
+            .get_questions(QuestionsRequest { event_id })
+            .await?
+            .questions;
+
+        let lines: Vec<String> = questions
+            .iter()
+            .map(|question| {
+                let line = match &question.name {
+                    Some(name) => tr!(
+                        lang,
+                        "question-line",
+                        number = question.number.unwrap_or_default(),
+                        question = markdown::escape(&question.question),
+                        name = markdown::escape(name)
+                    ),
+                    None => tr!(
+                        lang,
+                        "question-line-anonymous",
+                        number = question.number.unwrap_or_default(),
+                        question = markdown::escape(&question.question)
+                    ),
+                };
+
+                match question.covered {
+                    true => format!("~{}~", line),
+                    false => line,
+                }
+            })
+            .collect();
+
+        Ok(QuestionList {
+            event_id,
+            text: tr!(
+                lang,
+                "questions-title",
+                count = questions.len(),
+                lines = lines.join("\n")
+            ),
+            numbers: questions
+                .iter()
+                .map(|question| (question.number.unwrap_or_default(), question.covered))
+                .collect(),
+        })
+    }
+
+    // check-ins are marked as sent before they are delivered, same as reminders
+    pub async fn due_checkins(&self) -> Result<Vec<CheckIn>, Box<dyn Error>> {
+        let now = Utc::now();
+        let due = self
+            .repository
+            .get_due_checkins(DueCheckinsRequest {
+                now,
+                due_before: now - chrono::Duration::days(CHECKIN_INTERVAL_DAYS),
+            })
+            .await?
+            .checkins;
+
+        let mut checkins = vec![];
+
+        for checkin in due {
+            self.repository
+                .mark_checkin_sent(SentCheckinRequest {
+                    event_id: checkin.event_id,
+                    sent_at: now,
+                })
+                .await?;
+
+            let settings = self.settings(checkin.chat_id).await?;
+            let lang = i18n::resolve_language(settings.language.as_deref(), None);
+
+            let members = self
+                .repository
+                .get_progress(ProgressRequest {
+                    event_id: checkin.event_id,
+                })
+                .await?
+                .members;
+
+            let text = tr!(
+                lang,
+                "checkin",
+                noun = event_noun(&checkin.track, lang),
+                subject = checkin.subject.as_str()
+            );
+
+            let text = match members.is_empty() {
+                true => text,
+                false => tr!(
+                    lang,
+                    "checkin-progress",
+                    checkin = text,
+                    lines = progress_lines(&members, lang).join("\n")
+                ),
+            };
+
+            checkins.push(CheckIn {
+                chat_id: checkin.chat_id,
+                event_id: checkin.event_id,
+                text,
+            })
+        }
+
+        Ok(checkins)
+    }
+
+    pub async fn settings(&self, chat_id: i64) -> Result<ClubSettings, Box<dyn Error>> {
+        let settings = self
+            .repository
+            .get_club_settings(ClubSettingsRequest { chat_id })
+            .await?;
+
+        Ok(settings)
+    }
+
+    pub async fn update_settings(
+        &self,
+        user_id: i64,
+        settings: &ClubSettings,
+        change: SettingChange,
+    ) -> Result<ClubSettings, Box<dyn Error>> {
+        let mut settings = settings.clone();
+
+        match change {
+            SettingChange::ToggleDefaultInsights => {
+                settings.default_insights = !settings.default_insights
+            }
+            SettingChange::ToggleHostOnly => settings.host_only = !settings.host_only,
+            SettingChange::NextKind => {
+                settings.kind = match settings.kind {
+                    ClubKind::Books => ClubKind::Films,
+                    ClubKind::Films => ClubKind::BoardGames,
+                    ClubKind::BoardGames => ClubKind::Generic,
+                    ClubKind::Generic => ClubKind::Books,
+                }
+            }
+            SettingChange::Kind(name) => match ClubKind::parse(name.trim()) {
+                Some(kind) => settings.kind = kind,
+                None => return Err(Box::new(Err::WrongSettingValue)),
+            },
+            SettingChange::NextPickMode => {
+                settings.pick_mode = match settings.pick_mode {
+                    PickMode::Random => PickMode::Poll,
+                    PickMode::Poll => PickMode::Ranked,
+                    PickMode::Ranked => PickMode::Random,
+                }
+            }
+            SettingChange::NextSuggestionLimit => {
+                let position = SUGGESTION_LIMITS
+                    .iter()
+                    .position(|limit| *limit == settings.suggestion_limit);
+                settings.suggestion_limit = match position {
+                    Some(i) => SUGGESTION_LIMITS[(i + 1) % SUGGESTION_LIMITS.len()],
+                    None => SUGGESTION_LIMITS[0],
+                }
+            }
+            SettingChange::SuggestionLimit(limit) => {
+                if limit < 0 {
+                    return Err(Box::new(Err::WrongSettingValue));
+                }
+                settings.suggestion_limit = limit
+            }
+            SettingChange::NextLanguage => {
+                let position = LANGUAGES
+                    .iter()
+                    .position(|lang| Some(lang.to_string()) == settings.language);
+                settings.language = match position {
+                    None => Some(LANGUAGES[0].to_string()),
+                    Some(i) if i + 1 < LANGUAGES.len() => Some(LANGUAGES[i + 1].to_string()),
+                    Some(_) => None,
+                }
+            }
+            SettingChange::TimeZone(name) => match name.trim().parse::<Tz>() {
+                Ok(tz) => settings.time_zone = tz.name().to_string(),
+                Err(_) => return Err(Box::new(Err::UnknownTimeZone(name))),
+            },
+            SettingChange::ToggleReminder(offset) => {
+                if settings.reminder_offsets.contains(&offset) {
+                    settings
+                        .reminder_offsets
+                        .retain(|current| *current != offset)
+                } else {
+                    settings.reminder_offsets.push(offset);
+                    settings.reminder_offsets.sort_unstable_by(|a, b| b.cmp(a))
+                }
+            }
+        }
+
+        self.repository
+            .write_club_settings(UpdateClubSettingsRequest {
+                actor_id: user_id,
+                settings: settings.clone(),
+            })
+            .await?;
+
+        Ok(settings)
+    }
+
+    // reminders are marked as sent before they are delivered, a missed one is better than a repeated one
+    pub async fn due_reminders(&self) -> Result<Vec<Notice>, Box<dyn Error>> {
+        let due = self
+            .repository
+            .get_due_reminders(DueRemindersRequest { now: Utc::now() })
+            .await?
+            .reminders;
+
+        // several offsets become due at once for events created shortly before they start,
+        // only the closest one is worth sending
+        let mut by_event: HashMap<Uuid, Vec<DueReminder>> = HashMap::new();
+        for reminder in due {
+            by_event
+                .entry(reminder.event_id)
+                .or_default()
+                .push(reminder);
+        }
+
+        let mut reminders = vec![];
+
+        for (event_id, mut due) in by_event {
+            due.sort_by_key(|reminder| reminder.offset_minutes);
+
+            self.repository
+                .mark_reminders_sent(SentRemindersRequest {
+                    event_id,
+                    offsets: due.iter().map(|reminder| reminder.offset_minutes).collect(),
+                })
+                .await?;
+
+            let reminder = &due[0];
+            let settings = self.settings(reminder.chat_id).await?;
+            let lang = i18n::resolve_language(settings.language.as_deref(), None);
+
+            let text = tr!(
+                lang,
+                "reminder",
+                noun = event_noun(&reminder.track, lang),
+                offset = describe_offset(reminder.offset_minutes, lang, false),
+                date = beautify_date(local_date(reminder.event_date, settings.tz()), lang),
+            );
+
+            if reminder.subject.is_empty() {
+                reminders.push(Notice {
+                    chat_id: reminder.chat_id,
+                    text: with_details(
+                        tr!(lang, "reminder-not-picked", reminder = text),
+                        reminder,
+                        lang,
+                    ),
+                });
+
+                continue;
+            }
+
+            let text = tr!(
+                lang,
+                "reminder-subject",
+                reminder = text,
+                subject = reminder.subject.as_str()
+            );
+
+            // members who shared their progress and are slower than the days left allow
+            let expected = expected_progress(
+                reminder.picked_at,
+                reminder.event_date,
+                Utc::now().naive_utc(),
+            );
+            let behind: Vec<String> = self
+                .repository
+                .get_progress(ProgressRequest { event_id })
+                .await?
+                .members
+                .into_iter()
+                .filter(|member| member.percent < expected)
+                .map(|member| format!("{} ({}%)", member.name, member.percent))
+                .collect();
+
+            let text = match behind.is_empty() {
+                true => text,
+                false => tr!(
+                    lang,
+                    "reminder-behind",
+                    reminder = text,
+                    members = behind.join(", ")
+                ),
+            };
+
+            reminders.push(Notice {
+                chat_id: reminder.chat_id,
+                text: with_details(text, reminder, lang),
+            })
+        }
+
+        Ok(reminders)
+    }
+
+    pub async fn hosts(&self, chat_id: i64, lang: &str) -> Result<String, Box<dyn Error>> {
+        let rotation = self.repository.get_hosts(HostsRequest { chat_id }).await?;
+
+        if rotation.hosts.is_empty() {
+            return Ok(tr!(lang, "hosts-empty"));
+        }
+
+        let next = next_host_index(&rotation);
+        let lines: Vec<String> = rotation
+            .hosts
+            .iter()
+            .enumerate()
+            .map(|(i, host)| {
+                let key = match Some(i) == next {
+                    true => "hosts-line-next",
+                    false => "hosts-line",
+                };
+
+                tr!(lang, key, number = i + 1, name = host.name.as_str())
+            })
+            .collect();
+
+        Ok(tr!(lang, "hosts-title", lines = lines.join("\n")))
+    }
+
+    pub async fn add_host(
+        &self,
+        chat_id: i64,
+        actor_id: i64,
+        user_id: i64,
+        name: &str,
+        lang: &str,
+    ) -> Result<String, Box<dyn Error>> {
+        let resp = self
+            .repository
+            .write_host(NewHostRequest {
+                chat_id,
+                actor_id,
+                user_id,
+                name: name.to_string(),
+            })
+            .await;
+
+        if let Err(repository::Error::Conflict) = resp {
+            return Err(Box::new(Err::AlreadyHost(name.to_string())));
+        }
+
+        resp?;
+        let count = self
+            .repository
+            .get_hosts(HostsRequest { chat_id })
+            .await?
+            .hosts
+            .len();
+
+        Ok(tr!(lang, "host-added", name = name, number = count))
+    }
+
+    pub async fn remove_host(
+        &self,
+        chat_id: i64,
+        actor_id: i64,
+        target: HostRef,
+        lang: &str,
+    ) -> Result<String, Box<dyn Error>> {
+        let hosts = self
+            .repository
+            .get_hosts(HostsRequest { chat_id })
+            .await?
+            .hosts;
+
+        let host = match target {
+            HostRef::User(user_id) => hosts.into_iter().find(|host| host.user_id == user_id),
+            HostRef::Number(number) => hosts.into_iter().nth(number.wrapping_sub(1)),
+        };
+
+        let Some(host) = host else {
+            return Err(Box::new(Err::NotAHost));
+        };
+
+        self.repository
+            .delete_host(RemoveHostRequest {
+                chat_id,
+                actor_id,
+                user_id: host.user_id,
+            })
+            .await?;
+
+        Ok(tr!(lang, "host-removed", name = host.name))
+    }
+
+    // hands the event to another member, when both are in the rotation they trade places
+    // in it too, so the previous host takes the other one's turn later
+    pub async fn swap_host(
+        &self,
+        actor_id: i64,
+        privileged: bool,
+        args: &str,
+        host: Member,
+        settings: &ClubSettings,
+        lang: &str,
+    ) -> Result<String, Box<dyn Error>> {
+        let chat_id = settings.chat_id;
+        let events = self
+            .repository
+            .get_active_events(LastEventRequest { chat_id })
+            .await?
+            .events;
+
+        let track = args
+            .split_whitespace()
+            .next()
+            .map(str::to_lowercase)
+            .filter(|track| events.iter().any(|event| event.track == *track))
+            .unwrap_or_default();
+        let event = resolve_event(events, &track)?;
+        let previous = event.host.as_ref().map(|host| host.user_id);
+
+        if !privileged && previous != Some(actor_id) {
+            return Err(Box::new(Err::SwapNotAllowed));
+        }
+
+        let rotation = self
+            .repository
+            .get_hosts(HostsRequest { chat_id })
+            .await?
+            .hosts;
+        let position = |user_id: i64| {
+            rotation
+                .iter()
+                .find(|host| host.user_id == user_id)
+                .map(|host| host.position)
+        };
+
+        let positions = match (previous.and_then(position), position(host.user_id)) {
+            (Some(from), Some(to)) if from != to => vec![
+                HostPosition {
+                    user_id: previous.unwrap_or_default(),
+                    position: to,
+                },
+                HostPosition {
+                    user_id: host.user_id,
+                    position: from,
+                },
+            ],
+            _ => vec![],
+        };
+
+        self.repository
+            .write_event_host(SwapHostRequest {
+                event_id: event.event_id,
+                chat_id,
+                actor_id,
+                host: host.clone(),
+                positions,
+            })
+            .await?;
+
+        let tz = settings.tz();
+
+        Ok(tr!(
+            lang,
+            "host-swapped",
+            name = member_name(&host, lang),
+            noun = event_noun(&event.track, lang),
+            date = beautify_date(local_date(event.event_date, tz), lang)
+        ))
+    }
+
+    // the host's name when only they may pick or achieve the event and the member isn't them
+    pub async fn host_required(
+        &self,
+        user_id: i64,
+        track: &str,
+        settings: &ClubSettings,
+        lang: &str,
+    ) -> Result<Option<String>, Box<dyn Error>> {
+        let chat_id = settings.chat_id;
+        if !settings.host_only {
+            return Ok(None);
+        }
+
+        // an event that can't be resolved is left for the command itself to report
+        let host = match self.active_event(chat_id, track).await {
+            Ok(event) => event.host,
+            Err(_) => None,
+        };
+
+        Ok(host
+            .filter(|host| host.user_id != user_id)
+            .map(|host| member_name(&host, lang)))
+    }
+
+    // resolves the event a command refers to: the named track or the only active one
+    async fn active_event(
+        &self,
+        chat_id: i64,
+        track: &str,
+    ) -> Result<LastEventResponse, Box<dyn Error>> {
+        let events = self
+            .repository
+            .get_active_events(LastEventRequest { chat_id })
+            .await?
+            .events;
+
+        resolve_event(events, track)
+    }
+
+    pub async fn get_audit_log(
+        &self,
+        limit: i64,
+        settings: &ClubSettings,
+        lang: &str,
+    ) -> Result<String, Box<dyn Error>> {
+        let chat_id = settings.chat_id;
+        let entries = self
+            .repository
+            .get_audit_log(AuditLogRequest { chat_id, limit })
+            .await?
+            .entries;
+
+        if entries.is_empty() {
+            return Ok(tr!(lang, "log-empty"));
+        }
+
+        let tz = settings.tz();
+        let lines: Vec<String> = entries
+            .iter()
+            .map(|entry| describe_audit_entry(entry, tz, lang))
+            .collect();
+
+        Ok(tr!(
+            lang,
+            "log-title",
+            count = lines.len(),
+            lines = lines.join("\n")
+        ))
+    }
+
+    pub async fn language(&self, chat_id: i64, user_language: Option<&str>) -> &'static str {
+        let club_language = match self.settings(chat_id).await {
+            Ok(settings) => settings.language,
+            Err(_) => None,
+        };
+
+        i18n::resolve_language(club_language.as_deref(), user_language)
+    }
+}
+
+fn resolve_event(
+    mut events: Vec<LastEventResponse>,
+    track: &str,
+) -> Result<LastEventResponse, Box<dyn Error>> {
+    let track = track.trim().to_lowercase();
+
+    if !track.is_empty() {
+        return match events.into_iter().find(|event| event.track == track) {
+            Some(event) => Ok(event),
+            None => Err(Box::new(Err::NoActiveEventInTrack(track))),
+        };
+    }
+
+    match events.len() {
+        0 => Err(Box::new(Err::NoActiveEventFound)),
+        1 => Ok(events.remove(0)),
+        _ => {
+            let tracks: Vec<String> = events.into_iter().map(|event| event.track).collect();
+            Err(Box::new(Err::TrackRequired(tracks.join(", "))))
+        }
+    }
+}
+
+// "52.5200, 13.4050 Cafe Einstein" keeps the coordinates for a map pin, anything else is
+// taken as written, be it an address, a venue name or a video call link
+fn parse_location(text: &str) -> Option<Location> {
+    let text = text.trim();
+
+    if text.is_empty() {
+        return None;
+    }
+
+    match split_coordinates(text) {
+        Some((coordinates, "")) => Some(Location {
+            address: format!("{}, {}", coordinates.latitude, coordinates.longitude),
+            coordinates: Some(coordinates),
+        }),
+        Some((coordinates, address)) => Some(Location {
+            address: address.to_string(),
+            coordinates: Some(coordinates),
+        }),
+        None => Some(Location {
+            address: text.to_string(),
+            coordinates: None,
+        }),
+    }
+}
+
+// the coordinates are "lat,lon" or "lat, lon" at the start, what follows is the address
+fn split_coordinates(text: &str) -> Option<(Coordinates, &str)> {
+    let words: Vec<&str> = text.split_whitespace().collect();
+
+    (1..=2)
+        .filter(|count| words.len() >= *count)
+        .find_map(|count| {
+            let pair = words[..count].concat();
+            let (latitude, longitude) = pair.trim_end_matches(',').split_once(',')?;
+            let latitude: f64 = latitude.parse().ok()?;
+            let longitude: f64 = longitude.parse().ok()?;
+
+            if !(-90.0..=90.0).contains(&latitude) || !(-180.0..=180.0).contains(&longitude) {
+                return None;
+            }
+
+            let address = skip_words(text, count).trim_start_matches(',').trim();
+
+            Some((
+                Coordinates {
+                    latitude,
+                    longitude,
+                },
+                address,
+            ))
+        })
+}
+
+// what's left of the text after its first words, keeping the spacing of the rest
+fn skip_words(text: &str, count: usize) -> &str {
+    (0..count).fold(text.trim_start(), |rest, _| {
+        rest.trim_start_matches(|c: char| !c.is_whitespace())
+            .trim_start()
+    })
+}
+
+fn calendar_entry(event: CalendarEvent, lang: &str) -> ical::Entry {
+    let noun = event_noun(&event.track, lang);
+    let summary = match event.subject.is_empty() {
+        true => tr!(lang, "calendar-summary-not-picked", noun = noun),
+        false => tr!(
+            lang,
+            "calendar-summary",
+            noun = noun,
+            subject = event.subject.as_str()
+        ),
+    };
+
+    let mut details = vec![];
+    if let Some(host) = &event.host {
+        details.push(tr!(lang, "calendar-host", host = member_name(host, lang)));
+    }
+    if let Some(link) = &event.insights_link {
+        details.push(tr!(lang, "calendar-insights", link = link.as_str()));
+    }
+
+    ical::Entry {
+        uid: format!("{}@clubvent", event.event_id),
+        start: event.event_date,
+        summary,
+        description: (!details.is_empty()).then(|| details.join("\n")),
+        location: event.location,
+        url: event.insights_link,
+        sequence: event.revision,
+        last_modified: event.updated_at,
+    }
+}
+
+fn venue(location: Location, noun: &str, date: &str, lang: &str) -> Option<Venue> {
+    Some(Venue {
+        coordinates: location.coordinates?,
+        title: location.address,
+        address: tr!(lang, "location-venue", noun = noun, date = date),
+    })
+}
+
+// splits "/suggest books Dune" into track and suggestion when the first word names an active track,
+// with a single active event there's nothing to choose and "/suggest Main Street" stays whole
+fn split_track(events: &[LastEventResponse], text: &str) -> (String, String) {
+    let text = text.trim();
+
+    if events.len() < 2 {
+        return (String::new(), text.to_string());
+    }
+
+    if let Some((first, rest)) = text.split_once(char::is_whitespace) {
+        let first = first.to_lowercase();
+        if !rest.trim().is_empty() && events.iter().any(|event| event.track == first) {
+            return (first, rest.trim().to_string());
+        }
+    }
+
+    (String::new(), text.to_string())
+}
+
+fn discussion_of(event: LastEventResponse, lang: &str) -> Result<Discussion, Box<dyn Error>> {
+    if event.subject.is_empty() {
+        return Err(Box::new(Err::SubjectNotPicked));
+    }
+
+    Ok(Discussion {
+        event_id: event.event_id,
+        topic: tr!(lang, "discussion-topic", subject = event.subject.as_str())
+            .chars()
+            .take(MAX_TOPIC_NAME_LENGTH)
+            .collect(),
+        announcement: tr!(
+            lang,
+            "discussion-announcement",
+            subject = event.subject.as_str()
+        ),
+        thread: event.discussion,
+    })
+}
+
+fn is_valid_track_name(track: &str) -> bool {
+    !track.is_empty() && track.chars().count() <= 32 && track.chars().all(char::is_alphanumeric)
+}
+
+fn event_noun(track: &str, lang: &str) -> String {
+    tr!(lang, "event-noun", track = track)
+}
+
+// renders event info as MarkdownV2, so every value coming from the db is escaped
+fn describe_event(
+    event: LastEventResponse,
+    members: &[MemberProgress],
+    tz: Tz,
+    lang: &str,
+) -> String {
+    let date = markdown::escape(&beautify_date(local_date(event.event_date, tz), lang));
+    let noun = markdown::escape(&event_noun(&event.track, lang));
+    let details = |message: String| {
+        let message = match &event.host {
+            Some(host) => tr!(
+                lang,
+                "current-host",
+                message = message,
+                host = markdown::escape(&member_name(host, lang))
+            ),
+            None => message,
+        };
+
+        match &event.location {
+            Some(location) => tr!(
+                lang,
+                "current-location",
+                message = message,
+                location = markdown::escape(&location.address)
+            ),
+            None => message,
+        }
+    };
+
+    if event.subject.is_empty() {
+        return details(tr!(lang, "current-not-picked", noun = noun, date = date));
+    }
+
+    let message = details(tr!(
+        lang,
+        "current-picked",
+        noun = noun,
+        date = date,
+        subject = markdown::escape(&event.subject)
+    ));
+
+    let message = match (event.with_insights, event.insights_link) {
+        (true, Some(link)) => tr!(
+            lang,
+            "current-insights",
+            message = message,
+            link = markdown::escape_url(&link)
+        ),
+        (true, None) => tr!(lang, "current-insights-pending", message = message),
+        (false, _) => message,
+    };
+
+    if members.is_empty() {
+        return message;
+    }
+
+    tr!(
+        lang,
+        "current-progress",
+        message = message,
+        lines = markdown::escape(&progress_lines(members, lang).join("\n"))
+    )
+}
+
+fn progress_lines(members: &[MemberProgress], lang: &str) -> Vec<String> {
+    members
+        .iter()
+        .map(|member| {
+            let filled = (member.percent * PROGRESS_BAR_LENGTH / 100).clamp(0, PROGRESS_BAR_LENGTH);
+            let bar = format!(
+                "{}{}",
+                "▓".repeat(filled as usize),
+                "░".repeat((PROGRESS_BAR_LENGTH - filled) as usize)
+            );
+
+            tr!(
+                lang,
+                "progress-line",
+                bar = bar,
+                percent = member.percent,
+                name = member.name.as_str()
+            )
+        })
+        .collect()
+}
+
+fn average_stars(ratings: &[&Rating]) -> f64 {
+    let total: i32 = ratings.iter().map(|rating| rating.stars).sum();
+    total as f64 / ratings.len() as f64
+}
+
+fn describe_achieved_event(event: &AchievedEvent, tz: Tz, lang: &str) -> String {
+    let ratings: Vec<&Rating> = event.ratings.iter().collect();
+    let rating = match ratings.is_empty() {
+        true => tr!(lang, "history-not-rated"),
+        false => tr!(
+            lang,
+            "history-rating",
+            average = format!("{:.1}", average_stars(&ratings)),
+            count = ratings.len()
+        ),
+    };
+
+    let mut lines = vec![tr!(
+        lang,
+        "history-line",
+        date = local_date(event.event_date, tz)
+            .format("%Y.%m.%d")
+            .to_string(),
+        subject = event.subject.as_str(),
+        rating = rating
+    )];
+
+    for rating in &event.ratings {
+        if let Some(review) = &rating.review {
+            lines.push(tr!(
+                lang,
+                "history-review",
+                name = rating.name.as_str(),
+                stars = rating.stars,
+                review = review.as_str()
+            ));
+        }
+    }
+
+    lines.join("\n")
+}
+
+// best rated picks, the member giving the lowest stars and how the picks of each suggester did
+fn describe_stats(events: &[AchievedEvent], lang: &str) -> String {
+    let rated: Vec<&AchievedEvent> = events
+        .iter()
+        .filter(|event| !event.ratings.is_empty())
+        .collect();
+
+    if rated.is_empty() {
+        return tr!(lang, "stats-empty");
+    }
+
+    let mut picks: Vec<(&AchievedEvent, f64)> = rated
+        .iter()
+        .map(|event| {
+            let ratings: Vec<&Rating> = event.ratings.iter().collect();
+            (*event, average_stars(&ratings))
+        })
+        .collect();
+    picks.sort_by(|(a, a_stars), (b, b_stars)| {
+        b_stars
+            .total_cmp(a_stars)
+            .then(b.ratings.len().cmp(&a.ratings.len()))
+    });
+
+    let best: Vec<String> = picks
+        .iter()
+        .take(STATS_TOP_PICKS)
+        .map(|(event, stars)| {
+            tr!(
+                lang,
+                "stats-best-line",
+                subject = event.subject.as_str(),
+                average = format!("{:.1}", stars),
+                count = event.ratings.len()
+            )
+        })
+        .collect();
+
+    let mut sections = vec![tr!(lang, "stats-best", lines = best.join("\n"))];
+
+    // the latest name a member rated with is the one shown
+    let mut critics: Vec<(i64, &str, Vec<&Rating>)> = vec![];
+    for rating in rated.iter().rev().flat_map(|event| &event.ratings) {
+        match critics
+            .iter_mut()
+            .find(|(user_id, _, _)| *user_id == rating.user_id)
+        {
+            Some((_, name, ratings)) => {
+                *name = rating.name.as_str();
+                ratings.push(rating);
+            }
+            None => critics.push((rating.user_id, rating.name.as_str(), vec![rating])),
+        }
+    }
+
+    let critic = critics.iter().min_by(|(_, _, a), (_, _, b)| {
+        average_stars(a)
+            .total_cmp(&average_stars(b))
+            .then(b.len().cmp(&a.len()))
+    });
+
+    if let Some((_, name, ratings)) = critic {
+        sections.push(tr!(
+            lang,
+            "stats-critic",
+            name = *name,
+            average = format!("{:.1}", average_stars(ratings)),
+            count = ratings.len()
+        ));
+    }
+
+    let mut suggesters: Vec<(&Member, usize, Vec<&Rating>)> = vec![];
+    for event in &rated {
+        for member in &event.suggested_by {
+            match suggesters
+                .iter_mut()
+                .find(|(known, _, _)| known.user_id == member.user_id)
+            {
+                Some((_, picks, ratings)) => {
+                    *picks += 1;
+                    ratings.extend(&event.ratings);
+                }
+                None => suggesters.push((member, 1, event.ratings.iter().collect())),
+            }
+        }
+    }
+    suggesters.sort_by(|(_, _, a), (_, _, b)| average_stars(b).total_cmp(&average_stars(a)));
+
+    if !suggesters.is_empty() {
+        let lines: Vec<String> = suggesters
+            .iter()
+            .map(|(member, picks, ratings)| {
+                let name = member_name(member, lang);
+
+                tr!(
+                    lang,
+                    "stats-suggester-line",
+                    name = name,
+                    average = format!("{:.1}", average_stars(ratings)),
+                    picks = *picks
+                )
+            })
+            .collect();
+
+        sections.push(tr!(lang, "stats-suggesters", lines = lines.join("\n")));
+    }
+
+    format!("{}\n\n{}", tr!(lang, "stats-title"), sections.join("\n\n"))
+}
+
+// how far a member keeping an even pace since the pick should be by now,
+// all the way when the pick date isn't known
+fn expected_progress(
+    picked_at: Option<NaiveDateTime>,
+    event_date: NaiveDateTime,
+    now: NaiveDateTime,
+) -> i32 {
+    let Some(picked_at) = picked_at else {
+        return 100;
+    };
+
+    let total = (event_date - picked_at).num_minutes();
+    if total <= 0 {
+        return 100;
+    }
+
+    let elapsed = (now - picked_at).num_minutes().clamp(0, total);
+    (elapsed * 100 / total) as i32
+}
+
+// everyone who suggested something takes part
+pub fn skipped_note(skipped: usize, kind: ClubKind, lang: &str) -> String {
+    tr!(
+        lang,
+        "pick-unknown-skipped",
+        count = skipped,
+        kind = kind.as_str()
+    )
+}
+
+fn participants(suggestions: &[EventSuggestion]) -> Vec<i64> {
+    let mut participants: Vec<i64> = suggestions.iter().map(|s| s.user_id).collect();
+    participants.sort_unstable();
+    participants.dedup();
+    participants
+}
+
+// everyone who suggested the same thing as the pick, they are credited with its ratings
+fn suggesters(suggestions: &[EventSuggestion], picked: &EventSuggestion) -> Vec<Member> {
+    let key = suggestion_key(picked);
+    let mut members: Vec<Member> = vec![];
+
+    for suggestion in suggestions {
+        if suggestion_key(suggestion) == key
+            && !members
+                .iter()
+                .any(|member| member.user_id == suggestion.user_id)
+        {
+            members.push(Member {
+                user_id: suggestion.user_id,
+                name: suggestion.name.clone(),
+            })
+        }
+    }
+
+    members
+}
+
+// suggestions resolved to the same work count together however they were written
+fn suggestion_key(suggestion: &EventSuggestion) -> String {
+    match (&suggestion.work, &suggestion.identifier) {
+        (Some(work), _) => format!("work:{}", work.key),
+        (None, Some(identifier)) => format!("{}:{}", identifier.kind(), identifier.value()),
+        (None, None) => suggestion.suggestion.trim().to_lowercase(),
+    }
+}
+
+// the canonical title when the suggestion was resolved, the raw text otherwise
+fn subject_of(suggestion: &EventSuggestion, lang: &str) -> String {
+    match &suggestion.work {
+        Some(work) => work_title(work, lang),
+        None => raw_title(&suggestion.suggestion, suggestion.identifier.as_ref(), lang),
+    }
+}
+
+// a bare link the catalog couldn't resolve is shown by the id it names, not as a url
+fn raw_title(text: &str, identifier: Option<&Identifier>, lang: &str) -> String {
+    let Some(identifier) = identifier else {
+        return text.to_string();
+    };
+
+    // isbns may be written in groups, "ISBN 0 441 17271 7"
+    let bare = text.split_whitespace().all(|word| {
+        word.trim_end_matches(':').eq_ignore_ascii_case("isbn")
+            || word
+                .chars()
+                .all(|c| c.is_ascii_digit() || c == '-' || c == 'X')
+            || identifier::detect(word).as_ref() == Some(identifier)
+    });
+
+    match bare {
+        true => tr!(
+            lang,
+            "work-identifier",
+            kind = identifier.kind(),
+            value = identifier.value()
+        ),
+        false => text.to_string(),
+    }
+}
+
+fn distinct(suggestions: &[EventSuggestion]) -> Vec<&EventSuggestion> {
+    let mut keys: Vec<String> = vec![];
+
+    suggestions
+        .iter()
+        .filter(|suggestion| {
+            let key = suggestion_key(suggestion);
+            let new = !keys.contains(&key);
+            keys.push(key);
+            new
+        })
+        .collect()
+}
+
+fn work_title(work: &Work, lang: &str) -> String {
+    match (&work.director, &work.author) {
+        (Some(director), _) => tr!(
+            lang,
+            "work-title-director",
+            title = work.title.as_str(),
+            director = director.as_str()
+        ),
+        (None, Some(author)) => tr!(
+            lang,
+            "work-title",
+            title = work.title.as_str(),
+            author = author.as_str()
+        ),
+        (None, None) => work.title.clone(),
+    }
+}
+
+// title, author and whatever else the catalog knows about the kind of work, one line each
+pub fn describe_work(work: &Work, kind: ClubKind, lang: &str) -> String {
+    let mut lines = vec![work_title(work, lang)];
+    let mut facts = vec![];
+
+    if let Some(year) = work.year {
+        facts.push(tr!(
+            lang,
+            "work-year",
+            kind = kind.as_str(),
+            year = year.to_string()
+        ));
+    }
+
+    if let Some(pages) = work.pages {
+        facts.push(tr!(lang, "work-pages", pages = pages));
+    }
+
+    if let Some(minutes) = work.runtime {
+        facts.push(tr!(
+            lang,
+            "work-runtime",
+            kind = kind.as_str(),
+            minutes = minutes
+        ));
+    }
+
+    if !facts.is_empty() {
+        lines.push(facts.join(", "));
+    }
+
+    if let Some(cover_url) = &work.cover_url {
+        lines.push(tr!(lang, "work-cover", url = cover_url.as_str()));
+    }
+
+    lines.join("\n")
+}
+
+// what a pick filter limits, pages of a book or minutes of a film or a game
+fn work_size(work: &Work, kind: ClubKind) -> Option<i32> {
+    match kind {
+        ClubKind::Books => work.pages,
+        ClubKind::Films | ClubKind::BoardGames => work.runtime,
+        ClubKind::Generic => None,
+    }
+}
+
+// suggestion proposed by most members wins, ties are broken randomly
+fn most_suggested(suggestions: &[EventSuggestion]) -> &EventSuggestion {
+    let mut members: HashMap<String, Vec<i64>> = HashMap::new();
+
+    for suggestion in suggestions {
+        let voters = members.entry(suggestion_key(suggestion)).or_default();
+        if !voters.contains(&suggestion.user_id) {
+            voters.push(suggestion.user_id)
+        }
+    }
+
+    let most = members.values().map(Vec::len).max().unwrap_or(0);
+    let leaders: Vec<&EventSuggestion> = suggestions
+        .iter()
+        .filter(|suggestion| members[&suggestion_key(suggestion)].len() == most)
+        .collect();
+
+    leaders.choose(&mut rand::thread_rng()).unwrap()
+}
+
+fn poll_option(suggestion: &str) -> String {
+    suggestion
+        .trim()
+        .chars()
+        .take(MAX_POLL_OPTION_LENGTH)
+        .collect()
+}
+
+fn poll_options(suggestions: &[EventSuggestion], lang: &str) -> Vec<String> {
+    let mut options: Vec<String> = vec![];
+    let mut keys: Vec<String> = vec![];
+
+    for suggestion in suggestions {
+        let option = poll_option(&subject_of(suggestion, lang));
+        let key = suggestion_key(suggestion);
+        if !keys.contains(&key)
+            && !options
+                .iter()
+                .any(|o| o.to_lowercase() == option.to_lowercase())
+        {
+            options.push(option);
+            keys.push(key);
+        }
+    }
 
-fn unescape_hyphen(text: &str) -> String {
-    text.replace("\\-", "-")
+    if options.len() > MAX_POLL_OPTIONS {
+        options = options
+            .choose_multiple(&mut rand::thread_rng(), MAX_POLL_OPTIONS)
+            .cloned()
+            .collect();
+    }
+
+    options
+}
+
+pub fn describe_offset(minutes: i32, lang: &str, short: bool) -> String {
+    let (count, unit) = match minutes {
+        m if m % 10080 == 0 => (m / 10080, "weeks"),
+        m if m % 1440 == 0 => (m / 1440, "days"),
+        m if m % 60 == 0 => (m / 60, "hours"),
+        m => (m, "minutes"),
+    };
+
+    let key = match short {
+        true => format!("duration-{}-short", unit),
+        false => format!("duration-{}", unit),
+    };
+
+    tr!(lang, &key, count = count)
+}
+
+// doubles with every attempt, from OUTBOX_RETRY_DELAY_SECONDS up to an hour
+fn outbox_backoff(attempts: i32) -> chrono::Duration {
+    let delay = OUTBOX_RETRY_DELAY_SECONDS << (attempts - 1).clamp(0, 16);
+
+    chrono::Duration::seconds(delay.min(OUTBOX_MAX_RETRY_DELAY_SECONDS))
+}
+
+fn local_date(ts: NaiveDateTime, tz: Tz) -> NaiveDateTime {
+    tz.from_utc_datetime(&ts).naive_local()
+}
+
+// MarkdownV2 reminder with the place, the host is mentioned so telegram pings them
+fn with_details(reminder: String, due: &DueReminder, lang: &str) -> String {
+    let reminder = match &due.location {
+        Some(location) => tr!(
+            lang,
+            "reminder-location",
+            reminder = reminder,
+            location = location.address.as_str()
+        ),
+        None => reminder,
+    };
+    let reminder = markdown::escape(&reminder);
+
+    match &due.host {
+        Some(host) => tr!(
+            lang,
+            "reminder-host",
+            reminder = reminder,
+            host = markdown::user_mention(host.user_id, &member_name(host, lang))
+        ),
+        None => reminder,
+    }
+}
+
+fn member_name(member: &Member, lang: &str) -> String {
+    match &member.name {
+        Some(name) => name.clone(),
+        None => tr!(lang, "member-fallback", user = member.user_id.to_string()),
+    }
+}
+
+// the one after the latest event's host, the first one when that host left the rotation
+fn next_host_index(rotation: &HostsResponse) -> Option<usize> {
+    if rotation.hosts.is_empty() {
+        return None;
+    }
+
+    let last = rotation
+        .last_host
+        .and_then(|last| rotation.hosts.iter().position(|host| host.user_id == last));
+
+    Some(last.map_or(0, |i| (i + 1) % rotation.hosts.len()))
+}
+
+fn next_host(rotation: HostsResponse) -> Option<Member> {
+    let next = next_host_index(&rotation)?;
+
+    rotation.hosts.into_iter().nth(next).map(|host| Member {
+        user_id: host.user_id,
+        name: Some(host.name),
+    })
 }
 
-fn beautify_date(ts: NaiveDateTime) -> String {
-    let day = match ts.day() {
-        1 | 21 | 31 => format!("{}st", ts.day()),
-        2 | 22 => format!("{}nd", ts.day()),
-        3 | 23 => format!("{}rd", ts.day()),
-        _ => format!("{}th", ts.day()),
+fn describe_audit_entry(entry: &AuditLogEntry, tz: Tz, lang: &str) -> String {
+    let field = |name: &str| entry.payload[name].as_str().unwrap_or_default().to_string();
+
+    let action = match AuditAction::parse(entry.action.as_str()) {
+        Some(AuditAction::ClubRegistered) => tr!(lang, "audit-club-registered"),
+        Some(AuditAction::EventCreated) => match field("track").as_str() {
+            "" | DEFAULT_TRACK => tr!(lang, "audit-event-created", date = field("event_date")),
+            track => tr!(
+                lang,
+                "audit-track-event-created",
+                track = track,
+                date = field("event_date")
+            ),
+        },
+        Some(AuditAction::SuggestionAdded) => {
+            tr!(
+                lang,
+                "audit-suggestion-added",
+                suggestion = field("suggestion")
+            )
+        }
+        Some(AuditAction::InsightsToggled) => match entry.payload["insights"].as_bool() {
+            Some(true) => tr!(lang, "audit-insights-on"),
+            _ => tr!(lang, "audit-insights-off"),
+        },
+        Some(AuditAction::SubjectPicked) => {
+            tr!(lang, "audit-subject-picked", subject = field("subject"))
+        }
+        Some(AuditAction::EventStarted) => tr!(lang, "audit-event-started"),
+        Some(AuditAction::EventAchieved) => tr!(lang, "audit-event-achieved"),
+        Some(AuditAction::SettingsChanged) => tr!(lang, "audit-settings-changed"),
+        Some(AuditAction::HostAdded) => tr!(lang, "audit-host-added", name = field("name")),
+        Some(AuditAction::HostRemoved) => tr!(lang, "audit-host-removed", name = field("name")),
+        Some(AuditAction::HostSwapped) => {
+            tr!(lang, "audit-host-swapped", name = field("name"))
+        }
+        Some(AuditAction::LocationChanged) => {
+            tr!(lang, "audit-location-changed", location = field("location"))
+        }
+        Some(AuditAction::RatingGiven) => tr!(
+            lang,
+            "audit-rating-given",
+            subject = field("subject"),
+            stars = entry.payload["stars"].as_i64().unwrap_or_default()
+        ),
+        Some(AuditAction::ReviewAdded) => {
+            tr!(lang, "audit-review-added", subject = field("subject"))
+        }
+        Some(AuditAction::CalendarReset) => match entry.payload["reset"].as_bool() {
+            Some(true) => tr!(lang, "audit-calendar-reset"),
+            _ => tr!(lang, "audit-calendar-created"),
+        },
+        Some(AuditAction::ProgressMarked) => tr!(
+            lang,
+            "audit-progress-marked",
+            percent = entry.payload["percent"].as_i64().unwrap_or_default()
+        ),
+        Some(AuditAction::PollStarted) => tr!(lang, "audit-poll-started"),
+        Some(AuditAction::WorkChosen) => tr!(lang, "audit-work-chosen", title = field("title")),
+        Some(AuditAction::DiscussionOpened) => tr!(lang, "audit-discussion-opened"),
+        Some(AuditAction::QuestionAsked) => tr!(lang, "audit-question-asked"),
+        Some(AuditAction::QuestionCovered) => {
+            let number = entry.payload["number"].as_i64().unwrap_or_default();
+
+            match entry.payload["covered"].as_bool() {
+                Some(true) => tr!(lang, "audit-question-covered", number = number),
+                _ => tr!(lang, "audit-question-uncovered", number = number),
+            }
+        }
+        None => entry.action.clone(),
     };
 
-    format!(
-        "{}, {} of {} at {}",
-        ts.format("%A"),
-        day,
-        ts.format("%B"),
-        ts.format("%H:%M")
+    tr!(
+        lang,
+        "log-line",
+        date = entry
+            .created_at
+            .with_timezone(&tz)
+            .format("%Y.%m.%d %H:%M")
+            .to_string(),
+        user = entry.actor_id.to_string(),
+        action = action
+    )
+}
+
+fn beautify_date(ts: NaiveDateTime, lang: &str) -> String {
+    tr!(
+        lang,
+        "date-full",
+        weekday = tr!(
+            lang,
+            &format!("weekday-{}", ts.weekday().number_from_monday())
+        ),
+        day = ts.day(),
+        month = tr!(lang, &format!("month-{}", ts.month())),
+        time = ts.format("%H:%M").to_string()
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sqlite::new_sqlite_repository;
+
+    fn event(track: &str) -> LastEventResponse {
+        LastEventResponse {
+            event_id: Uuid::new_v4(),
+            track: track.to_string(),
+            event_date: Utc::now().naive_utc(),
+            subject: String::new(),
+            with_insights: false,
+            insights_link: None,
+            poll_message_id: None,
+            work: None,
+            discussion: None,
+            started_by: None,
+            host: None,
+            location: None,
+            revision: 0,
+            updated_at: Utc::now().naive_utc(),
+        }
+    }
+
+    #[test]
+    fn split_track_keeps_text_with_one_active_event() {
+        let events = [event("main")];
+
+        assert_eq!(
+            split_track(&events, "Main Street"),
+            (String::new(), "Main Street".to_string())
+        );
+    }
+
+    #[test]
+    fn split_track_takes_track_with_several_active_events() {
+        let events = [event("main"), event("films")];
+
+        assert_eq!(
+            split_track(&events, "Films  Solaris "),
+            ("films".to_string(), "Solaris".to_string())
+        );
+        assert_eq!(
+            split_track(&events, "Main"),
+            (String::new(), "Main".to_string())
+        );
+        assert_eq!(
+            split_track(&events, "Dune Messiah"),
+            (String::new(), "Dune Messiah".to_string())
+        );
+    }
+
+    #[test]
+    fn progress_mark_reads_percents_and_pages() {
+        assert!(matches!(
+            ProgressMark::parse("45%"),
+            Some(ProgressMark::Percent(45))
+        ));
+        assert!(matches!(
+            ProgressMark::parse(" 100 % "),
+            Some(ProgressMark::Percent(100))
+        ));
+        assert!(matches!(
+            ProgressMark::parse("0%"),
+            Some(ProgressMark::Percent(0))
+        ));
+        assert!(matches!(
+            ProgressMark::parse("p.120"),
+            Some(ProgressMark::Page(120))
+        ));
+        assert!(matches!(
+            ProgressMark::parse("P. 120"),
+            Some(ProgressMark::Page(120))
+        ));
+        assert!(matches!(
+            ProgressMark::parse("page 7"),
+            Some(ProgressMark::Page(7))
+        ));
+        assert!(matches!(
+            ProgressMark::parse("стр. 300"),
+            Some(ProgressMark::Page(300))
+        ));
+    }
+
+    #[test]
+    fn progress_mark_refuses_out_of_range() {
+        for text in ["101%", "-1%", "p.0", "p. -3", "стр 0"] {
+            assert!(ProgressMark::parse(text).is_none(), "{}", text);
+        }
+    }
+
+    #[test]
+    fn progress_mark_refuses_garbage() {
+        for text in ["", "%", "p.", "120", "half", "45%%", "p.12a", "chapter 3"] {
+            assert!(ProgressMark::parse(text).is_none(), "{}", text);
+        }
+    }
+
+    fn chapter(split: Option<(SpoilerMark, &str)>) -> Option<(String, &str)> {
+        match split? {
+            (SpoilerMark::Chapter(chapter), rest) => Some((chapter, rest)),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn spoiler_mark_splits_chapters() {
+        assert_eq!(
+            chapter(SpoilerMark::split("ch. 12 Snape kills Dumbledore")),
+            Some(("12".to_string(), "Snape kills Dumbledore"))
+        );
+        assert_eq!(
+            chapter(SpoilerMark::split("  12.3   the twist ")),
+            Some(("12.3".to_string(), "the twist"))
+        );
+        assert_eq!(
+            chapter(SpoilerMark::split("гл. IV он жив")),
+            Some(("IV".to_string(), "он жив"))
+        );
+    }
+
+    #[test]
+    fn spoiler_mark_splits_progress() {
+        assert!(matches!(
+            SpoilerMark::split("45% the twist"),
+            Some((
+                SpoilerMark::Progress(ProgressMark::Percent(45)),
+                "the twist"
+            ))
+        ));
+        assert!(matches!(
+            SpoilerMark::split("стр. 120 он жив"),
+            Some((SpoilerMark::Progress(ProgressMark::Page(120)), "он жив"))
+        ));
+    }
+
+    #[test]
+    fn spoiler_mark_refuses_out_of_range() {
+        for text in ["101% the twist", "p. 0 the twist", "-5% the twist"] {
+            assert!(SpoilerMark::split(text).is_none(), "{}", text);
+        }
+    }
+
+    #[test]
+    fn spoiler_mark_refuses_garbage() {
+        // a mark alone has no spoiler to hide, and lowercase words aren't numerals
+        for text in [
+            "",
+            "ch. 12",
+            "45%",
+            "did you see it",
+            "Snape dies",
+            "ch. twelve it ends",
+        ] {
+            assert!(SpoilerMark::split(text).is_none(), "{}", text);
+        }
+    }
+
+    #[test]
+    fn parse_location_keeps_coordinates() {
+        let location = parse_location("52.5200, 13.4050 Cafe Einstein").unwrap();
+        let coordinates = location.coordinates.unwrap();
+        assert_eq!(location.address, "Cafe Einstein");
+        assert_eq!(coordinates.latitude, 52.52);
+        assert_eq!(coordinates.longitude, 13.405);
+
+        let location = parse_location("-33.8568,151.2153").unwrap();
+        assert_eq!(location.address, "-33.8568, 151.2153");
+        assert!(location.coordinates.is_some());
+
+        let location = parse_location("Main Street 1").unwrap();
+        assert_eq!(location.address, "Main Street 1");
+        assert!(location.coordinates.is_none());
+    }
+
+    #[test]
+    fn parse_location_takes_out_of_range_coordinates_as_written() {
+        for text in ["91, 13.4 Cafe", "52.5, 181", "-90.5,0"] {
+            let location = parse_location(text).unwrap();
+            assert_eq!(location.address, text);
+            assert!(location.coordinates.is_none(), "{}", text);
+        }
+    }
+
+    #[test]
+    fn parse_location_takes_garbage_as_written() {
+        assert!(parse_location("").is_none());
+        assert!(parse_location("   ").is_none());
+
+        for text in ["north, south", "1,2,3 Cafe", "https://meet.example/club"] {
+            let location = parse_location(text).unwrap();
+            assert_eq!(location.address, text);
+            assert!(location.coordinates.is_none(), "{}", text);
+        }
+    }
+
+    fn rating(user_id: i64, name: &str, stars: i32) -> Rating {
+        Rating {
+            user_id,
+            name: name.to_string(),
+            stars,
+            review: None,
+        }
+    }
+
+    fn achieved(subject: &str, suggested_by: i64, ratings: Vec<Rating>) -> AchievedEvent {
+        AchievedEvent {
+            event_id: Uuid::new_v4(),
+            event_date: Utc::now().naive_utc(),
+            subject: subject.to_string(),
+            suggested_by: vec![Member {
+                user_id: suggested_by,
+                name: Some(format!("member {}", suggested_by)),
+            }],
+            ratings,
+        }
+    }
+
+    #[test]
+    fn describe_stats_of_an_empty_club() {
+        assert_eq!(describe_stats(&[], "en"), tr!("en", "stats-empty"));
+        assert_eq!(
+            describe_stats(&[achieved("Dune", 1, vec![])], "en"),
+            tr!("en", "stats-empty")
+        );
+    }
+
+    #[test]
+    fn describe_stats_breaks_ties_by_rating_count() {
+        // every average is 4, the pick and the critic with more ratings come first
+        let events = [
+            achieved("Solaris", 1, vec![rating(10, "Ann", 4)]),
+            achieved("Dune", 2, vec![rating(10, "Ann", 4), rating(11, "Bob", 4)]),
+        ];
+
+        let stats = describe_stats(&events, "en");
+
+        assert!(
+            stats.contains("Dune: 4.0★ (2)\nSolaris: 4.0★ (1)"),
+            "{}",
+            stats
+        );
+        assert!(
+            stats.contains("Harshest critic: Ann, 4.0★ on average over 2 ratings"),
+            "{}",
+            stats
+        );
+    }
+
+    const CHAT_ID: i64 = -100;
+
+    // a club with one open event and the fixture catalog
+    async fn club(dir: &tempfile::TempDir, kind: ClubKind) -> (Service, ClubSettings) {
+        let path = dir.path().join("club.db");
+        let repository = new_sqlite_repository(path.to_str().unwrap()).await.unwrap();
+
+        repository
+            .register_new_club(NewClubRequest {
+                chat_id: CHAT_ID,
+                actor_id: 1,
+            })
+            .await
+            .unwrap();
+        repository
+            .write_new_event(NewEventRequest {
+                chat_id: CHAT_ID,
+                actor_id: 1,
+                event_id: Uuid::new_v4(),
+                event_date: Utc::now().naive_utc(),
+                track: "main".to_string(),
+                with_insights: false,
+                host: None,
+                location: None,
+            })
+            .await
+            .unwrap();
+
+        let catalog = catalog::new_catalog(&[&catalog::CatalogSource::Fixture(
+            "fixtures/catalog.json".to_string(),
+        )])
+        .unwrap();
+        let service = Service::with_repository(Box::new(repository), catalog);
+
+        let mut settings = ClubSettings::new(CHAT_ID);
+        settings.kind = kind;
+
+        (service, settings)
+    }
+
+    async fn suggest(service: &Service, user_id: i64, text: &str, settings: &ClubSettings) {
+        service
+            .new_member_suggestion(user_id, "member", text, settings, "en")
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn pick_under_tells_how_many_of_unknown_length_were_left_out() {
+        let dir = tempfile::tempdir().unwrap();
+        let (service, settings) = club(&dir, ClubKind::Films).await;
+        suggest(
+            &service,
+            1,
+            "https://www.imdb.com/title/tt1160419/",
+            &settings,
+        )
+        .await;
+        suggest(&service, 2, "my cousin's wedding video", &settings).await;
+
+        let err = service
+            .pick_from_suggestions(1, "", Some(100), "club", &settings, "en")
+            .await
+            .err()
+            .unwrap();
+        assert!(matches!(
+            err.downcast_ref::<Err>(),
+            Some(Err::NoSuggestionsUnder(100, _, 1))
+        ));
+
+        let Ok(Pick::Picked(message)) = service
+            .pick_from_suggestions(1, "", Some(160), "club", &settings, "en")
+            .await
+        else {
+            panic!("dune runs under 160 minutes");
+        };
+        assert!(message.contains("Dune"), "{}", message);
+        assert!(
+            message.contains("1 suggestion with an unknown runtime was left out"),
+            "{}",
+            message
+        );
+    }
+
+    fn suggestion(user_id: i64, text: &str) -> EventSuggestion {
+        EventSuggestion {
+            user_id,
+            name: None,
+            suggestion: text.to_string(),
+            work: None,
+            identifier: identifier::detect(text),
+        }
+    }
+
+    #[test]
+    fn raw_title_names_unresolved_links_by_their_id() {
+        let title = |text: &str| raw_title(text, identifier::detect(text).as_ref(), "en");
+
+        assert_eq!(
+            title("https://www.imdb.com/title/tt9999999/"),
+            "IMDb tt9999999"
+        );
+        assert_eq!(title("ISBN 0 441 17271 7"), "ISBN 9780441172719");
+        assert_eq!(
+            title("Arrival imdb.com/title/tt2543164"),
+            "Arrival imdb.com/title/tt2543164"
+        );
+        assert_eq!(title("Arrival"), "Arrival");
+    }
+
+    #[test]
+    fn distinct_counts_a_work_suggested_twice_once() {
+        let suggestions = [
+            suggestion(1, "https://www.imdb.com/title/tt2543164/"),
+            suggestion(2, "imdb.com/title/tt2543164"),
+            suggestion(3, "Solaris"),
+            suggestion(4, "solaris "),
+        ];
+
+        let users: Vec<i64> = distinct(&suggestions).iter().map(|s| s.user_id).collect();
+
+        assert_eq!(users, [1, 3]);
+    }
+
+    #[tokio::test]
+    async fn imdb_links_resolve_through_the_film_catalog() {
+        let dir = tempfile::tempdir().unwrap();
+        let (service, settings) = club(&dir, ClubKind::Films).await;
+
+        let added = service
+            .new_member_suggestion(
+                1,
+                "member",
+                "https://www.imdb.com/title/tt0083658/",
+                &settings,
+                "en",
+            )
+            .await
+            .unwrap();
+        let Suggestion::Added(description) = added else {
+            panic!("an imdb link names one film");
+        };
+        assert!(description.contains("Blade Runner"), "{}", description);
+
+        let unknown = service
+            .new_member_suggestion(
+                2,
+                "member",
+                "https://www.imdb.com/title/tt9999999/",
+                &settings,
+                "en",
+            )
+            .await
+            .unwrap();
+        assert!(matches!(unknown, Suggestion::Added(title) if title == "IMDb tt9999999"));
+    }
+
+    async fn suggested_works(service: &Service) -> Vec<(String, Option<String>)> {
+        let event = service.active_event(CHAT_ID, "").await.unwrap();
+
+        service
+            .repository
+            .get_all_suggestions_for_event(EventSuggestionsRequest {
+                event_id: event.event_id,
+            })
+            .await
+            .unwrap()
+            .suggestions
+            .into_iter()
+            .map(|suggestion| (suggestion.suggestion, suggestion.work.map(|work| work.key)))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn one_catalog_match_is_resolved_right_away() {
+        let dir = tempfile::tempdir().unwrap();
+        let (service, settings) = club(&dir, ClubKind::Books).await;
+
+        let added = service
+            .new_member_suggestion(1, "member", "pride and prejudice", &settings, "en")
+            .await
+            .unwrap();
+
+        assert!(matches!(added, Suggestion::Added(text) if text.contains("Jane Austen")));
+        assert_eq!(
+            suggested_works(&service).await,
+            [(
+                "pride and prejudice".to_string(),
+                Some("OL66554W".to_string())
+            )]
+        );
+    }
+
+    #[tokio::test]
+    async fn several_catalog_matches_are_chosen_by_the_member() {
+        let dir = tempfile::tempdir().unwrap();
+        let (service, settings) = club(&dir, ClubKind::Books).await;
+
+        let choose = service
+            .new_member_suggestion(1, "member", "orwell", &settings, "en")
+            .await
+            .unwrap();
+        let Suggestion::Choose {
+            suggestion_id,
+            works,
+            ..
+        } = choose
+        else {
+            panic!("both orwell books match");
+        };
+        assert_eq!(works.len(), 2);
+        assert_eq!(suggested_works(&service).await[0].1, None);
+
+        let not_yours = service
+            .choose_work(2, suggestion_id, Some("OL1168007W"), &settings, "en")
+            .await;
+        assert!(matches!(
+            not_yours.err().unwrap().downcast_ref::<Err>(),
+            Some(Err::NotYourSuggestion)
+        ));
+
+        let chosen = service
+            .choose_work(1, suggestion_id, Some("OL1168007W"), &settings, "en")
+            .await
+            .unwrap();
+        assert!(chosen.contains("Animal Farm"), "{}", chosen);
+        assert_eq!(
+            suggested_works(&service).await,
+            [("orwell".to_string(), Some("OL1168007W".to_string()))]
+        );
+    }
+
+    #[tokio::test]
+    async fn unmatched_suggestions_keep_the_raw_text() {
+        let dir = tempfile::tempdir().unwrap();
+        let (service, settings) = club(&dir, ClubKind::Books).await;
+
+        let added = service
+            .new_member_suggestion(1, "member", "Solaris", &settings, "en")
+            .await
+            .unwrap();
+        assert!(matches!(added, Suggestion::Added(text) if text == "Solaris"));
+
+        // the member may also say none of the offered works is theirs
+        let Suggestion::Choose { suggestion_id, .. } = service
+            .new_member_suggestion(2, "member", "orwell", &settings, "en")
+            .await
+            .unwrap()
+        else {
+            panic!("both orwell books match");
+        };
+        let kept = service
+            .choose_work(2, suggestion_id, None, &settings, "en")
+            .await
+            .unwrap();
+        assert_eq!(kept, "orwell");
+
+        assert_eq!(
+            suggested_works(&service).await,
+            [("Solaris".to_string(), None), ("orwell".to_string(), None)]
+        );
+    }
+}
@@ -0,0 +1,1360 @@
+use crate::identifier::Identifier;
+use crate::models::*;
+use crate::repository::{Error, Repository};
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use rusqlite::{params, Connection, OptionalExtension, Row, Transaction};
+use serde_json::json;
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+
+// sqlite keeps its own schema history in user_version, one entry per migration
+const MIGRATIONS: [&str; 14] = [
+    include_str!("../migrations/sqlite/0001_init.sql"),
+    include_str!("../migrations/sqlite/0002_active_event_invariants.sql"),
+    include_str!("../migrations/sqlite/0003_insights_outbox.sql"),
+    include_str!("../migrations/sqlite/0004_suggestion_works.sql"),
+    include_str!("../migrations/sqlite/0005_suggestion_identifiers.sql"),
+    include_str!("../migrations/sqlite/0006_club_kind.sql"),
+    include_str!("../migrations/sqlite/0007_reading_progress.sql"),
+    include_str!("../migrations/sqlite/0008_ratings.sql"),
+    include_str!("../migrations/sqlite/0009_discussion_threads.sql"),
+    include_str!("../migrations/sqlite/0010_questions.sql"),
+    include_str!("../migrations/sqlite/0011_hosts.sql"),
+    include_str!("../migrations/sqlite/0012_event_location.sql"),
+    include_str!("../migrations/sqlite/0013_calendar_feeds.sql"),
+    include_str!("../migrations/sqlite/0014_event_revisions.sql"),
+];
+
+// timestamps are stored as utc text in sqlite's own format, so they sort and compare as strings
+const DATE_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+pub struct Sqlite {
+    // rusqlite is blocking, so every query runs on the blocking pool under this lock
+    conn: Arc<Mutex<Connection>>,
+}
+
+pub async fn new_sqlite_repository(path: &str) -> Result<Sqlite, Error> {
+    let path = path.to_string();
+
+    let conn = tokio::task::spawn_blocking(move || -> Result<Connection, rusqlite::Error> {
+        let mut conn = Connection::open(path)?;
+        conn.execute_batch("PRAGMA journal_mode = WAL;")?;
+        migrate(&mut conn)?;
+        Ok(conn)
+    })
+    .await
+    .unwrap()?;
+
+    Ok(Sqlite {
+        conn: Arc::new(Mutex::new(conn)),
+    })
+}
+
+// foreign keys are off while migrating so tables can be rebuilt,
+// the way sqlite docs suggest, and are checked before each commit instead
+fn migrate(conn: &mut Connection) -> Result<(), rusqlite::Error> {
+    let version: usize = conn.query_row("PRAGMA user_version;", [], |row| row.get(0))?;
+
+    conn.pragma_update(None, "foreign_keys", false)?;
+
+    for (i, sql) in MIGRATIONS.iter().enumerate().skip(version) {
+        log::info!("applying sqlite migration {}", i + 1);
+
+        let tx = conn.transaction()?;
+        tx.execute_batch(sql)?;
+        tx.query_row("PRAGMA foreign_key_check;", [], |_| Ok(()))
+            .optional()?
+            .map_or(Ok(()), |_| {
+                Err(rusqlite::Error::SqliteFailure(
+                    rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_CONSTRAINT_FOREIGNKEY),
+                    Some(format!("migration {} breaks foreign keys", i + 1)),
+                ))
+            })?;
+        tx.pragma_update(None, "user_version", i + 1)?;
+        tx.commit()?;
+    }
+
+    conn.pragma_update(None, "foreign_keys", true)
+}
+
+impl Sqlite {
+    async fn call<T, F>(&self, f: F) -> Result<T, Error>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut Connection) -> Result<T, rusqlite::Error> + Send + 'static,
+    {
+        let conn = self.conn.clone();
+
+        tokio::task::spawn_blocking(move || f(&mut conn.lock().unwrap()))
+            .await
+            .unwrap()
+            .map_err(Error::from)
+    }
+}
+
+fn format_date(date: NaiveDateTime) -> String {
+    date.format(DATE_FORMAT).to_string()
+}
+
+fn parse_date(text: String) -> NaiveDateTime {
+    NaiveDateTime::parse_from_str(text.as_str(), DATE_FORMAT).unwrap_or_default()
+}
+
+fn parse_uuid(text: String) -> Uuid {
+    Uuid::parse_str(text.as_str()).unwrap_or_default()
+}
+
+fn parse_identifier(kind: Option<String>, value: Option<String>) -> Option<Identifier> {
+    kind.zip(value)
+        .and_then(|(kind, value)| Identifier::parse(&kind, value))
+}
+
+// works are kept as json text, one that no longer parses is treated as unresolved
+fn parse_work(value: Option<serde_json::Value>) -> Option<Work> {
+    value.and_then(|value| serde_json::from_value(value).ok())
+}
+
+// same as the postgres query, club points to its soonest active event
+const REFRESH_CLUB_NEXT_EVENT: &str = "UPDATE club SET
+    active_event = (SELECT id FROM events WHERE chat_id = ?1 AND active = true ORDER BY event_date LIMIT 1),
+    next_event = (SELECT min(event_date) FROM events WHERE chat_id = ?1 AND active = true)
+WHERE chat_id = ?1;";
+
+fn insert_audit_record(tx: &Transaction, rec: &AuditRecord) -> Result<(), rusqlite::Error> {
+    tx.execute(
+        "INSERT INTO club_audit (chat_id, actor_id, action, payload) VALUES (?1, ?2, ?3, ?4);",
+        params![rec.chat_id, rec.actor_id, rec.action.as_str(), rec.payload],
+    )
+    .map(|_| ())
+}
+
+fn insert_outbox_message(tx: &Transaction, msg: &OutboxMessage) -> Result<(), rusqlite::Error> {
+    tx.execute(
+        "INSERT INTO insights_outbox (id, event_id, chat_id, kind, payload) VALUES (?1, ?2, ?3, ?4, ?5);",
+        params![
+            msg.id.to_string(),
+            msg.event_id.to_string(),
+            msg.chat_id,
+            msg.kind.as_str(),
+            msg.payload,
+        ],
+    )
+    .map(|_| ())
+}
+
+fn event_from_row(row: &Row) -> Result<LastEventResponse, rusqlite::Error> {
+    let subject: Option<String> = row.get(2)?;
+
+    Ok(LastEventResponse {
+        event_id: parse_uuid(row.get(0)?),
+        track: row.get(5)?,
+        event_date: parse_date(row.get(1)?),
+        subject: subject.unwrap_or_default(),
+        with_insights: row.get(3)?,
+        insights_link: row.get(4)?,
+        poll_message_id: row.get(6)?,
+        work: parse_work(row.get(7)?),
+        discussion: DiscussionThread::parse(row.get(8)?, row.get(9)?),
+        started_by: row.get(10)?,
+        host: Member::parse(row.get(11)?, row.get(12)?),
+        location: Location::parse(row.get(13)?, row.get(14)?, row.get(15)?),
+        revision: row.get(16)?,
+        updated_at: parse_date(row.get(17)?),
+    })
+}
+
+#[async_trait]
+impl Repository for Sqlite {
+    async fn register_new_club(&self, req: NewClubRequest) -> Result<(), Error> {
+        self.call(move |conn| {
+            let tx = conn.transaction()?;
+
+            tx.execute("INSERT INTO club (chat_id) VALUES (?1);", [req.chat_id])?;
+
+            insert_audit_record(
+                &tx,
+                &AuditRecord {
+                    chat_id: req.chat_id,
+                    actor_id: req.actor_id,
+                    action: AuditAction::ClubRegistered,
+                    payload: json!({}),
+                },
+            )?;
+
+            tx.commit()
+        })
+        .await
+    }
+
+    async fn write_new_event(&self, req: NewEventRequest) -> Result<(), Error> {
+        self.call(move |conn| {
+            let tx = conn.transaction()?;
+
+            tx.execute(
+                "INSERT INTO events (id, chat_id, event_date, active, track, insights, host_id, host_name, location, latitude, longitude, updated_at)
+                VALUES (?1, ?2, ?3, true, ?4, ?5, ?6, ?7, ?8, ?9, ?10, CURRENT_TIMESTAMP);",
+                params![
+                    req.event_id.to_string(),
+                    req.chat_id,
+                    format_date(req.event_date),
+                    req.track,
+                    req.with_insights,
+                    req.host.as_ref().map(|host| host.user_id),
+                    req.host.as_ref().and_then(|host| host.name.clone()),
+                    req.location.as_ref().map(|location| location.address.clone()),
+                    req.location.as_ref().and_then(Location::latitude),
+                    req.location.as_ref().and_then(Location::longitude),
+                ],
+            )?;
+
+            tx.execute(REFRESH_CLUB_NEXT_EVENT, [req.chat_id])?;
+
+            insert_audit_record(
+                &tx,
+                &AuditRecord {
+                    chat_id: req.chat_id,
+                    actor_id: req.actor_id,
+                    action: AuditAction::EventCreated,
+                    payload: json!({
+                        "event_id": req.event_id,
+                        "event_date": req.event_date.format("%Y.%m.%d %H:%M").to_string(),
+                        "track": req.track,
+                    }),
+                },
+            )?;
+
+            tx.commit()
+        })
+        .await
+    }
+
+    async fn get_active_events(
+        &self,
+        req: LastEventRequest,
+    ) -> Result<ActiveEventsResponse, Error> {
+        self.call(move |conn| {
+            let mut stmt = conn.prepare(
+                "SELECT id, event_date, subject, insights, insights_link, track, poll_message_id, work, discussion_kind, discussion_id, started_by, host_id, host_name, location, latitude, longitude, revision, COALESCE(updated_at, created_at) FROM events WHERE chat_id = ?1 AND active = true ORDER BY event_date;",
+            )?;
+
+            let events = stmt
+                .query_map([req.chat_id], event_from_row)?
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Ok(ActiveEventsResponse { events })
+        })
+        .await
+    }
+
+    async fn write_new_member_suggestion(&self, req: NewMemberSuggestion) -> Result<i64, Error> {
+        self.call(move |conn| {
+            let tx = conn.transaction()?;
+
+            tx.execute(
+                "INSERT INTO suggestions (event_id, chat_id, user_id, suggestion, work, identifier_kind, identifier, name)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8);",
+                params![
+                    req.event_id.to_string(),
+                    req.chat_id,
+                    req.user_id,
+                    req.suggestion,
+                    req.work.as_ref().map(|work| json!(work)),
+                    req.identifier.as_ref().map(Identifier::kind),
+                    req.identifier.as_ref().map(Identifier::value),
+                    req.name,
+                ],
+            )?;
+
+            let id = tx.last_insert_rowid();
+
+            insert_audit_record(
+                &tx,
+                &AuditRecord {
+                    chat_id: req.chat_id,
+                    actor_id: req.user_id,
+                    action: AuditAction::SuggestionAdded,
+                    payload: json!({ "event_id": req.event_id, "suggestion": req.suggestion }),
+                },
+            )?;
+
+            tx.commit()?;
+
+            Ok(id)
+        })
+        .await
+    }
+
+    async fn achieve_event(&self, req: AchieveEventRequest) -> Result<(), Error> {
+        self.call(move |conn| {
+            let tx = conn.transaction()?;
+
+            tx.execute(
+                "UPDATE events SET active = false, achieved_on = CURRENT_TIMESTAMP WHERE id = ?1;",
+                [req.event_id.to_string()],
+            )?;
+
+            tx.execute(
+                "UPDATE club SET last_event = CURRENT_TIMESTAMP WHERE chat_id = ?1;",
+                [req.chat_id],
+            )?;
+
+            tx.execute(REFRESH_CLUB_NEXT_EVENT, [req.chat_id])?;
+
+            insert_audit_record(
+                &tx,
+                &AuditRecord {
+                    chat_id: req.chat_id,
+                    actor_id: req.actor_id,
+                    action: AuditAction::EventAchieved,
+                    payload: json!({ "event_id": req.event_id }),
+                },
+            )?;
+
+            if let Some(msg) = &req.outbox {
+                insert_outbox_message(&tx, msg)?;
+            }
+
+            tx.commit()
+        })
+        .await
+    }
+
+    async fn get_all_suggestions_for_event(
+        &self,
+        req: EventSuggestionsRequest,
+    ) -> Result<EventSuggestionsResponse, Error> {
+        self.call(move |conn| {
+            let mut stmt = conn.prepare(
+                "SELECT user_id, suggestion, work, identifier_kind, identifier, name FROM suggestions WHERE event_id = ?1;",
+            )?;
+
+            let suggestions = stmt
+                .query_map([req.event_id.to_string()], |row| {
+                    Ok(EventSuggestion {
+                        user_id: row.get(0)?,
+                        name: row.get(5)?,
+                        suggestion: row.get(1)?,
+                        work: parse_work(row.get(2)?),
+                        identifier: parse_identifier(row.get(3)?, row.get(4)?),
+                    })
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Ok(EventSuggestionsResponse { suggestions })
+        })
+        .await
+    }
+
+    async fn write_picked_subject(&self, req: PickedSubjectRequest) -> Result<(), Error> {
+        self.call(move |conn| {
+            let tx = conn.transaction()?;
+
+            tx.execute(
+                "UPDATE events SET subject = ?1, work = ?3, suggested_by = ?4, picked_at = CURRENT_TIMESTAMP, revision = revision + 1, updated_at = CURRENT_TIMESTAMP WHERE id = ?2;",
+                params![
+                    req.subject,
+                    req.event_id.to_string(),
+                    req.work.as_ref().map(|work| json!(work)),
+                    json!(req.suggested_by),
+                ],
+            )?;
+
+            insert_audit_record(
+                &tx,
+                &AuditRecord {
+                    chat_id: req.chat_id,
+                    actor_id: req.actor_id,
+                    action: AuditAction::SubjectPicked,
+                    payload: json!({
+                        "event_id": req.event_id,
+                        "subject": req.subject,
+                    }),
+                },
+            )?;
+
+            if let Some(msg) = &req.outbox {
+                insert_outbox_message(&tx, msg)?;
+            }
+
+            tx.commit()
+        })
+        .await
+    }
+
+    async fn toggle_with_insights(&self, req: EventToggleWithInsightsRequest) -> Result<(), Error> {
+        self.call(move |conn| {
+            let tx = conn.transaction()?;
+
+            tx.execute(
+                "UPDATE events SET insights = ?1, revision = revision + 1, updated_at = CURRENT_TIMESTAMP WHERE id = ?2;",
+                params![!req.with_insights, req.event_id.to_string()],
+            )?;
+
+            insert_audit_record(
+                &tx,
+                &AuditRecord {
+                    chat_id: req.chat_id,
+                    actor_id: req.actor_id,
+                    action: AuditAction::InsightsToggled,
+                    payload: json!({ "event_id": req.event_id, "insights": !req.with_insights }),
+                },
+            )?;
+
+            tx.commit()
+        })
+        .await
+    }
+
+    async fn get_audit_log(&self, req: AuditLogRequest) -> Result<AuditLogResponse, Error> {
+        self.call(move |conn| {
+            let mut stmt = conn.prepare(
+                "SELECT actor_id, action, payload, created_at FROM club_audit WHERE chat_id = ?1 ORDER BY created_at DESC, id DESC LIMIT ?2;",
+            )?;
+
+            let entries = stmt
+                .query_map(params![req.chat_id, req.limit], |row| {
+                    Ok(AuditLogEntry {
+                        actor_id: row.get(0)?,
+                        action: row.get(1)?,
+                        payload: row.get(2)?,
+                        created_at: parse_date(row.get(3)?).and_utc(),
+                    })
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Ok(AuditLogResponse { entries })
+        })
+        .await
+    }
+
+    async fn write_pick_poll(&self, req: PickPollRequest) -> Result<(), Error> {
+        self.call(move |conn| {
+            let tx = conn.transaction()?;
+
+            tx.execute(
+                "UPDATE events SET poll_message_id = ?1 WHERE id = ?2;",
+                params![req.message_id, req.event_id.to_string()],
+            )?;
+
+            insert_audit_record(
+                &tx,
+                &AuditRecord {
+                    chat_id: req.chat_id,
+                    actor_id: req.actor_id,
+                    action: AuditAction::PollStarted,
+                    payload: json!({
+                        "event_id": req.event_id,
+                        "message_id": req.message_id,
+                    }),
+                },
+            )?;
+
+            tx.commit()
+        })
+        .await
+    }
+
+    async fn get_club_settings(&self, req: ClubSettingsRequest) -> Result<ClubSettings, Error> {
+        self.call(move |conn| {
+            let settings = conn
+                .query_row(
+                    "SELECT default_insights, pick_mode, suggestion_limit, time_zone, language, reminder_offsets, kind, host_only FROM club_settings WHERE chat_id = ?1;",
+                    [req.chat_id],
+                    |row| {
+                        let pick_mode: String = row.get(1)?;
+                        let reminder_offsets: serde_json::Value = row.get(5)?;
+                        let kind: String = row.get(6)?;
+
+                        Ok(ClubSettings {
+                            chat_id: req.chat_id,
+                            default_insights: row.get(0)?,
+                            pick_mode: PickMode::parse(pick_mode.as_str())
+                                .unwrap_or(PickMode::Random),
+                            suggestion_limit: row.get(2)?,
+                            time_zone: row.get(3)?,
+                            language: row.get(4)?,
+                            reminder_offsets: serde_json::from_value(reminder_offsets)
+                                .unwrap_or_default(),
+                            kind: ClubKind::parse(kind.as_str()).unwrap_or(ClubKind::Books),
+                            host_only: row.get(7)?,
+                        })
+                    },
+                )
+                .optional()?;
+
+            Ok(settings.unwrap_or(ClubSettings::new(req.chat_id)))
+        })
+        .await
+    }
+
+    async fn write_club_settings(&self, req: UpdateClubSettingsRequest) -> Result<(), Error> {
+        self.call(move |conn| {
+            let tx = conn.transaction()?;
+            let settings = &req.settings;
+
+            tx.execute(
+                "INSERT INTO club_settings (chat_id, default_insights, pick_mode, suggestion_limit, time_zone, language, reminder_offsets, kind, host_only)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+                ON CONFLICT (chat_id) DO UPDATE SET default_insights = ?2, pick_mode = ?3, suggestion_limit = ?4,
+                    time_zone = ?5, language = ?6, reminder_offsets = ?7, kind = ?8, host_only = ?9, updated_at = CURRENT_TIMESTAMP;",
+                params![
+                    settings.chat_id,
+                    settings.default_insights,
+                    settings.pick_mode.as_str(),
+                    settings.suggestion_limit,
+                    settings.time_zone,
+                    settings.language,
+                    json!(settings.reminder_offsets),
+                    settings.kind.as_str(),
+                    settings.host_only,
+                ],
+            )?;
+
+            insert_audit_record(
+                &tx,
+                &AuditRecord {
+                    chat_id: settings.chat_id,
+                    actor_id: req.actor_id,
+                    action: AuditAction::SettingsChanged,
+                    payload: json!({
+                        "default_insights": settings.default_insights,
+                        "pick_mode": settings.pick_mode.as_str(),
+                        "suggestion_limit": settings.suggestion_limit,
+                        "time_zone": settings.time_zone,
+                        "language": settings.language,
+                        "reminder_offsets": settings.reminder_offsets,
+                        "kind": settings.kind.as_str(),
+                        "host_only": settings.host_only,
+                    }),
+                },
+            )?;
+
+            tx.commit()
+        })
+        .await
+    }
+
+    async fn get_due_reminders(
+        &self,
+        req: DueRemindersRequest,
+    ) -> Result<DueRemindersResponse, Error> {
+        self.call(move |conn| {
+            let mut stmt = conn.prepare(
+                "SELECT e.id, e.chat_id, e.track, e.event_date, e.subject, o.value, e.picked_at, e.host_id, e.host_name,
+                    e.location, e.latitude, e.longitude
+                FROM events e
+                JOIN club_settings s ON s.chat_id = e.chat_id
+                JOIN json_each(s.reminder_offsets) o
+                WHERE e.active = true
+                    AND e.event_date > ?1
+                    AND datetime(e.event_date, '-' || o.value || ' minutes') <= ?1
+                    AND NOT EXISTS (
+                        SELECT 1 FROM event_reminders r WHERE r.event_id = e.id AND r.offset_minutes = o.value
+                    )
+                ORDER BY e.event_date, o.value;",
+            )?;
+
+            let reminders = stmt
+                .query_map([format_date(req.now.naive_utc())], |row| {
+                    let subject: Option<String> = row.get(4)?;
+                    let picked_at: Option<String> = row.get(6)?;
+
+                    Ok(DueReminder {
+                        event_id: parse_uuid(row.get(0)?),
+                        chat_id: row.get(1)?,
+                        track: row.get(2)?,
+                        event_date: parse_date(row.get(3)?),
+                        subject: subject.unwrap_or_default(),
+                        picked_at: picked_at.map(parse_date),
+                        offset_minutes: row.get(5)?,
+                        host: Member::parse(row.get(7)?, row.get(8)?),
+                        location: Location::parse(row.get(9)?, row.get(10)?, row.get(11)?),
+                    })
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Ok(DueRemindersResponse { reminders })
+        })
+        .await
+    }
+
+    async fn mark_reminders_sent(&self, req: SentRemindersRequest) -> Result<(), Error> {
+        self.call(move |conn| {
+            let tx = conn.transaction()?;
+
+            for offset in &req.offsets {
+                tx.execute(
+                    "INSERT INTO event_reminders (event_id, offset_minutes) VALUES (?1, ?2) ON CONFLICT DO NOTHING;",
+                    params![req.event_id.to_string(), offset],
+                )?;
+            }
+
+            tx.commit()
+        })
+        .await
+    }
+
+    async fn write_event_started(&self, req: EventStartedRequest) -> Result<(), Error> {
+        self.call(move |conn| {
+            let tx = conn.transaction()?;
+
+            insert_audit_record(
+                &tx,
+                &AuditRecord {
+                    chat_id: req.chat_id,
+                    actor_id: req.actor_id,
+                    action: AuditAction::EventStarted,
+                    payload: json!({ "event_id": req.event_id }),
+                },
+            )?;
+
+            tx.execute(
+                "UPDATE events SET started_by = COALESCE(started_by, ?2) WHERE id = ?1;",
+                params![req.event_id.to_string(), req.actor_id],
+            )?;
+
+            for number in &req.numbers {
+                tx.execute(
+                    "UPDATE questions SET number = ?2 WHERE id = ?1;",
+                    params![number.question_id.to_string(), number.number],
+                )?;
+            }
+
+            if let Some(outbox) = &req.outbox {
+                insert_outbox_message(&tx, outbox)?;
+            }
+
+            tx.commit()
+        })
+        .await
+    }
+
+    async fn get_due_outbox(&self, req: DueOutboxRequest) -> Result<DueOutboxResponse, Error> {
+        self.call(move |conn| {
+            let mut stmt = conn.prepare(
+                "SELECT o.id, o.event_id, o.chat_id, o.kind, o.payload, o.attempts
+                FROM insights_outbox o
+                WHERE o.status = 'pending'
+                    AND o.next_attempt_at <= ?1
+                    AND (?2 IS NULL OR o.event_id = ?2)
+                    AND NOT EXISTS (
+                        SELECT 1 FROM insights_outbox p WHERE p.event_id = o.event_id AND p.status = 'pending' AND p.seq < o.seq
+                    )
+                ORDER BY o.seq
+                LIMIT ?3;",
+            )?;
+
+            let entries = stmt
+                .query_map(
+                    params![
+                        format_date(req.now.naive_utc()),
+                        req.event_id.map(|id| id.to_string()),
+                        req.limit,
+                    ],
+                    |row| {
+                        let kind: String = row.get(3)?;
+                        let Some(kind) = OutboxKind::parse(&kind) else {
+                            return Ok(None);
+                        };
+
+                        Ok(Some(OutboxEntry {
+                            message: OutboxMessage {
+                                id: parse_uuid(row.get(0)?),
+                                event_id: parse_uuid(row.get(1)?),
+                                chat_id: row.get(2)?,
+                                kind,
+                                payload: row.get(4)?,
+                            },
+                            attempts: row.get(5)?,
+                        }))
+                    },
+                )?
+                .filter_map(Result::transpose)
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Ok(DueOutboxResponse { entries })
+        })
+        .await
+    }
+
+    async fn mark_outbox_delivered(&self, req: OutboxDeliveredRequest) -> Result<(), Error> {
+        self.call(move |conn| {
+            let tx = conn.transaction()?;
+
+            tx.execute(
+                "UPDATE insights_outbox SET status = 'delivered', attempts = attempts + 1, delivered_at = CURRENT_TIMESTAMP, last_error = NULL
+                WHERE id = ?1 AND status = 'pending';",
+                [req.id.to_string()],
+            )?;
+
+            if let Some(link) = &req.insights_link {
+                tx.execute(
+                    "UPDATE events SET insights_link = ?1, revision = revision + 1, updated_at = CURRENT_TIMESTAMP WHERE id = ?2;",
+                    params![link, req.event_id.to_string()],
+                )?;
+            }
+
+            tx.commit()
+        })
+        .await
+    }
+
+    async fn mark_outbox_failed(&self, req: OutboxFailedRequest) -> Result<(), Error> {
+        self.call(move |conn| {
+            let status = match req.next_attempt_at {
+                Some(_) => "pending",
+                None => "failed",
+            };
+
+            conn.execute(
+                "UPDATE insights_outbox SET status = ?2, attempts = attempts + 1, last_error = ?3, next_attempt_at = coalesce(?4, next_attempt_at)
+                WHERE id = ?1 AND status = 'pending';",
+                params![
+                    req.id.to_string(),
+                    status,
+                    req.error,
+                    req.next_attempt_at.map(|date| format_date(date.naive_utc())),
+                ],
+            )
+            .map(|_| ())
+        })
+        .await
+    }
+
+    async fn get_event_chat(
+        &self,
+        req: EventChatRequest,
+    ) -> Result<Option<EventChatResponse>, Error> {
+        self.call(move |conn| {
+            conn.query_row(
+                "SELECT chat_id, subject, started_by, host_id FROM events WHERE id = ?1;",
+                [req.event_id.to_string()],
+                |row| {
+                    let subject: Option<String> = row.get(1)?;
+
+                    Ok(EventChatResponse {
+                        chat_id: row.get(0)?,
+                        subject: subject.unwrap_or_default(),
+                        started_by: row.get(2)?,
+                        host_id: row.get(3)?,
+                    })
+                },
+            )
+            .optional()
+        })
+        .await
+    }
+
+    async fn get_suggestion(
+        &self,
+        req: SuggestionRequest,
+    ) -> Result<Option<SuggestionResponse>, Error> {
+        self.call(move |conn| {
+            conn.query_row(
+                "SELECT chat_id, user_id, suggestion FROM suggestions WHERE rowid = ?1;",
+                [req.id],
+                |row| {
+                    Ok(SuggestionResponse {
+                        chat_id: row.get(0)?,
+                        user_id: row.get(1)?,
+                        suggestion: row.get(2)?,
+                    })
+                },
+            )
+            .optional()
+        })
+        .await
+    }
+
+    async fn write_suggestion_work(&self, req: SuggestionWorkRequest) -> Result<(), Error> {
+        self.call(move |conn| {
+            let tx = conn.transaction()?;
+
+            tx.execute(
+                "UPDATE suggestions SET work = ?2 WHERE rowid = ?1;",
+                params![req.id, json!(req.work)],
+            )?;
+
+            insert_audit_record(
+                &tx,
+                &AuditRecord {
+                    chat_id: req.chat_id,
+                    actor_id: req.actor_id,
+                    action: AuditAction::WorkChosen,
+                    payload: json!({
+                        "suggestion_id": req.id,
+                        "key": req.work.key,
+                        "title": req.work.title,
+                    }),
+                },
+            )?;
+
+            tx.commit()
+        })
+        .await
+    }
+
+    async fn get_due_checkins(
+        &self,
+        req: DueCheckinsRequest,
+    ) -> Result<DueCheckinsResponse, Error> {
+        self.call(move |conn| {
+            let mut stmt = conn.prepare(
+                "SELECT id, chat_id, track, subject FROM events
+                WHERE active = true
+                    AND subject <> ''
+                    AND event_date > ?1
+                    AND coalesce(checkin_at, picked_at) <= ?2
+                ORDER BY event_date;",
+            )?;
+
+            let checkins = stmt
+                .query_map(
+                    [
+                        format_date(req.now.naive_utc()),
+                        format_date(req.due_before.naive_utc()),
+                    ],
+                    |row| {
+                        Ok(DueCheckin {
+                            event_id: parse_uuid(row.get(0)?),
+                            chat_id: row.get(1)?,
+                            track: row.get(2)?,
+                            subject: row.get(3)?,
+                        })
+                    },
+                )?
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Ok(DueCheckinsResponse { checkins })
+        })
+        .await
+    }
+
+    async fn mark_checkin_sent(&self, req: SentCheckinRequest) -> Result<(), Error> {
+        self.call(move |conn| {
+            conn.execute(
+                "UPDATE events SET checkin_at = ?2 WHERE id = ?1;",
+                params![
+                    req.event_id.to_string(),
+                    format_date(req.sent_at.naive_utc())
+                ],
+            )
+            .map(|_| ())
+        })
+        .await
+    }
+
+    async fn write_progress(&self, req: NewProgressRequest) -> Result<(), Error> {
+        self.call(move |conn| {
+            let tx = conn.transaction()?;
+
+            tx.execute(
+                "INSERT INTO progress (event_id, user_id, name, percent, page) VALUES (?1, ?2, ?3, ?4, ?5)
+                ON CONFLICT (event_id, user_id) DO UPDATE SET name = ?3, percent = ?4, page = ?5, updated_at = CURRENT_TIMESTAMP;",
+                params![
+                    req.event_id.to_string(),
+                    req.user_id,
+                    req.name,
+                    req.percent,
+                    req.page,
+                ],
+            )?;
+
+            insert_audit_record(
+                &tx,
+                &AuditRecord {
+                    chat_id: req.chat_id,
+                    actor_id: req.user_id,
+                    action: AuditAction::ProgressMarked,
+                    payload: json!({
+                        "event_id": req.event_id,
+                        "percent": req.percent,
+                        "page": req.page,
+                    }),
+                },
+            )?;
+
+            tx.commit()
+        })
+        .await
+    }
+
+    async fn get_progress(&self, req: ProgressRequest) -> Result<ProgressResponse, Error> {
+        self.call(move |conn| {
+            let mut stmt = conn.prepare(
+                "SELECT name, percent FROM progress WHERE event_id = ?1 ORDER BY percent DESC, updated_at;",
+            )?;
+
+            let members = stmt
+                .query_map([req.event_id.to_string()], |row| {
+                    Ok(MemberProgress {
+                        name: row.get(0)?,
+                        percent: row.get(1)?,
+                    })
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Ok(ProgressResponse { members })
+        })
+        .await
+    }
+
+    async fn write_rating(&self, req: NewRatingRequest) -> Result<(), Error> {
+        self.call(move |conn| {
+            let tx = conn.transaction()?;
+
+            tx.execute(
+                "INSERT INTO ratings (event_id, user_id, name, stars) VALUES (?1, ?2, ?3, ?4)
+                ON CONFLICT (event_id, user_id) DO UPDATE SET name = ?3, stars = ?4;",
+                params![req.event_id.to_string(), req.user_id, req.name, req.stars],
+            )?;
+
+            insert_audit_record(
+                &tx,
+                &AuditRecord {
+                    chat_id: req.chat_id,
+                    actor_id: req.user_id,
+                    action: AuditAction::RatingGiven,
+                    payload: json!({
+                        "event_id": req.event_id,
+                        "subject": req.subject,
+                        "stars": req.stars,
+                    }),
+                },
+            )?;
+
+            tx.commit()
+        })
+        .await
+    }
+
+    async fn write_review(&self, req: NewReviewRequest) -> Result<(), Error> {
+        self.call(move |conn| {
+            let tx = conn.transaction()?;
+
+            tx.execute(
+                "UPDATE ratings SET review = ?3 WHERE event_id = ?1 AND user_id = ?2;",
+                params![req.event_id.to_string(), req.user_id, req.review],
+            )?;
+
+            insert_audit_record(
+                &tx,
+                &AuditRecord {
+                    chat_id: req.chat_id,
+                    actor_id: req.user_id,
+                    action: AuditAction::ReviewAdded,
+                    payload: json!({
+                        "event_id": req.event_id,
+                        "subject": req.subject,
+                    }),
+                },
+            )?;
+
+            tx.commit()
+        })
+        .await
+    }
+
+    async fn get_achieved_events(
+        &self,
+        req: AchievedEventsRequest,
+    ) -> Result<AchievedEventsResponse, Error> {
+        self.call(move |conn| {
+            let mut stmt = conn.prepare(
+                "SELECT e.id, e.event_date, e.subject, e.suggested_by, r.user_id, r.name, r.stars, r.review
+                FROM (
+                    SELECT * FROM events
+                    WHERE chat_id = ?1 AND active = false AND achieved_on IS NOT NULL AND subject <> ''
+                    ORDER BY achieved_on DESC
+                    LIMIT ?2
+                ) e
+                LEFT JOIN ratings r ON r.event_id = e.id
+                ORDER BY e.achieved_on DESC, e.id, r.created_at;",
+            )?;
+            let mut rows = stmt.query(params![req.chat_id, req.limit])?;
+            let mut events: Vec<AchievedEvent> = vec![];
+
+            while let Some(row) = rows.next()? {
+                let event_id = parse_uuid(row.get(0)?);
+
+                if events.last().map(|event| event.event_id) != Some(event_id) {
+                    let suggested_by: serde_json::Value = row.get(3)?;
+
+                    events.push(AchievedEvent {
+                        event_id,
+                        event_date: parse_date(row.get(1)?),
+                        subject: row.get(2)?,
+                        suggested_by: serde_json::from_value(suggested_by).unwrap_or_default(),
+                        ratings: vec![],
+                    })
+                }
+
+                let user_id: Option<i64> = row.get(4)?;
+                if let (Some(user_id), Some(event)) = (user_id, events.last_mut()) {
+                    event.ratings.push(Rating {
+                        user_id,
+                        name: row.get(5)?,
+                        stars: row.get(6)?,
+                        review: row.get(7)?,
+                    })
+                }
+            }
+
+            Ok(AchievedEventsResponse { events })
+        })
+        .await
+    }
+
+    async fn write_discussion_thread(&self, req: DiscussionThreadRequest) -> Result<(), Error> {
+        self.call(move |conn| {
+            let tx = conn.transaction()?;
+
+            tx.execute(
+                "UPDATE events SET discussion_kind = ?2, discussion_id = ?3 WHERE id = ?1;",
+                params![
+                    req.event_id.to_string(),
+                    req.thread.kind.as_str(),
+                    req.thread.id
+                ],
+            )?;
+
+            insert_audit_record(
+                &tx,
+                &AuditRecord {
+                    chat_id: req.chat_id,
+                    actor_id: req.actor_id,
+                    action: AuditAction::DiscussionOpened,
+                    payload: json!({
+                        "event_id": req.event_id,
+                        "kind": req.thread.kind.as_str(),
+                        "thread_id": req.thread.id,
+                    }),
+                },
+            )?;
+
+            tx.commit()
+        })
+        .await
+    }
+
+    async fn write_question(&self, req: NewQuestionRequest) -> Result<(), Error> {
+        self.call(move |conn| {
+            let tx = conn.transaction()?;
+
+            tx.execute(
+                "INSERT INTO questions (id, event_id, user_id, name, question) VALUES (?1, ?2, ?3, ?4, ?5);",
+                params![
+                    req.id.to_string(),
+                    req.event_id.to_string(),
+                    req.user_id,
+                    req.name,
+                    req.question
+                ],
+            )?;
+
+            insert_audit_record(
+                &tx,
+                &AuditRecord {
+                    chat_id: req.chat_id,
+                    actor_id: req.user_id,
+                    action: AuditAction::QuestionAsked,
+                    payload: json!({
+                        "event_id": req.event_id,
+                        "question_id": req.id,
+                    }),
+                },
+            )?;
+
+            tx.commit()
+        })
+        .await
+    }
+
+    async fn get_questions(&self, req: QuestionsRequest) -> Result<QuestionsResponse, Error> {
+        self.call(move |conn| {
+            let mut stmt = conn.prepare(
+                "SELECT id, name, question, number, covered FROM questions
+                WHERE event_id = ?1
+                ORDER BY number NULLS LAST, created_at, rowid;",
+            )?;
+
+            let questions = stmt
+                .query_map([req.event_id.to_string()], |row| {
+                    Ok(Question {
+                        id: parse_uuid(row.get(0)?),
+                        name: row.get(1)?,
+                        question: row.get(2)?,
+                        number: row.get(3)?,
+                        covered: row.get(4)?,
+                    })
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Ok(QuestionsResponse { questions })
+        })
+        .await
+    }
+
+    async fn write_question_covered(&self, req: QuestionCoveredRequest) -> Result<(), Error> {
+        self.call(move |conn| {
+            let tx = conn.transaction()?;
+
+            tx.execute(
+                "UPDATE questions SET covered = ?2 WHERE id = ?1;",
+                params![req.question_id.to_string(), req.covered],
+            )?;
+
+            insert_audit_record(
+                &tx,
+                &AuditRecord {
+                    chat_id: req.chat_id,
+                    actor_id: req.actor_id,
+                    action: AuditAction::QuestionCovered,
+                    payload: json!({
+                        "event_id": req.event_id,
+                        "question_id": req.question_id,
+                        "number": req.number,
+                        "covered": req.covered,
+                    }),
+                },
+            )?;
+
+            tx.commit()
+        })
+        .await
+    }
+
+    async fn get_hosts(&self, req: HostsRequest) -> Result<HostsResponse, Error> {
+        self.call(move |conn| {
+            let mut stmt = conn.prepare(
+                "SELECT user_id, name, position FROM hosts WHERE chat_id = ?1 ORDER BY position, added_at;",
+            )?;
+
+            let hosts = stmt
+                .query_map([req.chat_id], |row| {
+                    Ok(Host {
+                        user_id: row.get(0)?,
+                        name: row.get(1)?,
+                        position: row.get(2)?,
+                    })
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let last_host = conn
+                .query_row(
+                    "SELECT e.host_id FROM events e
+                    JOIN hosts h ON h.chat_id = e.chat_id AND h.user_id = e.host_id
+                    WHERE e.chat_id = ?1
+                    ORDER BY e.created_at DESC, e.rowid DESC
+                    LIMIT 1;",
+                    [req.chat_id],
+                    |row| row.get(0),
+                )
+                .optional()?;
+
+            Ok(HostsResponse { hosts, last_host })
+        })
+        .await
+    }
+
+    async fn write_host(&self, req: NewHostRequest) -> Result<(), Error> {
+        self.call(move |conn| {
+            let tx = conn.transaction()?;
+
+            tx.execute(
+                "INSERT INTO hosts (chat_id, user_id, name, position)
+                SELECT ?1, ?2, ?3, COALESCE(max(position), 0) + 1 FROM hosts WHERE chat_id = ?1;",
+                params![req.chat_id, req.user_id, req.name],
+            )?;
+
+            insert_audit_record(
+                &tx,
+                &AuditRecord {
+                    chat_id: req.chat_id,
+                    actor_id: req.actor_id,
+                    action: AuditAction::HostAdded,
+                    payload: json!({ "user_id": req.user_id, "name": req.name }),
+                },
+            )?;
+
+            tx.commit()
+        })
+        .await
+    }
+
+    async fn delete_host(&self, req: RemoveHostRequest) -> Result<(), Error> {
+        self.call(move |conn| {
+            let tx = conn.transaction()?;
+
+            let name: Option<String> = tx
+                .query_row(
+                    "DELETE FROM hosts WHERE chat_id = ?1 AND user_id = ?2 RETURNING name;",
+                    [req.chat_id, req.user_id],
+                    |row| row.get(0),
+                )
+                .optional()?;
+
+            insert_audit_record(
+                &tx,
+                &AuditRecord {
+                    chat_id: req.chat_id,
+                    actor_id: req.actor_id,
+                    action: AuditAction::HostRemoved,
+                    payload: json!({ "user_id": req.user_id, "name": name }),
+                },
+            )?;
+
+            tx.commit()
+        })
+        .await
+    }
+
+    async fn write_event_host(&self, req: SwapHostRequest) -> Result<(), Error> {
+        self.call(move |conn| {
+            let tx = conn.transaction()?;
+
+            tx.execute(
+                "UPDATE events SET host_id = ?2, host_name = ?3, revision = revision + 1, updated_at = CURRENT_TIMESTAMP WHERE id = ?1;",
+                params![req.event_id.to_string(), req.host.user_id, req.host.name],
+            )?;
+
+            for host in &req.positions {
+                tx.execute(
+                    "UPDATE hosts SET position = ?3 WHERE chat_id = ?1 AND user_id = ?2;",
+                    params![req.chat_id, host.user_id, host.position],
+                )?;
+            }
+
+            insert_audit_record(
+                &tx,
+                &AuditRecord {
+                    chat_id: req.chat_id,
+                    actor_id: req.actor_id,
+                    action: AuditAction::HostSwapped,
+                    payload: json!({
+                        "event_id": req.event_id,
+                        "user_id": req.host.user_id,
+                        "name": req.host.name,
+                    }),
+                },
+            )?;
+
+            tx.commit()
+        })
+        .await
+    }
+
+    async fn write_event_location(&self, req: EventLocationRequest) -> Result<(), Error> {
+        self.call(move |conn| {
+            let tx = conn.transaction()?;
+
+            tx.execute(
+                "UPDATE events SET location = ?2, latitude = ?3, longitude = ?4, revision = revision + 1, updated_at = CURRENT_TIMESTAMP WHERE id = ?1;",
+                params![
+                    req.event_id.to_string(),
+                    req.location.address,
+                    req.location.latitude(),
+                    req.location.longitude(),
+                ],
+            )?;
+
+            insert_audit_record(
+                &tx,
+                &AuditRecord {
+                    chat_id: req.chat_id,
+                    actor_id: req.actor_id,
+                    action: AuditAction::LocationChanged,
+                    payload: json!({
+                        "event_id": req.event_id,
+                        "location": req.location.address,
+                    }),
+                },
+            )?;
+
+            tx.commit()
+        })
+        .await
+    }
+
+    async fn get_calendar_token(
+        &self,
+        req: CalendarTokenRequest,
+    ) -> Result<Option<CalendarTokenResponse>, Error> {
+        self.call(move |conn| {
+            conn.query_row(
+                "SELECT calendar_token FROM club WHERE chat_id = ?1;",
+                [req.chat_id],
+                |row| Ok(CalendarTokenResponse { token: row.get(0)? }),
+            )
+            .optional()
+        })
+        .await
+    }
+
+    async fn write_calendar_token(&self, req: NewCalendarTokenRequest) -> Result<(), Error> {
+        self.call(move |conn| {
+            let tx = conn.transaction()?;
+
+            tx.execute(
+                "UPDATE club SET calendar_token = ?2 WHERE chat_id = ?1;",
+                params![req.chat_id, req.token],
+            )?;
+
+            insert_audit_record(
+                &tx,
+                &AuditRecord {
+                    chat_id: req.chat_id,
+                    actor_id: req.actor_id,
+                    action: AuditAction::CalendarReset,
+                    payload: json!({ "reset": req.reset }),
+                },
+            )?;
+
+            tx.commit()
+        })
+        .await
+    }
+
+    async fn get_calendar(&self, req: CalendarRequest) -> Result<Option<CalendarResponse>, Error> {
+        self.call(move |conn| {
+            let chat_id: Option<i64> = conn
+                .query_row(
+                    "SELECT chat_id FROM club WHERE calendar_token = ?1;",
+                    [&req.token],
+                    |row| row.get(0),
+                )
+                .optional()?;
+
+            let Some(chat_id) = chat_id else {
+                return Ok(None);
+            };
+
+            let mut stmt = conn.prepare(
+                "SELECT id, track, event_date, subject, CASE WHEN insights THEN insights_link END, host_id, host_name, location, latitude, longitude, revision, COALESCE(updated_at, created_at)
+                FROM events WHERE chat_id = ?1 ORDER BY event_date DESC LIMIT ?2;",
+            )?;
+
+            let events = stmt
+                .query_map(params![chat_id, req.limit], |row| {
+                    let subject: Option<String> = row.get(3)?;
+
+                    Ok(CalendarEvent {
+                        event_id: parse_uuid(row.get(0)?),
+                        track: row.get(1)?,
+                        event_date: parse_date(row.get(2)?),
+                        subject: subject.unwrap_or_default(),
+                        insights_link: row.get(4)?,
+                        host: Member::parse(row.get(5)?, row.get(6)?),
+                        location: Location::parse(row.get(7)?, row.get(8)?, row.get(9)?),
+                        revision: row.get(10)?,
+                        updated_at: parse_date(row.get(11)?),
+                    })
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Ok(Some(CalendarResponse { chat_id, events }))
+        })
+        .await
+    }
+}
@@ -0,0 +1,389 @@
+use crate::insights;
+use crate::models::InsightsCallback;
+use crate::service::Service;
+use axum::body::Bytes;
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode};
+use axum::routing::post;
+use axum::Router;
+use std::collections::{HashSet, VecDeque};
+use std::hash::Hash;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use teloxide::prelude::*;
+
+// telegram and insights both deliver again what they think got lost,
+// a few thousand ids cover any redelivery window
+pub const RECENT_DELIVERIES: usize = 4096;
+
+pub struct WebhookOptions {
+    pub address: SocketAddr,
+    // insights signs callbacks with it the way the bot signs its own requests
+    pub secret: String,
+}
+
+// ids seen lately, the oldest is forgotten once there are more than capacity of them
+pub struct RecentIds<T> {
+    capacity: usize,
+    seen: Mutex<(HashSet<T>, VecDeque<T>)>,
+}
+
+impl<T: Eq + Hash + Clone> RecentIds<T> {
+    pub fn new(capacity: usize) -> Self {
+        RecentIds {
+            capacity,
+            seen: Mutex::new((HashSet::new(), VecDeque::new())),
+        }
+    }
+
+    // true only for the first delivery of an id
+    pub fn insert(&self, id: T) -> bool {
+        let mut seen = self.seen.lock().unwrap();
+        let (ids, order) = &mut *seen;
+
+        if !ids.insert(id.clone()) {
+            return false;
+        }
+
+        order.push_back(id);
+        if order.len() > self.capacity {
+            if let Some(oldest) = order.pop_front() {
+                ids.remove(&oldest);
+            }
+        }
+
+        true
+    }
+
+    // a delivery that failed may come again and is handled then
+    pub fn remove(&self, id: &T) {
+        let mut seen = self.seen.lock().unwrap();
+        let (ids, order) = &mut *seen;
+
+        if ids.remove(id) {
+            order.retain(|seen| seen != id);
+        }
+    }
+}
+
+// dispatcher filter, telegram resends an update it has no answer for
+pub fn first_delivery(update: Update, updates: Arc<RecentIds<i32>>) -> bool {
+    let first = updates.insert(update.id);
+    if !first {
+        log::info!("skipping update {} delivered again", update.id);
+    }
+
+    first
+}
+
+#[derive(Clone)]
+struct WebhookState {
+    bot: Bot,
+    service: Arc<Service>,
+    secret: Arc<String>,
+    // digests of the callbacks already posted to their chats
+    callbacks: Arc<RecentIds<[u8; 32]>>,
+}
+
+pub async fn serve(opts: WebhookOptions, bot: Bot, service: Arc<Service>) {
+    let app = router(bot, service, opts.secret);
+
+    log::info!("listening for insights callbacks on {}", opts.address);
+
+    if let Err(err) = axum::Server::bind(&opts.address)
+        .serve(app.into_make_service())
+        .await
+    {
+        log::error!("insights webhook stopped: {}", err);
+    }
+}
+
+fn router(bot: Bot, service: Arc<Service>, secret: String) -> Router {
+    Router::new()
+        .route("/insights/callback", post(insights_callback))
+        .with_state(WebhookState {
+            bot,
+            service,
+            secret: Arc::new(secret),
+            callbacks: Arc::new(RecentIds::new(RECENT_DELIVERIES)),
+        })
+}
+
+// anything but 200 makes insights deliver the callback again
+async fn insights_callback(
+    State(state): State<WebhookState>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> StatusCode {
+    let header = |name| {
+        headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default()
+    };
+
+    if !insights::verify(
+        &state.secret,
+        header(insights::TIMESTAMP_HEADER),
+        header(insights::SIGNATURE_HEADER),
+        &body,
+    ) {
+        log::warn!("rejected insights callback with a bad signature");
+        return StatusCode::UNAUTHORIZED;
+    }
+
+    let callback: InsightsCallback = match serde_json::from_slice(&body) {
+        Ok(callback) => callback,
+        Err(err) => {
+            log::warn!("unable to parse insights callback: {}", err);
+            return StatusCode::BAD_REQUEST;
+        }
+    };
+
+    let event_id = callback.event_id();
+
+    // a callback insights sends again, once the chat has it, is only acknowledged
+    let digest = openssl::sha::sha256(&body);
+    if !state.callbacks.insert(digest) {
+        log::info!(
+            "skipping insights callback for {} delivered again",
+            event_id
+        );
+        return StatusCode::OK;
+    }
+
+    let status = deliver_callback(&state, callback).await;
+    if status != StatusCode::OK {
+        state.callbacks.remove(&digest);
+    }
+
+    status
+}
+
+async fn deliver_callback(state: &WebhookState, callback: InsightsCallback) -> StatusCode {
+    let event_id = callback.event_id();
+
+    let notice = match state.service.insights_callback(callback).await {
+        Ok(Some(notice)) => notice,
+        Ok(None) => {
+            log::warn!("insights callback for unknown event {}", event_id);
+            return StatusCode::NOT_FOUND;
+        }
+        Err(err) => {
+            log::error!("unable to handle insights callback: {}", err);
+            return StatusCode::INTERNAL_SERVER_ERROR;
+        }
+    };
+
+    match state
+        .bot
+        .send_message(ChatId(notice.chat_id), notice.text)
+        .await
+    {
+        Ok(_) => StatusCode::OK,
+        Err(err) => {
+            log::error!(
+                "unable to send insights callback to {}: {}",
+                notice.chat_id,
+                err
+            );
+            StatusCode::BAD_GATEWAY
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{NewClubRequest, NewEventRequest};
+    use crate::repository::Repository;
+    use crate::sqlite::new_sqlite_repository;
+    use axum::extract::State as FakeState;
+    use chrono::Utc;
+    use serde_json::json;
+    use std::net::TcpListener;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use teloxide::dispatching::UpdateHandler;
+    use uuid::Uuid;
+
+    const SECRET: &str = "insights-secret";
+    const CHAT_ID: i64 = -100;
+
+    fn spawn(app: Router) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = format!("http://{}", listener.local_addr().unwrap());
+        let server = axum::Server::from_tcp(listener)
+            .unwrap()
+            .serve(app.into_make_service());
+        tokio::spawn(server);
+
+        address
+    }
+
+    // telegram api that answers every method with the same message and counts the calls
+    fn fake_telegram() -> (Bot, Arc<AtomicUsize>) {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let app = Router::new()
+            .fallback(|FakeState(calls): FakeState<Arc<AtomicUsize>>| async move {
+                calls.fetch_add(1, Ordering::SeqCst);
+
+                axum::Json(json!({
+                    "ok": true,
+                    "result": {
+                        "message_id": 1,
+                        "date": 0,
+                        "chat": {"id": CHAT_ID, "type": "group", "title": "club"},
+                        "text": "sent",
+                    },
+                }))
+            })
+            .with_state(calls.clone());
+
+        let address = spawn(app);
+        let bot = Bot::new("token").set_api_url(reqwest::Url::parse(&address).unwrap());
+
+        (bot, calls)
+    }
+
+    async fn club_event(dir: &tempfile::TempDir) -> (Arc<Service>, Uuid) {
+        let path = dir.path().join("club.db");
+        let repository = new_sqlite_repository(path.to_str().unwrap()).await.unwrap();
+        let event_id = Uuid::new_v4();
+
+        repository
+            .register_new_club(NewClubRequest {
+                chat_id: CHAT_ID,
+                actor_id: 1,
+            })
+            .await
+            .unwrap();
+        repository
+            .write_new_event(NewEventRequest {
+                chat_id: CHAT_ID,
+                actor_id: 1,
+                event_id,
+                event_date: Utc::now().naive_utc(),
+                track: "main".to_string(),
+                with_insights: true,
+                host: None,
+                location: None,
+            })
+            .await
+            .unwrap();
+
+        let service = Service::with_repository(Box::new(repository), None);
+
+        (Arc::new(service), event_id)
+    }
+
+    async fn post(address: &str, secret: &str, body: &str) -> StatusCode {
+        let timestamp = Utc::now().timestamp().to_string();
+
+        reqwest::Client::new()
+            .post(format!("{}/insights/callback", address))
+            .header(insights::TIMESTAMP_HEADER, &timestamp)
+            .header(
+                insights::SIGNATURE_HEADER,
+                insights::sign(secret, &timestamp, body.as_bytes()),
+            )
+            .body(body.to_string())
+            .send()
+            .await
+            .unwrap()
+            .status()
+    }
+
+    #[tokio::test]
+    async fn same_callback_is_posted_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let (service, event_id) = club_event(&dir).await;
+        let (bot, sent) = fake_telegram();
+        let address = spawn(router(bot, service, SECRET.to_string()));
+
+        let body = json!({
+            "type": "insights_submitted",
+            "event_id": event_id,
+            "count": 3,
+        })
+        .to_string();
+
+        assert_eq!(post(&address, SECRET, &body).await, StatusCode::OK);
+        assert_eq!(post(&address, SECRET, &body).await, StatusCode::OK);
+        assert_eq!(sent.load(Ordering::SeqCst), 1);
+
+        // a new count is news, even for the same event
+        let more = body.replace("\"count\":3", "\"count\":4");
+        assert_eq!(post(&address, SECRET, &more).await, StatusCode::OK);
+        assert_eq!(sent.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn failed_callback_is_handled_again() {
+        let dir = tempfile::tempdir().unwrap();
+        let (service, _) = club_event(&dir).await;
+        let (bot, sent) = fake_telegram();
+        let address = spawn(router(bot, service, SECRET.to_string()));
+
+        let body = json!({
+            "type": "insights_submitted",
+            "event_id": Uuid::new_v4(),
+            "count": 3,
+        })
+        .to_string();
+
+        assert_eq!(post(&address, SECRET, &body).await, StatusCode::NOT_FOUND);
+        assert_eq!(post(&address, SECRET, &body).await, StatusCode::NOT_FOUND);
+        assert_eq!(
+            post(&address, "another-secret", &body).await,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(sent.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn same_update_is_handled_once() {
+        let update = json!({
+            "update_id": 7,
+            "message": {
+                "message_id": 1,
+                "date": 0,
+                "chat": {"id": CHAT_ID, "type": "group", "title": "club"},
+                "from": {"id": 1, "is_bot": false, "first_name": "Ann"},
+                "text": "/help",
+            },
+        });
+
+        let handled = Arc::new(AtomicUsize::new(0));
+        let updates = Arc::new(RecentIds::<i32>::new(RECENT_DELIVERIES));
+        let handler: UpdateHandler<()> = dptree::entry().filter(first_delivery).endpoint(
+            |handled: Arc<AtomicUsize>| async move {
+                handled.fetch_add(1, Ordering::SeqCst);
+                Ok(())
+            },
+        );
+
+        // telegram posts the update again when it got no answer in time
+        for _ in 0..2 {
+            let update: Update = serde_json::from_value(update.clone()).unwrap();
+            let _ = handler
+                .dispatch(dptree::deps![update, updates.clone(), handled.clone()])
+                .await;
+        }
+
+        assert_eq!(handled.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn recent_ids_forget_the_oldest() {
+        let ids = RecentIds::new(2);
+
+        assert!(ids.insert(1));
+        assert!(ids.insert(2));
+        assert!(!ids.insert(1));
+        assert!(ids.insert(3));
+        assert!(ids.insert(1));
+
+        ids.remove(&1);
+        assert!(ids.insert(1));
+    }
+}